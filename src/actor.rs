@@ -25,9 +25,9 @@ use std::collections::{HashMap, HashSet};
 use serde::{Serialize, Deserialize};
 
 use crate::dice;
-use crate::display::{DARK_BROWN, GREY, GREEN, BRIGHT_RED, BLUE, GOLD, YELLOW_ORANGE, WHITE};
+use crate::display::{DARK_BROWN, GREY, GREEN, BRIGHT_RED, BLUE, GOLD, LIGHT_BLUE, YELLOW_ORANGE, WHITE};
 use crate::display::GameUI;
-use crate::items::{Item, Inventory};
+use crate::items::{DmgType, Item, Inventory};
 use crate::map;
 use crate::map::Tile;
 use crate::pathfinding::find_path;
@@ -37,12 +37,43 @@ use crate::util::sqs_adj;
 
 use super::{do_ability_check, GameState};
 
+const MIN_EXERTED_STAMINA: u8 = 1;
+const PACK_ALERT_RADIUS: usize = 10;
+const BARRICADE_BASH_RANGE: usize = 12;
+
 #[derive(Debug,Serialize,Deserialize)]
 pub enum PirateType {
 	Swab,
 	Seadog,
 }
 
+// What the player got up to before washing up on the islands. It doesn't
+// touch their stats, just tops up the kit they start with, and each kit is
+// worth about the same.
+pub struct Background {
+	pub name: &'static str,
+	pub blurb: &'static str,
+	kit: &'static [(&'static str, u8)],
+}
+
+pub const BACKGROUNDS: [Background; 3] = [
+	Background { name: "Gunner", blurb: "Ye manned the guns and always carry spare shot.",
+		kit: &[("corroded flintlock", 1), ("lead ball", 12)] },
+	Background { name: "Brawler", blurb: "Ye would rather settle a score with yer fists.",
+		kit: &[("stout boots", 1), ("leather gloves", 1), ("draught of rum", 5)] },
+	Background { name: "Navigator", blurb: "Ye kept the charts and can read the sky and sea.",
+		kit: &[("compass", 1), ("spyglass", 1), ("barometer", 1), ("sextant", 1)] },
+];
+
+pub const THIRSTY: u16 = 1000;
+pub const PARCHED: u16 = 1500;
+pub const DEHYDRATED: u16 = 2000;
+// Three days without fruit brings on scurvy, which then gets worse every
+// half day until the player finds some
+pub const SCURVY_ONSET: u16 = 7200;
+pub const SCURVY_INTERVAL: u16 = 1200;
+pub const MAX_SCURVY: u8 = 10;
+
 #[derive(Debug,Serialize,Deserialize)]
 pub struct Player {
 	pub name: String,
@@ -65,6 +96,15 @@ pub struct Player {
 	pub poisoned: bool,
 	pub charmed: bool,
 	pub drunkeness: u8,
+	pub mount: Option<Monster>,
+	// Goes up every turn and only fresh water (or near enough) brings it down
+	#[serde(default)]
+	pub thirst: u16,
+	#[serde(default)]
+	pub since_fruit: u16,
+	// How much max stamina the scurvy has cost the player so far
+	#[serde(default)]
+	pub scurvy: u8,
 }
 
 impl Player {
@@ -96,6 +136,10 @@ impl Player {
 			poisoned: false,
 			charmed: false,
 			drunkeness: 0,
+			mount: None,
+			thirst: 0,
+			since_fruit: 0,
+			scurvy: 0,
 		};
 
 		p.inventory.add(Item::get_item("rusty cutlass").unwrap());
@@ -149,6 +193,10 @@ impl Player {
 			poisoned: false,
 			charmed: false,
 			drunkeness: 0,
+			mount: None,
+			thirst: 0,
+			since_fruit: 0,
+			scurvy: 0,
 		};
 
 		p.inventory.add(Item::get_item("rusty cutlass").unwrap());
@@ -175,6 +223,34 @@ impl Player {
 		p
 	}
 
+	// 0 is fine, 1 thirsty, 2 parched and 3 dying of thirst
+	pub fn thirst_level(&self) -> u8 {
+		if self.thirst >= DEHYDRATED {
+			3
+		} else if self.thirst >= PARCHED {
+			2
+		} else if self.thirst >= THIRSTY {
+			1
+		} else {
+			0
+		}
+	}
+
+	pub fn quench(&mut self, amount: u16) {
+		self.thirst = self.thirst.saturating_sub(amount);
+	}
+
+	pub fn cure_scurvy(&mut self) -> bool {
+		self.since_fruit = 0;
+		if self.scurvy == 0 {
+			return false;
+		}
+
+		self.max_stamina += self.scurvy;
+		self.scurvy = 0;
+		true
+	}
+
 	pub fn add_stamina(&mut self, stamina: u8) {
 		self.curr_stamina += stamina;
 		if self.curr_stamina > self.max_stamina {
@@ -182,6 +258,42 @@ impl Player {
 		}
 	}
 
+	// Strenuous actions (swinging a heavy blade, hauling the anchor) wear the
+	// pirate down but will never finish them off. A hardy constitution shrugs
+	// off some of the effort.
+	pub fn exert(&mut self, effort: u8) {
+		let cost = effort as i8 - Player::mod_for_stat(self.constitution);
+		if cost <= 0 || self.curr_stamina <= MIN_EXERTED_STAMINA {
+			return;
+		}
+
+		let cost = cost as u8;
+		self.curr_stamina = if self.curr_stamina > cost + MIN_EXERTED_STAMINA {
+			self.curr_stamina - cost
+		} else {
+			MIN_EXERTED_STAMINA
+		};
+	}
+
+	// Hand out the background's kit, putting on anything the player isn't 
+	// already wearing or wielding something of the same sort.
+	pub fn outfit(&mut self, background: &Background) {
+		for (name, count) in background.kit.iter() {
+			for _ in 0..*count {
+				self.inventory.add(Item::get_item(name).unwrap());
+			}
+
+			if let Some((_, slot)) = self.inventory.count_of_item(name) {
+				let item = self.inventory.peek_at(slot).unwrap();
+				if item.equipable() && !item.equiped {
+					self.inventory.toggle_slot(slot);
+				}
+			}
+		}
+
+		self.calc_ac();
+	}
+
 	pub fn calc_ac(&mut self) {
 		let mut total: i8 = 10;
 		total += self.inventory.total_armour_value();
@@ -231,19 +343,61 @@ impl Player {
 #[derive(Serialize, Deserialize)]
 pub struct NPCTracker {
     npc_id: usize,
+    #[serde(default)]
+    pack_id: usize,
     npc_list: HashMap<usize, Monster>,
     loc_index: HashMap<(usize, usize), usize>,
 }
 
 impl NPCTracker {
     pub fn new() -> NPCTracker {
-        NPCTracker { npc_id:0, npc_list: HashMap::new(), loc_index: HashMap::new() }
+        NPCTracker { npc_id:0, pack_id: 0, npc_list: HashMap::new(), loc_index: HashMap::new() }
+    }
+
+    // Packs are just a shared number on each member. 0 means the monster is
+    // a loner.
+    pub fn new_pack(&mut self) -> usize {
+        self.pack_id += 1;
+        self.pack_id
+    }
+
+    pub fn add_to_pack(&mut self, row: usize, col: usize, pack: usize) {
+        if let Some(id) = self.loc_index.get(&(row, col)) {
+            self.npc_list.get_mut(id).unwrap().pack = pack;
+        }
+    }
+
+    // When one member of a pack catches wind of the player, the rest of the
+    // pack nearby is roused too.
+    pub fn alert_pack(&mut self, pack: usize, row: usize, col: usize, radius: usize) {
+        for m in self.npc_list.values_mut() {
+            if m.pack == pack && util::cartesian_d(row, col, m.row, m.col) <= radius {
+                m.aware_of_player = true;
+            }
+        }
+    }
+
+    // A blaze at night brings any pirates in the area to investigate
+    pub fn drawn_to_fire(&mut self, row: usize, col: usize, radius: usize) {
+        for m in self.npc_list.values_mut() {
+            if m.npc_type == NPCType::MaroonedPirate && m.hostile
+                    && util::cartesian_d(row, col, m.row, m.col) <= radius {
+                m.aware_of_player = true;
+            }
+        }
     }
 
     pub fn is_npc_at(&self, row: usize, col: usize) -> bool {
         self.loc_index.contains_key(&(row, col))
     }
 
+	pub fn is_follower_at(&self, row: usize, col: usize) -> bool {
+		match self.loc_index.get(&(row, col)) {
+			Some(id) => self.npc_list[id].tame,
+			None => false,
+		}
+	}
+
 	pub fn is_boulder_at(&self, row: usize, col: usize) -> bool {
 		let loc = (row, col);
         if self.loc_index.contains_key(&loc) {
@@ -262,10 +416,22 @@ impl NPCTracker {
         ids
     }
 
+    pub fn type_at(&self, row: usize, col: usize) -> Option<NPCType> {
+        self.loc_index.get(&(row, col)).map(|id| self.npc_list[id].npc_type.clone())
+    }
+
     pub fn tile_info(&self, row: usize, col: usize) -> (char, (u8, u8, u8)) {
         let id = self.loc_index.get(&(row, col)).unwrap();
         let m = self.npc_list.get(&id).unwrap();
 
+        // Folks who could go either way (pirates, castaways and the like) get
+        // a red tint once they've turned hostile so the player can tell at a
+        // glance who is safe to parley with
+        if m.hostile && m.symbol == '@' {
+            let c = m.color;
+            return (m.symbol, (((c.0 as u16 + 255) / 2) as u8, c.1 / 2, c.2 / 2));
+        }
+
         (m.symbol, m.color)
     }
 
@@ -287,21 +453,61 @@ impl NPCTracker {
         None
     }
 
-    pub fn update(&mut self, m: Monster, prev_row: usize, prev_col: usize) {
+    // If another monster already holds the square m wants to move to, m
+    // stays where it was. Otherwise the second one to arrive would clobber
+    // the first's loc_index entry and leave it invisible on the map.
+    pub fn update(&mut self, mut m: Monster, prev_row: usize, prev_col: usize) {
         let id = m.id;
         if prev_row != m.row || prev_col != m.col {
             let loc = (m.row, m.col);
-            self.loc_index.remove(&(prev_row, prev_col));
-            self.loc_index.insert(loc, id);
+            match self.loc_index.get(&loc) {
+                Some(other) if *other != id => {
+                    m.row = prev_row;
+                    m.col = prev_col;
+                },
+                _ => {
+                    if self.loc_index.get(&(prev_row, prev_col)) == Some(&id) {
+                        self.loc_index.remove(&(prev_row, prev_col));
+                    }
+                    self.loc_index.insert(loc, id);
+                },
+            }
         }
         self.npc_list.insert(id, m);
     }
 
+    // Take in a monster from another map. Ids are only unique within a tracker
+    // so it gets a fresh one, and it no longer belongs to its old pack or boss.
+    pub fn adopt(&mut self, mut m: Monster, row: usize, col: usize) {
+        self.npc_id += 1;
+        m.id = self.npc_id;
+        m.row = row;
+        m.col = col;
+        m.pack = 0;
+        m.boss = 0;
+        self.add(m);
+    }
+
+    // Put back a monster that was taken off the map, like a mount the
+    // player has climbed down from
+    pub fn add(&mut self, m: Monster) {
+        self.loc_index.insert((m.row, m.col), m.id);
+        self.npc_list.insert(m.id, m);
+    }
+
     pub fn remove(&mut self, id: usize, row: usize, col: usize) {
         self.npc_list.remove(&id);
         self.loc_index.remove(&(row, col));
     }
 
+    // Clear out everyone within the rows and columns given (end exclusive)
+    pub fn clear_area(&mut self, start_r: usize, start_c: usize, end_r: usize, end_c: usize) {
+        let in_area = |loc: &(usize, usize)| loc.0 >= start_r && loc.0 < end_r
+            && loc.1 >= start_c && loc.1 < end_c;
+        self.npc_list.retain(|_, m| !in_area(&(m.row, m.col)));
+        self.loc_index.retain(|loc, _| !in_area(loc));
+    }
+
 	pub fn minion_killed(&mut self, boss_id: usize) {
 		if self.npc_list.contains_key(&boss_id) {
 			let mut boss = self.npc_list.get_mut(&boss_id).unwrap(); 
@@ -309,13 +515,28 @@ impl NPCTracker {
 		}
 	}
 
+	// It's the captain's will that keeps the crew on their feet, so once
+	// they're gone, so are the skeletons. Returns how many collapsed.
+	pub fn boss_killed(&mut self, boss_id: usize) -> usize {
+		let minions = self.npc_list.values()
+			.filter(|m| m.npc_type == NPCType::Skeleton && m.boss == boss_id)
+			.map(|m| (m.id, m.row, m.col))
+			.collect::<Vec<(usize, usize, usize)>>();
+
+		for m in minions.iter() {
+			self.remove(m.0, m.1, m.2);
+		}
+
+		minions.len()
+	}
+
 	pub fn new_merperson(&mut self, row: usize, col: usize) {
         self.npc_id += 1;
         let id = self.npc_id;
 		let hp = dice::roll(8, 2, 0);
 
-		let mut m = Monster::new(String::from("merperson"), id, NPCType::Merfolk, 13, hp, 'y', row, col, 
-			YELLOW_ORANGE, 5, 1, 1, 0, 10);
+		let mut m = Monster::new(String::from("merperson"), id, NPCType::Merfolk, 'y', YELLOW_ORANGE, (row, col),
+			MonsterStats { ac: 13, hp, hit_bonus: 5, dmg: 1, dmg_dice: 1, dmg_bonus: 0, score: 10 });
 
 		m.aware_of_player = true; // they keep their eyes out for sailors
 
@@ -337,9 +558,12 @@ impl NPCTracker {
         let id = self.npc_id;
 		let hp = dice::roll(6, 2, 1);
 
-		let mut s = Monster::new(String::from("skeletal pirate"), id, NPCType::Skeleton, 13, hp, 'Z', row, col, 
-			WHITE, 4, 0, 0, 0, 5);
+		let mut s = Monster::new(String::from("skeletal pirate"), id, NPCType::Skeleton, 'Z', WHITE, (row, col),
+			MonsterStats { ac: 13, hp, hit_bonus: 4, dmg: 0, dmg_dice: 0, dmg_bonus: 0, score: 5 });
         s.boss = boss_id;
+		if rand::thread_rng().gen_range(0.0, 1.0) < 0.2 {
+			s.gear.push(Item::get_item("rusty cutlass").unwrap());
+		}
 
         self.npc_list.insert(id, s);
         self.loc_index.insert((row, col), id);
@@ -350,9 +574,12 @@ impl NPCTracker {
         let id = self.npc_id;
 		let hp = dice::roll(6, 4, 0);
 
-		let mut s = Monster::new(String::from("undead pirate captain"), id, NPCType::UndeadCaptain, 14, hp, 'Z', row, col, 
-			BRIGHT_RED, 5, 8, 1, 0, 15);
+		let mut s = Monster::new(String::from("undead pirate captain"), id, NPCType::UndeadCaptain, 'Z', BRIGHT_RED, (row, col),
+			MonsterStats { ac: 14, hp, hit_bonus: 5, dmg: 8, dmg_dice: 1, dmg_bonus: 0, score: 15 });
         s.minions = initial_minion_count;
+		s.gear.push(Item::get_item("rusty cutlass").unwrap());
+		s.gear.push(Item::get_item("battered tricorn").unwrap());
+		s.gear.push(Item::get_item("overcoat").unwrap());
 
         self.npc_list.insert(id, s);
         self.loc_index.insert((row, col), id);
@@ -365,9 +592,10 @@ impl NPCTracker {
         let id = self.npc_id;
 		let hp = dice::roll(8, 2, 0);
 
-		let mut p = Monster::new(String::from("marooned pirate"), id, NPCType::MaroonedPirate, 14, hp, '@', row, col, 
-			GREY, 5, 6, 1, 0, 10);
+		let mut p = Monster::new(String::from("marooned pirate"), id, NPCType::MaroonedPirate, '@', GREY, (row, col),
+			MonsterStats { ac: 14, hp, hit_bonus: 5, dmg: 6, dmg_dice: 1, dmg_bonus: 0, score: 10 });
 		p.anchor = anchor;
+		p.gear = Monster::pirate_gear();
 
 		let roll = rand::thread_rng().gen_range(0.0, 1.0);
 		if roll < 0.33 {
@@ -380,14 +608,57 @@ impl NPCTracker {
         self.loc_index.insert((row, col), id);
 	}
 
+	// A merchant ship's crew will fight to keep pirates off her deck
+	pub fn new_sailor(&mut self, row: usize, col: usize) {
+        self.npc_id += 1;
+        let id = self.npc_id;
+		let hp = dice::roll(8, 2, 0);
+
+		let mut s = Monster::new(String::from("merchant sailor"), id, NPCType::Sailor, '@', WHITE, (row, col),
+			MonsterStats { ac: 13, hp, hit_bonus: 4, dmg: 6, dmg_dice: 1, dmg_bonus: 0, score: 10 });
+		s.anchor = (row, col);
+		s.aware_of_player = true;
+		if rand::thread_rng().gen_range(0.0, 1.0) < 0.5 {
+			s.gear.push(Item::get_item("rusty cutlass").unwrap());
+		}
+
+		let roll = rand::thread_rng().gen_range(0.0, 1.0);
+		if roll < 0.33 {
+			s.gender = 1;
+		} else if roll < 0.66 {
+			s.gender = 2;
+		};
+		
+        self.npc_list.insert(id, s);
+        self.loc_index.insert((row, col), id);
+	}
+
 	fn get_item_for_sale() -> Option<Item> {
-		let roll = rand::thread_rng().gen_range(0, 3);
+		let roll = rand::thread_rng().gen_range(0, 12);
 		if roll == 0 {
 			return Item::get_item("flintlock pistol");
 		} else if roll == 1 {
 			return Item::get_item("lantern");
 		} else if roll == 2 {
 			return Item::get_item("stout boots");
+		} else if roll == 3 {
+			return Item::get_item("leather gloves");
+		} else if roll == 4 {
+			return Item::get_item("spyglass");
+		} else if roll == 5 {
+			return Item::get_item("compass");
+		} else if roll == 6 {
+			return Item::get_item("lead ball");
+		} else if roll == 7 {
+			return Item::get_item("flask of oil");
+		} else if roll == 8 {
+			return Item::get_item("fetish");
+		} else if roll == 9 {
+			return Item::get_item("barometer");
+		} else if roll == 10 {
+			return Item::get_item("shovel");
+		} else if roll == 11 {
+			return Item::get_item("wax earplugs");
 		}
 
 		None
@@ -398,8 +669,8 @@ impl NPCTracker {
         let id = self.npc_id;
 		let hp = dice::roll(8, 1, 0);
 
-		let mut c = Monster::new(String::from("castaway"), id, NPCType::Castaway, 10, hp, '@', row, col, 
-			GREY, 3, 6, 1, 0, 0);
+		let mut c = Monster::new(String::from("castaway"), id, NPCType::Castaway, '@', GREY, (row, col),
+			MonsterStats { ac: 10, hp, hit_bonus: 3, dmg: 6, dmg_dice: 1, dmg_bonus: 0, score: 0 });
 		c.anchor = anchor;
         c.voice_line = String::from(voice_line);
 
@@ -414,12 +685,22 @@ impl NPCTracker {
 		// some castaways have an item to sell
 		//if rand:;thread_rng().gen_range(0.0, 1.0) < 0.33 {
 			c.for_sale = NPCTracker::get_item_for_sale();
-			c.for_sale = Item::get_item("fetish");
-			if rand::thread_rng().gen_range(0, 2) == 0 {
-				// 0 is price is in doubloons, otherwise rum	
-				c.price = (0, rand::thread_rng().gen_range(3, 6));
+			let stackable = match &c.for_sale {
+				Some(i) => i.stackable,
+				None => false,
+			};
+			if stackable {
+				// Odds and ends like shot and oil are sold by the each, cheaply
+				c.stock = rand::thread_rng().gen_range(8, 21);
+				c.price = (0, rand::thread_rng().gen_range(1, 3));
 			} else {
-				c.price = (1, rand::thread_rng().gen_range(4, 8));
+				c.stock = 1;
+				if rand::thread_rng().gen_range(0, 2) == 0 {
+					// 0 is price is in doubloons, otherwise rum	
+					c.price = (0, rand::thread_rng().gen_range(3, 6));
+				} else {
+					c.price = (1, rand::thread_rng().gen_range(4, 8));
+				}
 			}
 		//}
 
@@ -427,6 +708,42 @@ impl NPCTracker {
         self.loc_index.insert((row, col), id);
 	}
 	
+	// An old sea dog retired to one of the forts. For a price they'll share
+	// what they know of the Strait.
+	pub fn new_navigator(&mut self, row: usize, col: usize) {
+        self.npc_id += 1;
+        let id = self.npc_id;
+		let hp = dice::roll(8, 1, 0);
+
+		let mut n = Monster::new(String::from("navigator"), id, NPCType::Castaway, '@', LIGHT_BLUE, (row, col),
+			MonsterStats { ac: 10, hp, hit_bonus: 3, dmg: 6, dmg_dice: 1, dmg_bonus: 0, score: 0 });
+		n.anchor = (row, col);
+		n.voice_line = String::from("I've charted every reef and shoal in these waters.");
+		n.hostile = false;
+		n.navigator = true;
+
+        self.npc_list.insert(id, n);
+        self.loc_index.insert((row, col), id);
+	}
+
+	// The same sea witch who enchanted the eye patch, or so she claims. She
+	// can be talked into blessing the player's gear.
+	pub fn new_sea_witch(&mut self, row: usize, col: usize) {
+        self.npc_id += 1;
+        let id = self.npc_id;
+		let hp = dice::roll(8, 2, 0);
+
+		let mut w = Monster::new(String::from("sea witch"), id, NPCType::Castaway, '@', GREEN, (row, col),
+			MonsterStats { ac: 12, hp, hit_bonus: 3, dmg: 6, dmg_dice: 1, dmg_bonus: 0, score: 0 });
+		w.anchor = (row, col);
+		w.voice_line = String::from("The tides whisper to me, dearie.");
+		w.hostile = false;
+		w.sea_witch = true;
+
+        self.npc_list.insert(id, w);
+        self.loc_index.insert((row, col), id);
+	}
+
 	pub fn new_snake(&mut self, row: usize, col: usize) {
         self.npc_id += 1;
         let id = self.npc_id;
@@ -441,8 +758,8 @@ impl NPCTracker {
 			GREEN 
 		};
 		
-		let mut s = Monster::new(String::from("snake"), id, NPCType::Snake, 14, hp, 'S', row, col, 
-			colour, 4, 4, 1, 0, 10);
+		let mut s = Monster::new(String::from("snake"), id, NPCType::Snake, 'S', colour, (row, col),
+			MonsterStats { ac: 14, hp, hit_bonus: 4, dmg: 4, dmg_dice: 1, dmg_bonus: 0, score: 10 });
 		s.special_dmg = String::from("poison");
 
         self.npc_list.insert(id, s);
@@ -454,8 +771,8 @@ impl NPCTracker {
         let id = self.npc_id;
 		let hp = dice::roll(6, 3, 0);
 		
-        let s = Monster::new(String::from("shark"), id, NPCType::Shark, 12, hp, '^', row, col, 
-			GREY, 4, 8, 1, 2, 10);
+        let s = Monster::new(String::from("shark"), id, NPCType::Shark, '^', GREY, (row, col),
+			MonsterStats { ac: 12, hp, hit_bonus: 4, dmg: 8, dmg_dice: 1, dmg_bonus: 2, score: 10 });
 
         self.npc_list.insert(id, s);
         self.loc_index.insert((row, col), id);
@@ -465,8 +782,8 @@ impl NPCTracker {
         self.npc_id += 1;
         let id = self.npc_id;
 		let hp = dice::roll(8, 4, 0);
-		let mut p = Monster::new(String::from("panther"), id, NPCType::Panther, 12, hp, 'f', row, col, 
-			BLUE, 5, 12, 1, 2, 10);
+		let mut p = Monster::new(String::from("panther"), id, NPCType::Panther, 'f', BLUE, (row, col),
+			MonsterStats { ac: 12, hp, hit_bonus: 5, dmg: 12, dmg_dice: 1, dmg_bonus: 2, score: 10 });
 
 		p.aware_of_player = true; // always on the hunt
 
@@ -478,8 +795,8 @@ impl NPCTracker {
         self.npc_id += 1;
         let id = self.npc_id;
 		let hp = dice::roll(5, 2, 0);
-		let b = Monster::new(String::from("wild boar"), id, NPCType::Boar, 12, hp, 'b', row, col, 
-			DARK_BROWN, 4, 6, 1, 2, 5);
+		let b = Monster::new(String::from("wild boar"), id, NPCType::Boar, 'b', DARK_BROWN, (row, col),
+			MonsterStats { ac: 12, hp, hit_bonus: 4, dmg: 6, dmg_dice: 1, dmg_bonus: 2, score: 5 });
 
         self.npc_list.insert(id, b);
         self.loc_index.insert((row, col), id);
@@ -489,8 +806,8 @@ impl NPCTracker {
         self.npc_id += 1;
         let id = self.npc_id;
 		let hp = dice::roll(5, 1, 0);
-		let b = Monster::new(String::from("rat"), id, NPCType::Rat, 12, hp, 'r', row, col, 
-			DARK_BROWN, 3, 3, 1, 0, 5);
+		let b = Monster::new(String::from("rat"), id, NPCType::Rat, 'r', DARK_BROWN, (row, col),
+			MonsterStats { ac: 12, hp, hit_bonus: 3, dmg: 3, dmg_dice: 1, dmg_bonus: 0, score: 5 });
 
         self.npc_list.insert(id, b);
         self.loc_index.insert((row, col), id);
@@ -500,15 +817,15 @@ impl NPCTracker {
         self.npc_id += 1;
         let id = self.npc_id;
 		
-        let mut b = Monster::new(String::from("boulder"), id, NPCType::Boulder, 99, 255, '0', row, col, 
-			WHITE, 4, 8, 1, 2, 10);
+        let mut b = Monster::new(String::from("boulder"), id, NPCType::Boulder, '0', WHITE, (row, col),
+			MonsterStats { ac: 99, hp: 255, hit_bonus: 4, dmg: 8, dmg_dice: 1, dmg_bonus: 2, score: 10 });
 		b.dir = dir;
         self.npc_list.insert(id, b);
         self.loc_index.insert((row, col), id);
 	}
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum NPCType {
 	Boar,
 	Shark,
@@ -519,10 +836,77 @@ pub enum NPCType {
 	Merfolk,
 	MaroonedPirate,
 	Castaway,
+	Sailor,
 	Rat,
 	Boulder, /* Work with me on this one... */
 }
 
+impl NPCType {
+	pub fn approach_verb(&self) -> &str {
+		match self {
+			NPCType::Boar => "charges",
+			NPCType::Shark => "cuts through the water",
+			NPCType::Snake => "slithers",
+			NPCType::Panther => "stalks",
+			NPCType::Skeleton | NPCType::UndeadCaptain => "shambles",
+			NPCType::Merfolk => "swims",
+			NPCType::Rat => "scurries",
+			NPCType::Boulder => "rolls",
+			_ => "heads",
+		}
+	}
+
+	// The player's notes on a creature for the bestiary: its symbol, 
+	// its colour, and a few words about it
+	pub fn bestiary_entry(&self) -> (char, &str, &str, &str) {
+		match self {
+			NPCType::Boar => ('b', "wild boar", "dark brown", "Bad-tempered and fond of carrion."),
+			NPCType::Shark => ('^', "shark", "grey", "Keep out of deep water or keep moving."),
+			NPCType::Snake => ('S', "snake", "varies", "Its bite is venomous."),
+			NPCType::Panther => ('f', "panther", "blue", "Stalks the jungle interiors of islands."),
+			NPCType::Skeleton => ('Z', "skeletal pirate", "white", "Raised by a captain who won't stay dead."),
+			NPCType::UndeadCaptain => ('Z', "undead pirate captain", "red", "Sink it and its crew falls apart."),
+			NPCType::Merfolk => ('y', "merperson", "orange", "Its song can charm a sailor."),
+			NPCType::MaroonedPirate => ('@', "marooned pirate", "grey", "Left to rot and bitter about it."),
+			NPCType::Castaway => ('@', "castaway", "grey", "Harmless, and sometimes has goods to trade."),
+			NPCType::Sailor => ('@', "merchant sailor", "white", "Crews the trading ships. Hates boarders."),
+			NPCType::Rat => ('r', "rat", "dark brown", "Vermin. Will eat anything, including you."),
+			NPCType::Boulder => ('0', "boulder", "white", "Get out of its way."),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resistance {
+	Normal,
+	Resists,
+	Vulnerable,
+	Immune,
+}
+
+impl Resistance {
+	pub fn adjust(&self, dmg: u8) -> u8 {
+		match self {
+			Resistance::Normal => dmg,
+			Resistance::Resists => dmg / 2,
+			Resistance::Vulnerable => dmg + dmg / 2,
+			Resistance::Immune => 0,
+		}
+	}
+}
+
+// The fighting numbers for a new monster, to keep Monster::new() from
+// growing an endless list of u8s
+pub struct MonsterStats {
+	pub ac: u8,
+	pub hp: u8,
+	pub hit_bonus: i8,
+	pub dmg: u8,
+	pub dmg_dice: u8,
+	pub dmg_bonus: u8,
+	pub score: u8,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Monster {
 	pub name: String,
@@ -530,6 +914,8 @@ pub struct Monster {
 	pub npc_type: NPCType,
 	pub ac: u8,
 	pub hp: u8,
+	#[serde(default)]
+	pub max_hp: u8,
 	pub symbol: char,
 	pub row: usize,
 	pub col: usize,
@@ -551,27 +937,58 @@ pub struct Monster {
 	pub killed: bool,
 	pub for_sale: Option<Item>,
 	pub price: (u8, u8),
+	#[serde(default)]
+	pub stock: u8,
+	#[serde(default)]
+	pub pack: usize,
+	#[serde(default)]
+	pub tame: bool,
+	#[serde(default)]
+	pub navigator: bool,
+	#[serde(default)]
+	pub sea_witch: bool,
+	// What they're carrying into the fight, which is left behind when they die
+	#[serde(default)]
+	pub gear: Vec<Item>,
+	// Winged in the legs, so they lose their next move
+	#[serde(default)]
+	pub slowed: bool,
 }
 
 impl Monster {
-	pub fn new(name: String, id: usize, npc_type: NPCType, ac:u8, hp: u8, symbol: char, row: usize, col: usize, 
-			color: (u8, u8, u8), hit_bonus: i8, dmg: u8, dmg_dice: u8, dmg_bonus: u8, score: u8) -> Monster {
-		Monster { name, id, npc_type, ac, hp, symbol, row, col, color, hit_bonus, 
-			dmg, dmg_dice, dmg_bonus, special_dmg: String::from(""),
-			gender: 0, anchor: (0, 0), score, aware_of_player: false, hostile: true,
+	pub fn new(name: String, id: usize, npc_type: NPCType, symbol: char, color: (u8, u8, u8),
+			loc: (usize, usize), stats: MonsterStats) -> Monster {
+		Monster { name, id, npc_type, ac: stats.ac, hp: stats.hp, max_hp: stats.hp, symbol, 
+			row: loc.0, col: loc.1, color, hit_bonus: stats.hit_bonus, dmg: stats.dmg,
+			dmg_dice: stats.dmg_dice, dmg_bonus: stats.dmg_bonus, special_dmg: String::from(""),
+			gender: 0, anchor: (0, 0), score: stats.score, aware_of_player: false, hostile: true,
 			voice_line: String::from(""), minions: 0, boss: 0 , dir: (0, 0),
-			killed: false, for_sale: None, price: (0, 0) }
+			killed: false, for_sale: None, price: (0, 0), stock: 0, pack: 0, tame: false,
+			navigator: false, sea_witch: false, gear: Vec::new(), slowed: false }
 	}
 
 	// I'm sure life doesn't need to be this way, but got to figure out the
 	// Rust polymorphism model
 	pub fn act(&mut self, state: &mut GameState, ships: &HashMap<(usize, usize), Ship>) 
 											-> Result<(), super::ExitReason> {
+		if self.slowed {
+			self.slowed = false;
+			return Ok(());
+		}
+
 		match self.npc_type {
 			NPCType::Shark => shark_action(self, state, ships)?,
-			NPCType::MaroonedPirate => pirate_action(self, state, ships)?,
+			NPCType::MaroonedPirate => {
+				let night = state.is_night();
+				pirate_action(self, state, ships, night)?
+			},
 			NPCType::Merfolk => merfolk_action(self, state)?,
-			NPCType::Castaway => castaway_action(self, state, ships)?,
+			NPCType::Sailor => sailor_action(self, state)?,
+			NPCType::Castaway => {
+				let night = state.is_night();
+				castaway_action(self, state, ships, night)?
+			},
+			NPCType::Boar if self.tame => follower_action(self, state, ships)?,
 			NPCType::Boar => basic_monster_action(self, state, ships, "gores")?,
 			NPCType::Skeleton => basic_undead_action(self, state, ships)?,
 			NPCType::UndeadCaptain => undead_boss_action(self, state, ships)?,
@@ -589,6 +1006,7 @@ impl Monster {
 					NPCType::Skeleton => state.write_msg_buff("No response."),
 			NPCType::MaroonedPirate | NPCType::UndeadCaptain |
 				NPCType::Castaway => state.write_msg_buff(&get_pirate_line()),
+			NPCType::Sailor => state.write_msg_buff("Keep off our deck, ye bilge rat!"),
 			NPCType::Boar | NPCType::Panther => {
 				let s = format!("The {} growls at you!", self.name);
 				state.write_msg_buff(&s);
@@ -599,6 +1017,43 @@ impl Monster {
 		}
 	}
 
+	// A rough idea of how the fight is going without giving away numbers.
+	// (Monsters from saves made before max_hp was tracked have it as 0, so
+	// the best we can say about them is they're still standing)
+	pub fn health_desc(&self) -> &'static str {
+		if self.max_hp == 0 || self.hp >= self.max_hp {
+			return "unhurt";
+		}
+
+		let pct = self.hp as u32 * 100 / self.max_hp as u32;
+		if pct >= 75 {
+			"lightly wounded"
+		} else if pct >= 50 {
+			"wounded"
+		} else if pct >= 25 {
+			"badly wounded"
+		} else {
+			"nearly dead"
+		}
+	}
+
+	// Saves from before merchants kept count of their stock have 0 for one
+	// with goods on offer, which really means they've just the one to sell
+	pub fn stock_left(&self) -> u8 {
+		if self.for_sale.is_some() {
+			u8::max(self.stock, 1)
+		} else {
+			self.stock
+		}
+	}
+
+	pub fn sold(&mut self, quantity: u8) {
+		self.stock = self.stock_left().saturating_sub(quantity);
+		if self.stock == 0 {
+			self.for_sale = None;
+		}
+	}
+
 	pub fn is_merchant(&self) -> bool {
 		if let Some(_) = self.for_sale {
 			true
@@ -607,11 +1062,51 @@ impl Monster {
 		}
 	}
 
+	// Bullets mostly whistle through a skeleton's ribs but a good clout will 
+	// shatter them, and there's not much that'll dent a boulder.
+	pub fn resistance(&self, dmg_type: DmgType) -> Resistance {
+		match (&self.npc_type, dmg_type) {
+			(NPCType::Skeleton, DmgType::Piercing) | (NPCType::UndeadCaptain, DmgType::Piercing) => Resistance::Resists,
+			(NPCType::Skeleton, DmgType::Blunt) | (NPCType::UndeadCaptain, DmgType::Blunt) => Resistance::Vulnerable,
+			(NPCType::Boulder, DmgType::Blunt) => Resistance::Resists,
+			(NPCType::Boulder, _) => Resistance::Immune,
+			(NPCType::Panther, DmgType::Fire) | (NPCType::Boar, DmgType::Fire) 
+				| (NPCType::Rat, DmgType::Fire) => Resistance::Vulnerable,
+			_ => Resistance::Normal,
+		}
+	}
+
+	// The undead just collapse into a heap of bones and boulders are, well, boulders
+	pub fn leaves_corpse(&self) -> bool {
+		!matches!(self.npc_type, NPCType::Skeleton | NPCType::UndeadCaptain | NPCType::Boulder)
+	}
+
+	// Marooned pirates have held on to whatever they could. Mostly a blade 
+	// but the odd one has kept their pistol.
+	fn pirate_gear() -> Vec<Item> {
+		let mut gear = Vec::new();
+		let roll = rand::thread_rng().gen_range(0.0, 1.0);
+		if roll < 0.15 {
+			gear.push(Item::get_item("corroded flintlock").unwrap());
+		} else if roll < 0.75 {
+			gear.push(Item::get_item("rusty cutlass").unwrap());
+		}
+
+		if rand::thread_rng().gen_range(0.0, 1.0) < 0.3 {
+			gear.push(Item::get_item("battered tricorn").unwrap());
+		}
+		if rand::thread_rng().gen_range(0.0, 1.0) < 0.2 {
+			gear.push(Item::get_item("leather jerkin").unwrap());
+		}
+
+		gear
+	}
+
 	pub fn treasure_drop(&self) -> Vec<Item> {
 		let mut treasure = Vec::new();
 
 		match self.npc_type {
-			NPCType::MaroonedPirate | NPCType::UndeadCaptain => {
+			NPCType::MaroonedPirate | NPCType::UndeadCaptain | NPCType::Sailor => {
 				for _ in 0..rand::thread_rng().gen_range(0, 6) {
 					treasure.push(Item::get_item("doubloon").unwrap());
 				}
@@ -632,6 +1127,11 @@ impl Monster {
 			},
 			_ => { },
 		}
+
+		// A proper captain's hat, for the pirate who's bested one
+		if self.npc_type == NPCType::UndeadCaptain {
+			treasure.push(Item::get_item("captain's tricorn").unwrap());
+		}
 		
 		treasure
 	}
@@ -664,6 +1164,75 @@ fn find_adj_empty_sq(row: i32, col: i32, state: &GameState,
 	}
 }
 
+// A monster that can't get any closer to the player will have a go at any
+// barricade standing between them.
+fn bash_barricade(m: &Monster, state: &mut GameState) {
+	let dis = util::cartesian_d(m.row, m.col, state.player.row, state.player.col);
+	if dis > BARRICADE_BASH_RANGE {
+		return;
+	}
+
+	for r in -1..=1 {
+		for c in -1..=1 {
+			if r == 0 && c == 0 { continue; }
+			let adj_r = m.row as i32 + r;
+			let adj_c = m.col as i32 + c;
+			if !map::in_bounds(&state.map[&state.map_id], adj_r, adj_c) { continue; }
+
+			let loc = (adj_r as usize, adj_c as usize);
+			let hp = match state.map[&state.map_id][loc.0][loc.1] {
+				Tile::Barricade(hp) => hp,
+				_ => continue,
+			};
+			if util::cartesian_d(loc.0, loc.1, state.player.row, state.player.col) >= dis {
+				continue;
+			}
+
+			let dmg = dice::roll(2, 1, 0);
+			if dmg >= hp {
+				let under = state.barricaded.remove(&(state.map_id, loc.0, loc.1)).unwrap_or(Tile::Floor);
+				state.map.get_mut(&state.map_id).unwrap()[loc.0][loc.1] = under;
+				let s = format!("The {} smashes through the barricade!", m.name);
+				state.write_msg_buff(&s);
+			} else {
+				state.map.get_mut(&state.map_id).unwrap()[loc.0][loc.1] = Tile::Barricade(hp - dmg);
+				state.write_msg_buff("You hear something pounding on a barricade.");
+			}
+
+			return;
+		}
+	}
+}
+
+// Find an open square next to the monster that gets it no further from the
+// player, preferring ones that get it closer.
+fn flanking_sq(m: &Monster, state: &GameState, ships: &HashMap<(usize, usize), Ship>,
+				passable: &HashSet<map::Tile>) -> Option<(usize, usize)> {
+	let dis = util::cartesian_d(m.row, m.col, state.player.row, state.player.col);
+	let mut best = None;
+	let mut best_d = dis;
+
+	for r in -1..=1 {
+		for c in -1..=1 {
+			if r == 0 && c == 0 { continue; }
+			let adj_r = m.row as i32 + r;
+			let adj_c = m.col as i32 + c;
+
+			if !map::in_bounds(&state.map[&state.map_id], adj_r, adj_c) { continue; }
+			if !passable.contains(&state.map[&state.map_id][adj_r as usize][adj_c as usize]) { continue; }
+			if !super::sq_is_open(state, ships, adj_r as usize, adj_c as usize) { continue; }
+
+			let d = util::cartesian_d(adj_r as usize, adj_c as usize, state.player.row, state.player.col);
+			if d <= best_d {
+				best_d = d;
+				best = Some((adj_r as usize, adj_c as usize));
+			}
+		}
+	}
+
+	best
+}
+
 fn do_special_dmg(state: &mut GameState, special_dmg: &str) {
 	if special_dmg == "poison" {
 		let con_mod = Player::mod_for_stat(state.player.constitution);
@@ -680,13 +1249,17 @@ fn stealth_check(state: &mut GameState, m: &mut Monster) {
 		m.aware_of_player = true;
         
         match m.npc_type {
-            NPCType::MaroonedPirate => state.write_msg_buff("You hear a shout."),
+            NPCType::MaroonedPirate | NPCType::Sailor => state.write_msg_buff("You hear a shout."),
 	        NPCType::Boar | NPCType::Panther => state.write_msg_buff("Something snarls."),
             NPCType::Skeleton => state.write_msg_buff("You hear rattling bones."),
             NPCType::Snake => state.write_msg_buff("You hear a hiss."),
             NPCType::Merfolk => state.write_msg_buff("You hear a splash."),
             _ => { /* no sound alert */ },
         }
+
+		if m.pack > 0 {
+			state.npcs.get_mut(&state.map_id).unwrap().alert_pack(m.pack, m.row, m.col, PACK_ALERT_RADIUS);
+		}
 	}
 }
 
@@ -726,7 +1299,9 @@ fn undead_boss_action(m: &mut Monster, state: &mut GameState,
 		passable.insert(map::Tile::Sand);
 		passable.insert(map::Tile::Tree);
 		passable.insert(map::Tile::Floor);
+		passable.insert(map::Tile::StoneFloor);
 		passable.insert(map::Tile::Water);
+		passable.insert(map::Tile::Reef);
 
 		let loc = find_adj_empty_sq(m.row as i32, m.col as i32, state, ships, &passable);
 		m.row = loc.0;
@@ -792,7 +1367,9 @@ fn basic_undead_action(m: &mut Monster, state: &mut GameState,
 			passable.insert(map::Tile::Sand);
 			passable.insert(map::Tile::Tree);
 			passable.insert(map::Tile::Floor);
+			passable.insert(map::Tile::StoneFloor);
 			passable.insert(map::Tile::Water);
+			passable.insert(map::Tile::Reef);
 
 			let path = find_path(state, m.row, m.col, 
 				state.player.row, state.player.col, &passable, ships);
@@ -807,6 +1384,8 @@ fn basic_undead_action(m: &mut Monster, state: &mut GameState,
 
 				m.row = new_loc.0;
 				m.col = new_loc.1;
+			} else {
+				bash_barricade(m, state);
 			}
 		}
 	}
@@ -814,9 +1393,53 @@ fn basic_undead_action(m: &mut Monster, state: &mut GameState,
 	Ok(())
 }
 
+// Wild animals want no part of a signal fire and will back away from it
+fn shy_of_fire(m: &mut Monster, state: &GameState, ships: &HashMap<(usize, usize), Ship>) -> bool {
+	if m.npc_type != NPCType::Boar && m.npc_type != NPCType::Panther {
+		return false;
+	}
+
+	let fire = match state.signal_fire {
+		Some((map_id, r, c, _)) if map_id == state.map_id => (r, c),
+		_ => return false,
+	};
+
+	let d = util::cartesian_d(m.row, m.col, fire.0, fire.1);
+	if d > 8 {
+		return false;
+	}
+
+	let mut best = (m.row, m.col);
+	let mut best_d = d;
+	for r in -1..=1 {
+		for c in -1..=1 {
+			let nr = m.row as i32 + r;
+			let nc = m.col as i32 + c;
+			if !map::in_bounds(&state.map[&state.map_id], nr, nc) { continue; }
+			let tile = &state.map[&state.map_id][nr as usize][nc as usize];
+			if !map::is_passable(tile) || !map::is_land(tile) || *tile == Tile::FirePit || *tile == Tile::Lava { continue; }
+			if !super::sq_is_open(state, ships, nr as usize, nc as usize) { continue; }
+			let nd = util::cartesian_d(nr as usize, nc as usize, fire.0, fire.1);
+			if nd > best_d {
+				best_d = nd;
+				best = (nr as usize, nc as usize);
+			}
+		}
+	}
+
+	m.row = best.0;
+	m.col = best.1;
+
+	true
+}
+
 fn basic_monster_action(m: &mut Monster, state: &mut GameState,
 							ships: &HashMap<(usize, usize), Ship>,
 							verb: &str) -> Result<(), super::ExitReason> {
+	if shy_of_fire(m, state, ships) {
+		return Ok(());
+	}
+
 	if m.aware_of_player && sqs_adj(m.row, m.col, state.player.row, state.player.col) && !state.player.on_ship {
 		if super::attack_player(state, m) {
 			let s = format!("The {} {} you!", m.name, verb);
@@ -844,7 +1467,27 @@ fn basic_monster_action(m: &mut Monster, state: &mut GameState,
 	passable.insert(map::Tile::StoneFloor);
 
 	let dis = util::cartesian_d(m.row, m.col, state.player.row, state.player.col);
-	
+
+	// Boars and rats are scavengers and will go for an easy meal over the player
+	if m.npc_type == NPCType::Boar || m.npc_type == NPCType::Rat {
+		if let Some((loc, corpse_d)) = state.nearest_corpse(m.row, m.col) {
+			if corpse_d < 10 && corpse_d < dis {
+				if corpse_d > 1 {
+					let path = find_path(state, m.row, m.col, loc.0, loc.1, &passable, ships);
+					if path.len() > 1 && !state.npcs[&state.map_id].is_npc_at(path[1].0, path[1].1) {
+						m.row = path[1].0;
+						m.col = path[1].1;
+					}
+				} else if dis < 8 {
+					let s = format!("The {} is gnawing on a corpse.", m.name);
+					state.write_msg_buff(&s);
+				}
+
+				return Ok(());
+			}
+		}
+	}
+
 	if dis > 20 {
 		let loc = find_adj_empty_sq(m.row as i32, m.col as i32, state, ships, &passable);
 		m.row = loc.0;
@@ -862,6 +1505,16 @@ fn basic_monster_action(m: &mut Monster, state: &mut GameState,
 		if path.len() > 1 {
 			let new_loc = path[1];
 			if state.npcs[&state.map_id].is_npc_at(new_loc.0, new_loc.1) {
+				// Pack hunters will try to work their way around a packmate
+				// rather than queue up behind it
+				if m.pack > 0 {
+					if let Some(loc) = flanking_sq(m, state, ships, &passable) {
+						m.row = loc.0;
+						m.col = loc.1;
+						return Ok(());
+					}
+				}
+
 				let s = format!("The {} is blocked.", m.name);
 				state.write_msg_buff(&s);
 				return Ok(());
@@ -869,17 +1522,70 @@ fn basic_monster_action(m: &mut Monster, state: &mut GameState,
 
 			m.row = new_loc.0;
 			m.col = new_loc.1;
+		} else if m.aware_of_player {
+			bash_barricade(m, state);
 		}
 	}
 
 	Ok(())
 }
 
-fn castaway_action(m: &mut Monster, state: &mut GameState,
+// A tamed animal trots along after the player, keeping close by
+fn follower_action(m: &mut Monster, state: &mut GameState,
 					ships: &HashMap<(usize, usize), Ship>) -> Result<(), super::ExitReason> {
+	// If the player's aboard ship, a follower close enough to her bow or stern
+	// will climb on and then stay put as crew
+	if state.player.on_ship {
+		if let Some(ship) = ships.get(&(state.player.row, state.player.col)) {
+			if ship.occupies(m.row, m.col) {
+				return Ok(());
+			}
+
+			let decks = [(ship.bow_row, ship.bow_col), (ship.aft_row, ship.aft_col)];
+			for loc in decks.iter() {
+				if util::cartesian_d(m.row, m.col, loc.0, loc.1) < 2
+						&& !state.npcs[&state.map_id].is_npc_at(loc.0, loc.1) {
+					m.row = loc.0;
+					m.col = loc.1;
+					let s = format!("The {} clambers aboard.", m.name);
+					state.write_msg_buff(&s);
+					return Ok(());
+				}
+			}
+		}
+	}
+
+	let dis = util::cartesian_d(m.row, m.col, state.player.row, state.player.col);
+	if dis <= 2 || dis > 30 {
+		return Ok(());
+	}
+
+	let mut passable = HashSet::new();
+	passable.insert(map::Tile::Dirt);
+	passable.insert(map::Tile::Grass);
+	passable.insert(map::Tile::Sand);
+	passable.insert(map::Tile::Tree);
+	passable.insert(map::Tile::Floor);
+	passable.insert(map::Tile::StoneFloor);
+	passable.insert(map::Tile::Water);
+
+	let path = find_path(state, m.row, m.col, 
+		state.player.row, state.player.col, &passable, ships);
+	if path.len() > 1 && !state.npcs[&state.map_id].is_npc_at(path[1].0, path[1].1) {
+		m.row = path[1].0;
+		m.col = path[1].1;
+	}
+
+	Ok(())
+}
+
+// Castaways keep to their camp and, unless they've been riled up, sleep 
+// through the night
+fn castaway_action(m: &mut Monster, state: &mut GameState,
+					ships: &HashMap<(usize, usize), Ship>, night: bool) -> Result<(), super::ExitReason> {
 	if m.hostile {
 		basic_monster_action(m, state, ships, "attacks")?;
-	} else {
+	} else if !night {
 		let d = util::cartesian_d(m.row, m.col, state.player.row, state.player.col);
 	
 		if d < 4 {
@@ -892,6 +1598,7 @@ fn castaway_action(m: &mut Monster, state: &mut GameState,
 			passable.insert(map::Tile::Dirt);
 			passable.insert(map::Tile::Grass);
 			passable.insert(map::Tile::Water);
+			passable.insert(map::Tile::Reef);
 			passable.insert(map::Tile::Sand);
 			passable.insert(map::Tile::Tree);
 
@@ -926,8 +1633,9 @@ fn get_pirate_line() -> String {
 	}
 }
 
-fn pirate_action(m: &mut Monster, state: &mut GameState,
-					ships: &HashMap<(usize, usize), Ship>) -> Result<(), super::ExitReason> {
+// Marooned pirates patrol around their camp by day and gather at the fire
+// come nightfall, until they spot the player
+fn cutlass_attack(m: &mut Monster, state: &mut GameState) -> Result<(), super::ExitReason> {
 	let pronoun = if m.gender == 0 {
 		"their"
 	} else if m.gender == 1 {
@@ -936,16 +1644,33 @@ fn pirate_action(m: &mut Monster, state: &mut GameState,
 		"his"
 	};
 
+	if super::attack_player(state, m) {
+		let s = format!("The {} slashes with {} cutlass!", m.name, pronoun);
+		state.write_msg_buff(&s);
+		let dmg_roll = dice::roll(m.dmg, m.dmg_dice, m.dmg_bonus as i8);
+		super::player_takes_dmg(&mut state.player, dmg_roll, &m.name)?;
+	} else {
+		let s = format!("The {} missed!", m.name);
+		state.write_msg_buff(&s);
+	}	
+
+	Ok(())
+}
+
+// A ship's crew stays aboard and only fights off anyone who comes over 
+// the side
+fn sailor_action(m: &mut Monster, state: &mut GameState) -> Result<(), super::ExitReason> {
 	if sqs_adj(m.row, m.col, state.player.row, state.player.col) && !state.player.on_ship {
-		if super::attack_player(state, m) {
-			let s = format!("The {} slashes with {} cutlass!", m.name, pronoun);
-			state.write_msg_buff(&s);
-			let dmg_roll = dice::roll(m.dmg, m.dmg_dice, m.dmg_bonus as i8);
-			super::player_takes_dmg(&mut state.player, dmg_roll, &m.name)?;
-		} else {
-			let s = format!("The {} missed!", m.name);
-			state.write_msg_buff(&s);
-		}	
+		cutlass_attack(m, state)?;
+	}
+
+	Ok(())
+}
+
+fn pirate_action(m: &mut Monster, state: &mut GameState,
+					ships: &HashMap<(usize, usize), Ship>, night: bool) -> Result<(), super::ExitReason> {
+	if sqs_adj(m.row, m.col, state.player.row, state.player.col) && !state.player.on_ship {
+		cutlass_attack(m, state)?;
 
 		if rand::thread_rng().gen_range(0.0, 1.0) < 0.2 {
 			state.write_msg_buff(&get_pirate_line());
@@ -954,6 +1679,34 @@ fn pirate_action(m: &mut Monster, state: &mut GameState,
 		return Ok(());
 	} 
 
+	let mut passable = HashSet::new();
+	passable.insert(map::Tile::Dirt);
+	passable.insert(map::Tile::Grass);
+	passable.insert(map::Tile::Water);
+	passable.insert(map::Tile::Reef);
+	passable.insert(map::Tile::Sand);
+	passable.insert(map::Tile::Tree);
+	passable.insert(map::Tile::Floor);
+
+	if !m.aware_of_player {
+		if night {
+			if util::cartesian_d(m.row, m.col, m.anchor.0, m.anchor.1) > 1 {
+				let path = find_path(state, m.row, m.col, 
+					m.anchor.0, m.anchor.1, &passable, ships);
+				if path.len() > 1 && !state.npcs[&state.map_id].is_npc_at(path[1].0, path[1].1) {
+					m.row = path[1].0;
+					m.col = path[1].1;
+				}
+			}
+		} else if rand::thread_rng().gen_range(0.0, 1.0) < 0.5 {
+			let loc = find_adj_empty_sq(m.row as i32, m.col as i32, state, ships, &passable);
+			if util::cartesian_d(m.anchor.0, m.anchor.1, loc.0, loc.1) < 9 {
+				m.row = loc.0;
+				m.col = loc.1;
+			}
+		}
+	}
+
 	// Too far away and they just ignore the player
 	let d = util::cartesian_d(m.row, m.col, state.player.row, state.player.col);
 	if d > 20 {
@@ -961,14 +1714,6 @@ fn pirate_action(m: &mut Monster, state: &mut GameState,
 	}
 
 	if m.aware_of_player {
-		let mut passable = HashSet::new();
-		passable.insert(map::Tile::Dirt);
-		passable.insert(map::Tile::Grass);
-		passable.insert(map::Tile::Water);
-		passable.insert(map::Tile::Sand);
-		passable.insert(map::Tile::Tree);
-		passable.insert(map::Tile::Floor);
-
 		let path = find_path(state, m.row, m.col, 
 			state.player.row, state.player.col, &passable, &ships);
 
@@ -1037,11 +1782,18 @@ fn pick_fleeing_move(state: &mut GameState, m: &Monster, passable: HashSet<Tile>
 fn merfolk_action(m: &mut Monster, state: &mut GameState) -> Result<(), super::ExitReason> {
 	let dis = util::cartesian_d(m.row, m.col, state.player.row , state.player.col);
 	if dis < 13 {
-		if !state.player.charmed {
+		if state.player.inventory.equiped_earplugs() {
+			// Ears stopped with wax, the player can go after the merfolk, or 
+			// try talking to them, safely
+			state.write_flavour_msg("The merfolk's lips move but you can't hear a thing.");
+		} else if !state.player.charmed {
 			state.write_msg_buff("You hear beautiful singing.");
 			let verve_mod = Player::mod_for_stat(state.player.verve);
 
-			let bonus = f32::round(state.player.drunkeness as f32 / 5.0) as i8;
+			let mut bonus = f32::round(state.player.drunkeness as f32 / 5.0) as i8;
+			if state.player.inventory.equiped_captains_hat() {
+				bonus += 2;
+			}
 			if !do_ability_check(verve_mod, 14, bonus) {
 				let s = format!("You are charmed by the {}'s song!", m.name);
 				state.write_msg_buff(&s);
@@ -1052,6 +1804,7 @@ fn merfolk_action(m: &mut Monster, state: &mut GameState) -> Result<(), super::E
 			let mut water = HashSet::new();
 			water.insert(map::Tile::DeepWater);
 			water.insert(map::Tile::Water);
+			water.insert(map::Tile::Reef);
 
 			match pick_fleeing_move(state, m, water) {
 				Some(mv) => {
@@ -1070,7 +1823,8 @@ fn merfolk_action(m: &mut Monster, state: &mut GameState) -> Result<(), super::E
 				let next_r = (m.row as i32 + mv.0) as usize;
 				let next_c = (m.col as i32 + mv.1) as usize;
 				if curr_map[next_r][next_c] == Tile::Water 
-								|| curr_map[next_r][next_c] == Tile::DeepWater {
+								|| curr_map[next_r][next_c] == Tile::DeepWater
+								|| curr_map[next_r][next_c] == Tile::Reef {
 					m.row = next_r;
 					m.col = next_r;
 					break;
@@ -1082,8 +1836,99 @@ fn merfolk_action(m: &mut Monster, state: &mut GameState) -> Result<(), super::E
 	Ok(())
 }
 
+// How many of the squares around a spot are deep water. The more there are,
+// the further the spot is from any shore.
+fn deep_water_around(state: &GameState, row: usize, col: usize) -> usize {
+	let curr_map = &state.map[&state.map_id];
+	let mut count = 0;
+	for r in -1..=1 {
+		for c in -1..=1 {
+			let nr = row as i32 + r;
+			let nc = col as i32 + c;
+			if map::in_bounds(curr_map, nr, nc) && curr_map[nr as usize][nc as usize] == Tile::DeepWater {
+				count += 1;
+			}
+		}
+	}
+
+	count
+}
+
+// A shark two squares off from a swimmer can close the gap and bite in the
+// same turn, biting harder for the momentum, and it may haul the player
+// further out from shore.
+fn shark_lunge(m: &mut Monster, state: &mut GameState, ships: &HashMap<(usize, usize), Ship>) 
+											-> Result<bool, super::ExitReason> {
+	let pr = state.player.row;
+	let pc = state.player.col;
+	if state.player.on_ship || ships.contains_key(&(pr, pc)) 
+			|| state.map[&state.map_id][pr][pc] != Tile::DeepWater {
+		return Ok(false);
+	}
+
+	let dr = pr as i32 - m.row as i32;
+	let dc = pc as i32 - m.col as i32;
+	if dr.abs().max(dc.abs()) != 2 {
+		return Ok(false);
+	}
+
+	let mid_r = (m.row as i32 + dr.signum()) as usize;
+	let mid_c = (m.col as i32 + dc.signum()) as usize;
+	if state.map[&state.map_id][mid_r][mid_c] != Tile::DeepWater 
+			|| !super::sq_is_open(state, ships, mid_r, mid_c) {
+		return Ok(false);
+	}
+
+	m.row = mid_r;
+	m.col = mid_c;
+
+	if !super::attack_player(state, m) {
+		state.write_msg_buff("A shark lunges at you but misses!");
+		return Ok(true);
+	}
+
+	state.write_msg_buff("A shark lunges out of the depths and bites you!");
+	let dmg_roll = dice::roll(m.dmg, m.dmg_dice, m.dmg_bonus as i8 + 2);
+	super::player_takes_dmg(&mut state.player, dmg_roll, "shark")?;
+
+	if thread_rng().gen_range(0, 3) == 0 {
+		let mut best = None;
+		let mut most_water = deep_water_around(state, pr, pc);
+		for r in -1..=1 {
+			for c in -1..=1 {
+				if r == 0 && c == 0 { continue; }
+				let nr = pr as i32 + r;
+				let nc = pc as i32 + c;
+				if !map::in_bounds(&state.map[&state.map_id], nr, nc) { continue; }
+				let loc = (nr as usize, nc as usize);
+				if state.map[&state.map_id][loc.0][loc.1] != Tile::DeepWater { continue; }
+				if (loc.0, loc.1) == (m.row, m.col) || !super::sq_is_open(state, ships, loc.0, loc.1) { continue; }
+				if ships.contains_key(&loc) { continue; }
+
+				let water = deep_water_around(state, loc.0, loc.1);
+				if water > most_water {
+					most_water = water;
+					best = Some(loc);
+				}
+			}
+		}
+
+		if let Some(loc) = best {
+			state.write_msg_buff("The shark drags you out into deeper water!");
+			state.player.row = loc.0;
+			state.player.col = loc.1;
+		}
+	}
+
+	Ok(true)
+}
+
 fn shark_action(m: &mut Monster, state: &mut GameState, ships: &HashMap<(usize, usize), Ship>) 
 													-> Result<(), super::ExitReason> {
+	if shark_lunge(m, state, ships)? {
+		return Ok(());
+	}
+
 	if sqs_adj(m.row, m.col, state.player.row, state.player.col) && !state.player.on_ship {
 		if super::attack_player(state, m) {
 			state.write_msg_buff("The shark bites you!");
@@ -1097,8 +1942,23 @@ fn shark_action(m: &mut Monster, state: &mut GameState, ships: &HashMap<(usize,
 		let mut water = HashSet::new();
 		water.insert(map::Tile::DeepWater);
 
-		let path = find_path(state, m.row, m.col, 
-			state.player.row, state.player.col, &water, ships);
+		// Blood in the water is more tempting than a swimmer, so a nearby corpse
+		// will draw the shark off
+		let player_d = util::cartesian_d(m.row, m.col, state.player.row, state.player.col);
+		let mut goal = (state.player.row, state.player.col);
+		if let Some((loc, corpse_d)) = state.nearest_corpse(m.row, m.col) {
+			if corpse_d < 20 && corpse_d < player_d {
+				if corpse_d <= 1 {
+					if player_d < 8 {
+						state.write_msg_buff("A shark thrashes in the water, tearing at a corpse.");
+					}
+					return Ok(());
+				}
+				goal = loc;
+			}
+		}
+
+		let path = find_path(state, m.row, m.col, goal.0, goal.1, &water, ships);
 		
 		if path.len() > 1 {
 			let new_loc = path[1];
@@ -1120,3 +1980,57 @@ fn shark_action(m: &mut Monster, state: &mut GameState, ships: &HashMap<(usize,
 	Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn skellie(id: usize, row: usize, col: usize) -> Monster {
+		Monster::new(String::from("skeleton"), id, NPCType::Skeleton, 'Z', WHITE, (row, col),
+			MonsterStats { ac: 12, hp: 10, hit_bonus: 3, dmg: 6, dmg_dice: 1, dmg_bonus: 0, score: 5 })
+	}
+
+	#[test]
+	fn old_merchant_sells_last_item() {
+		let mut m = Monster::new(String::from("castaway"), 1, NPCType::Castaway, '@', WHITE, (5, 5),
+			MonsterStats { ac: 10, hp: 8, hit_bonus: 2, dmg: 4, dmg_dice: 1, dmg_bonus: 0, score: 0 });
+		m.for_sale = Item::get_item("draught of rum");
+		m.price = (0, 2);
+		m.stock = 3;
+
+		// A save from before stock was tracked
+		let yaml = serde_yaml::to_string(&m).unwrap()
+			.lines()
+			.filter(|l| !l.starts_with("stock:"))
+			.collect::<Vec<&str>>()
+			.join("\n");
+		let mut loaded: Monster = serde_yaml::from_str(&yaml).unwrap();
+		assert_eq!(loaded.stock, 0);
+		assert_eq!(loaded.stock_left(), 1);
+
+		loaded.sold(1);
+		assert_eq!(loaded.stock, 0);
+		assert!(loaded.for_sale.is_none());
+	}
+
+	#[test]
+	fn update_wont_stack_monsters() {
+		let mut npcs = NPCTracker::new();
+		npcs.add(skellie(1, 5, 5));
+		npcs.add(skellie(2, 5, 7));
+
+		// Both skeletons path to (5, 6) on the same turn
+		let mut a = npcs.npc_with_id(1).unwrap();
+		a.col = 6;
+		npcs.update(a, 5, 5);
+		let mut b = npcs.npc_with_id(2).unwrap();
+		b.col = 6;
+		npcs.update(b, 5, 7);
+
+		assert_eq!(npcs.npc_at(5, 6).unwrap().id, 1);
+		assert_eq!(npcs.npc_at(5, 7).unwrap().id, 2);
+		assert!(npcs.npc_at(5, 5).is_none());
+		assert_eq!(npcs.all_npc_ids().len(), 2);
+		assert_eq!(npcs.loc_index.len(), 2);
+	}
+}
@@ -0,0 +1,104 @@
+// This file is part of YarrL, the pirate roguelike.
+//
+// YarrL is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// YarrL is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with YarrL.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fs;
+
+use serde::Deserialize;
+
+// Player-tweakable settings, read from config.yaml at start-up. Any option
+// missing from the file (or the file itself being missing) falls back to
+// the defaults below.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+	pub autosave_interval: u32,
+	pub debug: bool,
+	pub verbosity: Verbosity,
+	pub terrain_costs: bool,
+	pub font_scale: usize,
+	pub show_damage: bool,
+	pub plain_glyphs: bool,
+	pub faded_charts: bool,
+	pub relight_torches: bool,
+	pub spawn_density: SpawnDensity,
+	pub endgame: Endgame,
+}
+
+impl Default for Config {
+	fn default() -> Config {
+		Config { autosave_interval: 0, debug: false, verbosity: Verbosity::Normal, 
+			terrain_costs: true, font_scale: 1, show_damage: false,
+			plain_glyphs: false, faded_charts: false, relight_torches: true,
+			spawn_density: SpawnDensity::Normal, endgame: Endgame::Classic }
+	}
+}
+
+// How chatty the message log is about things that don't really matter, like
+// splashing through the shallows. Combat and danger are always reported.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+	Terse,
+	#[default]
+	Normal,
+	Verbose,
+}
+
+// How thickly the islands are populated with monsters when the world is made.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpawnDensity {
+	Sparse,
+	#[default]
+	Normal,
+	Swarming,
+}
+
+impl SpawnDensity {
+	// Sparse islands get about half the usual number of monsters (but never
+	// none if there would have been some), swarming ones get twice as many.
+	pub fn scale(&self, count: usize) -> usize {
+		match self {
+			SpawnDensity::Sparse => count.div_ceil(2),
+			SpawnDensity::Normal => count,
+			SpawnDensity::Swarming => count * 2,
+		}
+	}
+}
+
+// Classic: the game is won the moment the treasure is found. Escape: finding it
+// brings the Navy down on the player, who then has to sail clear of the strait.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Endgame {
+	#[default]
+	Classic,
+	Escape,
+}
+
+impl Config {
+	pub fn load() -> Config {
+		match fs::read_to_string("config.yaml") {
+			Ok(contents) => match serde_yaml::from_str(&contents) {
+				Ok(config) => config,
+				Err(_) => {
+					println!("Unable to parse config.yaml, using the default settings.");
+					Config::default()
+				},
+			},
+			Err(_) => Config::default(),
+		}
+	}
+}
@@ -18,11 +18,13 @@ extern crate rand;
 use std::collections::{HashMap, HashSet, VecDeque};
 
 use rand::Rng;
+use serde::{Serialize, Deserialize};
 
 use super::{GameState, ItemsTable, ShipsTable};
 use crate::actor::NPCTracker;
+use crate::config::SpawnDensity;
 use crate::dice;
-use crate::display::GREY;
+use crate::display::{DARK_BROWN, GREY};
 use crate::items::Item;
 use crate::map;
 use crate::map::Tile;
@@ -34,17 +36,53 @@ use crate::weather::{Weather, WeatherSystem};
 
 pub const WORLD_WIDTH: usize = 250;
 pub const WORLD_HEIGHT: usize = 250;
+const CAVE_DEPTH: usize = 3;
+
+// On top of its shape, each island has a character of its own that decides
+// what grows and lives there
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Biome {
+	Jungle,
+	Barren,
+	Lush,
+}
+
+impl Biome {
+	fn random() -> Biome {
+		let roll = rand::thread_rng().gen_range(0.0, 1.0);
+		if roll < 0.33 {
+			Biome::Jungle
+		} else if roll < 0.66 {
+			Biome::Barren
+		} else {
+			Biome::Lush
+		}
+	}
+
+	pub fn landfall_msg(&self) -> &str {
+		match self {
+			Biome::Jungle => "Thick jungle crowds the shore. Something growls in the undergrowth.",
+			Biome::Barren => "A bleak, windswept rock. Bones and broken timbers litter the beach.",
+			Biome::Lush => "Green and lush! You hear birdsong and running water.",
+		}
+	}
+}
 
 struct IslandInfo {
 	coastline: VecDeque<(usize, usize)>,
+	islets: HashSet<(usize, usize)>,
+	shore_reachable: HashSet<(usize, usize)>,
 	length: usize,
 	offset_r: usize,
 	offset_c: usize,
+	biome: Biome,
 }
 
 impl IslandInfo {
 	fn new(offset_r: usize, offset_c: usize) -> IslandInfo {
-		IslandInfo { coastline: VecDeque::new(), length: 0, offset_r, offset_c }
+		IslandInfo { coastline: VecDeque::new(), islets: HashSet::new(), shore_reachable: HashSet::new(),
+			length: 0, offset_r, offset_c,
+			biome: Biome::random() }
 	}
 }
 
@@ -75,7 +113,8 @@ fn initialize_map(state: &mut GameState) {
 
 pub fn generate_world(state: &mut GameState,
 		items: &mut HashMap<u8, ItemsTable>,
-		ships: &mut HashMap<u8, ShipsTable>) {
+		ships: &mut HashMap<u8, ShipsTable>,
+		density: SpawnDensity) {
 
 	initialize_map(state);
 	// at the moment I have two clue types: maps and 
@@ -102,14 +141,18 @@ pub fn generate_world(state: &mut GameState,
 	};
 
 	let mut q1_info = IslandInfo::new(5, 5);
-	create_island(state, items, &mut q1_info, ships);
+	create_island(state, items, &mut q1_info, ships, density);
 	let mut q2_info = IslandInfo::new(10, 100);
-	create_island(state, items, &mut q2_info, ships);
+	create_island(state, items, &mut q2_info, ships, density);
 	let mut q3_info = IslandInfo::new(100, 10);
-	create_island(state, items, &mut q3_info, ships);
+	create_island(state, items, &mut q3_info, ships, density);
 	let mut q4_info = IslandInfo::new(100, 100);
-	create_island(state, items, &mut q4_info, ships);
+	create_island(state, items, &mut q4_info, ships, density);
 	let islands = vec![q1_info, q2_info, q3_info, q4_info];
+	for island in islands.iter() {
+		state.islands.push((island.offset_r, island.offset_c, island.length));
+		state.biomes.push(island.biome);
+	}
 
 	state.pirate_lord = get_pirate_lord();
 	// the player isn't allowed to have a royal Yendorian naval ship
@@ -205,12 +248,66 @@ pub fn generate_world(state: &mut GameState,
     state.weather.insert(0, w);
 }
 
+// Debugging aid for eyeballing the island generator: throw away whichever
+// island is nearest the player and roll a fresh one in its place. Caves of the
+// old island are left orphaned, which is fine for testing but it's why this
+// isn't something to do in a real game. Returns which island was replaced.
+pub fn regenerate_island(state: &mut GameState,
+		items: &mut HashMap<u8, ItemsTable>,
+		ships: &mut HashMap<u8, ShipsTable>,
+		density: SpawnDensity) -> usize {
+	let mut nearest = 0;
+	let mut best_d = usize::MAX;
+	for (i, island) in state.islands.iter().enumerate() {
+		let d = util::cartesian_d(state.player.row, state.player.col, 
+			island.0 + island.2 / 2, island.1 + island.2 / 2);
+		if d < best_d {
+			best_d = d;
+			nearest = i;
+		}
+	}
+
+	let (offset_r, offset_c, length) = state.islands[nearest];
+	let end_r = usize::min(offset_r + length, WORLD_HEIGHT - 1);
+	let end_c = usize::min(offset_c + length, WORLD_WIDTH - 1);
+	let curr_map = state.map.get_mut(&0).unwrap();
+	for row in &mut curr_map[offset_r..end_r] {
+		for sq in &mut row[offset_c..end_c] {
+			*sq = Tile::DeepWater;
+		}
+	}
+	items.get_mut(&0).unwrap().clear_area(offset_r, offset_c, end_r, end_c);
+	state.npcs.get_mut(&0).unwrap().clear_area(offset_r, offset_c, end_r, end_c);
+	state.sandbars.retain(|loc| loc.0 < offset_r || loc.0 >= end_r 
+		|| loc.1 < offset_c || loc.1 >= end_c);
+
+	let mut info = IslandInfo::new(offset_r, offset_c);
+	create_island(state, items, &mut info, ships, density);
+	state.islands[nearest] = (info.offset_r, info.offset_c, info.length);
+	// Saves from before biomes were tracked won't have any
+	if nearest < state.biomes.len() {
+		state.biomes[nearest] = info.biome;
+	}
+	state.landfalls.remove(&nearest);
+
+	nearest
+}
+
 fn find_location_for_land_monster(world_map: &Vec<Vec<Tile>>, 
 					info: &IslandInfo) -> (usize, usize) {
 	loop {
 		let r = rand::thread_rng().gen_range(info.offset_r, info.offset_r + info.length); 
 		let c = rand::thread_rng().gen_range(info.offset_c, info.offset_c + info.length); 
 		
+		// Nothing on a little islet off by itself could ever get at the player,
+		// nor could something shut in somewhere it can't walk out of
+		if info.islets.contains(&(r, c)) {
+			continue;
+		}
+		if !info.shore_reachable.is_empty() && !info.shore_reachable.contains(&(r, c)) {
+			continue;
+		}
+
 		if world_map[r][c] == Tile::Grass || world_map[r][c] == Tile::Dirt ||
 			world_map[r][c] == Tile::Tree || world_map[r][c] == Tile::Sand ||
 			world_map[r][c] == Tile::Floor {
@@ -222,17 +319,20 @@ fn find_location_for_land_monster(world_map: &Vec<Vec<Tile>>,
 fn create_island(state: &mut GameState, 
 					items: &mut HashMap<u8, ItemsTable>,
 					island_info: &mut IslandInfo,
-					ships: &mut HashMap<u8, ShipsTable>) {
+					ships: &mut HashMap<u8, ShipsTable>,
+					density: SpawnDensity) {
 	let island;
 	let island_type = rand::thread_rng().gen_range(0.0, 1.0);
-	let max_shipwrecks;
+	let mut max_shipwrecks;
 	let max_old_campsites;
 	let max_campsites;
-	let max_fruit;
+	let mut max_fruit;
 	let mut spring = false;
 	let mut skeleton_island = false;
     let mut has_cave = false;
 	let mut has_temple = true; 
+	let mut reefs = false;
+	let mut atoll = false;
 
 	if island_type < 0.5 {
 		// regular island
@@ -246,7 +346,8 @@ fn create_island(state: &mut GameState,
 
 		// Once in a while, an island will be occupied by an undead
 		// skeleton captain who will raise an undead army 
-		if rand::thread_rng().gen_range(0.0, 1.0) < 0.15 {
+		let undead_chance = if island_info.biome == Biome::Barren { 0.4 } else { 0.15 };
+		if rand::thread_rng().gen_range(0.0, 1.0) < undead_chance {
 			skeleton_island = true;
 		}
 
@@ -262,6 +363,8 @@ fn create_island(state: &mut GameState,
 		max_fruit = 4; 
 		max_campsites = 3;
 		island_info.length = 129;
+		reefs = true;
+		atoll = true;
 	} else {
 		// volcano
 		island = generate_volcanic_island();
@@ -277,6 +380,15 @@ fn create_island(state: &mut GameState,
         }
 	}
 
+	match island_info.biome {
+		Biome::Jungle => max_fruit *= 2,
+		Biome::Barren => {
+			max_fruit /= 3;
+			max_shipwrecks += 2;
+		},
+		Biome::Lush => max_fruit += 2,
+	}
+
 	// this doesn't do what I wanted it to, I don't think
 	// I want to transpose islands that are very small but this
 	// just skips copying some of their water squares. But transposing
@@ -291,13 +403,27 @@ fn create_island(state: &mut GameState,
 		}
 	}
 
-	// find_hidden_valleys(&island);
+	if reefs {
+		place_reefs(curr_map, island_info);
+	}
+	place_sandbars(state, island_info);
 
-	if spring && rand::thread_rng().gen_range(0.0, 1.0) < 0.33 {
+	let spring_chance = if island_info.biome == Biome::Lush { 0.9 } else { 0.33 };
+	if spring && rand::thread_rng().gen_range(0.0, 1.0) < spring_chance {
 		place_spring(state, island_info);
 	}
 	
 	find_coastline(&state.map[&0], island_info);
+	let islets = find_islets(&state.map[&0], island_info);
+	island_info.islets = islets.iter().flatten().cloned().collect();
+
+	if atoll {
+		let lagoon = find_lagoon(&state.map[&0], island_info);
+		populate_lagoon(state, items.get_mut(&0).unwrap(), &lagoon);
+		if !islets.is_empty() && (island_info.biome == Biome::Lush || rand::thread_rng().gen_range(0.0, 1.0) < 0.5) {
+			place_islet_spring(state, &islets);
+		}
+	}
 
 	for _ in 0..rand::thread_rng().gen_range(0, max_shipwrecks) {
 		let cache = get_cache_items();
@@ -319,32 +445,71 @@ fn create_island(state: &mut GameState,
 	}
 
 	if rand::thread_rng().gen_range(0.0, 1.0) < 0.2 {
-		let mut curr_map = state.map.get_mut(&0).unwrap();
-		place_fort(&mut curr_map, island_info, items.get_mut(&state.map_id).unwrap());
+		let curr_map = state.map.get_mut(&0).unwrap();
+		if let Some(loc) = place_fort(curr_map, island_info, items.get_mut(&state.map_id).unwrap()) {
+			scatter_debris(state, (loc.0 + 3, loc.1 + 3), 6, 10);
+			if rand::thread_rng().gen_range(0.0, 1.0) < 0.5 {
+				state.npcs.get_mut(&0).unwrap().new_navigator(loc.0 + 4, loc.1 + 4);
+			}
+		}
 	}
 
+	island_info.shore_reachable = find_shore_reachable(&state.map[&0], island_info);
+
 	if !skeleton_island {
 		if rand::thread_rng().gen_range(0.0, 1.0) < 1.25 {
 			set_castaway(state, island_info);
 		}
 
+		if rand::thread_rng().gen_range(0.0, 1.0) < 0.15 {
+			let loc = find_location_for_land_monster(&state.map[&0], island_info);
+			if !state.npcs[&0].is_npc_at(loc.0, loc.1) {
+				state.npcs.get_mut(&0).unwrap().new_sea_witch(loc.0, loc.1);
+			}
+		}
+
 		let npcs = state.npcs.get_mut(&0).unwrap();
+		let (extra_snakes, extra_boars, sounder_chance, panther_chance) = match island_info.biome {
+			Biome::Jungle => (2, 0, 0.33, 0.6),
+			Biome::Barren => (0, 0, 0.1, 0.0),
+			Biome::Lush => (0, 2, 0.66, 0.1),
+		};
+
 		// let's add some monsters in 
-		for _ in 2..rand::thread_rng().gen_range(3, 5) {
+		let snake_count = density.scale(rand::thread_rng().gen_range(1, 3) + extra_snakes);
+		for _ in 0..snake_count {
 			let loc = find_location_for_land_monster(&state.map[&0], island_info);
             npcs.new_snake(loc.0, loc.1);
 		}
-		for _ in 1..rand::thread_rng().gen_range(2, 4) {
+		let boar_count = density.scale(rand::thread_rng().gen_range(1, 3) + extra_boars);
+		for _ in 0..boar_count {
 			let loc = find_location_for_land_monster(&state.map[&0], island_info);
             npcs.new_boar(loc.0, loc.1);
 		}
-		if rand::thread_rng().gen_range(0.0, 1.0) < 0.1 {
+
+		// and sometimes a sounder of boars roaming together
+		if rand::thread_rng().gen_range(0.0, 1.0) < sounder_chance {
+			let loc = find_location_for_land_monster(&state.map[&0], island_info);
+			let pack = npcs.new_pack();
+			for _ in 0..density.scale(rand::thread_rng().gen_range(3, 5)) {
+				let r = loc.0 + rand::thread_rng().gen_range(0, 3);
+				let c = loc.1 + rand::thread_rng().gen_range(0, 3);
+				if !map::in_bounds(&state.map[&0], r as i32, c as i32) { continue; }
+				let tile = &state.map[&0][r][c];
+				if (*tile == Tile::Grass || *tile == Tile::Dirt || *tile == Tile::Tree 
+						|| *tile == Tile::Sand) && !npcs.is_npc_at(r, c) {
+					npcs.new_boar(r, c);
+					npcs.add_to_pack(r, c, pack);
+				}
+			}
+		}
+		if rand::thread_rng().gen_range(0.0, 1.0) < panther_chance {
 			let loc = find_location_for_land_monster(&state.map[&0], island_info);
             npcs.new_panther(loc.0, loc.1);
 		}
 	} else {
 		let npcs = state.npcs.get_mut(&0).unwrap();
-		let skellie_count = rand::thread_rng().gen_range(8, 11); 
+		let skellie_count = density.scale(rand::thread_rng().gen_range(8, 11)) as u8; 
 		let loc = find_location_for_land_monster(&state.map[&0], island_info);
         let boss_id = npcs.new_undead_boss(loc.0, loc.1, skellie_count);
 		for _ in 0..skellie_count {
@@ -360,6 +525,8 @@ fn create_island(state: &mut GameState,
 	if has_temple {
 		place_old_temple(state, items, island_info, ships);
 	}
+
+	populate_hidden_valleys(state, items, island_info, ships);
 }
 
 fn get_pirate_lord() -> String {
@@ -705,11 +872,161 @@ fn get_cache_items() -> Vec<Item> {
 		let mut i = Item::get_item("rusty cutlass").unwrap();
 		i.hidden = true;
 		cache.push(i);
+	} else if rand::thread_rng().gen_range(0.0, 1.0) < 0.10 {
+		let mut i = Item::get_item("belaying pin").unwrap();
+		i.hidden = true;
+		cache.push(i);
+	}
+
+	if rand::thread_rng().gen_range(0.0, 1.0) < 0.10 {
+		let name = if rand::thread_rng().gen_range(0.0, 1.0) < 0.5 {
+			"stout boots"
+		} else {
+			"leather gloves"
+		};
+		let mut i = Item::get_item(name).unwrap();
+		i.hidden = true;
+		cache.push(i);
 	} 
 
+	if rand::thread_rng().gen_range(0.0, 1.0) < 0.08 {
+		let mut i = Item::get_item("cork vest").unwrap();
+		i.hidden = true;
+		cache.push(i);
+	}
+
+	if rand::thread_rng().gen_range(0.0, 1.0) < 0.05 {
+		let mut i = Item::get_item("sextant").unwrap();
+		i.hidden = true;
+		cache.push(i);
+	}
+
+	if rand::thread_rng().gen_range(0.0, 1.0) < 0.08 {
+		let mut i = Item::get_item("wax earplugs").unwrap();
+		i.hidden = true;
+		cache.push(i);
+	}
+
+	// timbers salvaged from the wreck
+	if rand::thread_rng().gen_range(0.0, 1.0) < 0.25 {
+		for _ in 0..rand::thread_rng().gen_range(1, 4) {
+			let mut i = Item::get_item("plank").unwrap();
+			i.hidden = true;
+			cache.push(i);
+		}
+	}
+
 	cache
 }
 
+// One of the Yendorian Navy's warships, sent after the player once word gets
+// out they have the treasure
+pub fn new_naval_ship(row: usize, col: usize) -> Ship {
+	let mut ship = Ship::new(format!("Y.S. {}", ship::random_name(false)));
+	ship.row = row;
+	ship.col = col;
+	ship.bearing = rand::thread_rng().gen_range(0, 8) * 2;
+	ship.anchored = false;
+	ship.naval = true;
+	ship.update_loc_info();
+
+	ship
+}
+
+// A trader plying the open sea, her hold full of the things a pirate runs
+// short of on a long voyage
+pub fn new_merchant_ship(row: usize, col: usize) -> Ship {
+	let mut ship = Ship::new(ship::random_name(false));
+	ship.row = row;
+	ship.col = col;
+	ship.bearing = rand::thread_rng().gen_range(0, 16);
+	ship.anchored = false;
+	ship.merchant = true;
+	ship.update_loc_info();
+
+	let stock = [("lead ball", 10, 21), ("draught of rum", 3, 7), ("flask of oil", 2, 5),
+				("salted pork", 3, 7), ("plank", 2, 6)];
+	for (name, lo, hi) in stock.iter() {
+		for _ in 0..rand::thread_rng().gen_range(*lo, *hi) {
+			ship.hold.push(Item::get_item(name).unwrap());
+		}
+	}
+
+	let roll = rand::thread_rng().gen_range(0, 5);
+	let name = if roll == 0 {
+		"flintlock pistol"
+	} else if roll == 1 {
+		"lantern"
+	} else if roll == 2 {
+		"spyglass"
+	} else if roll == 3 {
+		"barometer"
+	} else {
+		"cork vest"
+	};
+	ship.hold.push(Item::get_item(name).unwrap());
+
+	ship
+}
+
+// Loot for the secret chambers the player can uncover by searching cave walls.
+// Harder to find, so it's a bit more generous than a regular cache
+pub fn get_hidden_chamber_items() -> Vec<Item> {
+	let mut loot = Vec::new();
+
+	for _ in 0..rand::thread_rng().gen_range(5, 16) {
+		loot.push(Item::get_item("doubloon").unwrap());
+	}
+
+	let roll = rand::thread_rng().gen_range(0, 4);
+	let name = if roll == 0 {
+		"flintlock pistol"
+	} else if roll == 1 {
+		"stout boots"
+	} else if roll == 2 {
+		"leather gloves"
+	} else {
+		"fetish"
+	};
+	loot.push(Item::get_item(name).unwrap());
+
+	loot
+}
+
+// Try to dig a short passage from a wall square, heading in direction dir, into a
+// small chamber. It has to be surrounded by solid rock so we don't accidentally
+// open it up into another part of the cave. Returns the centre of the chamber if
+// there was room.
+pub fn carve_hidden_chamber(map: &mut [Vec<Tile>], wall: (usize, usize), 
+			dir: (i32, i32)) -> Option<(usize, usize)> {
+	let centre_r = wall.0 as i32 + dir.0 * 3;
+	let centre_c = wall.1 as i32 + dir.1 * 3;
+
+	for r in centre_r - 2..=centre_r + 2 {
+		for c in centre_c - 2..=centre_c + 2 {
+			if r < 1 || c < 1 || r >= map.len() as i32 - 1 || c >= map[0].len() as i32 - 1 {
+				return None;
+			}
+			if map[r as usize][c as usize] != Tile::Wall {
+				return None;
+			}
+		}
+	}
+
+	for j in 0..2 {
+		let r = (wall.0 as i32 + dir.0 * j) as usize;
+		let c = (wall.1 as i32 + dir.1 * j) as usize;
+		map[r][c] = Tile::StoneFloor;
+	}
+	for r in centre_r - 1..=centre_r + 1 {
+		for c in centre_c - 1..=centre_c + 1 {
+			map[r as usize][c as usize] = Tile::StoneFloor;
+		}
+	}
+
+	Some((centre_r as usize, centre_c as usize))
+}
+
 fn good_for_fort(tile: &Tile) -> bool {
 	if *tile == Tile::Tree || *tile == Tile::Grass ||
 		*tile  == Tile::Sand || *tile == Tile::Dirt {
@@ -796,9 +1113,20 @@ fn write_fort_sqs(loc: (usize, usize), world_map: &mut Vec<Vec<Tile>>,
 	}
 }
 
+// Scatter some wreckage around a spot, for atmosphere
+fn scatter_debris(state: &mut GameState, centre: (usize, usize), radius: i32, count: usize) {
+	for _ in 0..count {
+		let r = centre.0 as i32 + rand::thread_rng().gen_range(-radius, radius + 1);
+		let c = centre.1 as i32 + rand::thread_rng().gen_range(-radius, radius + 1);
+		if r < 0 || c < 0 { continue; } 
+		let ch = if rand::thread_rng().gen_range(0.0, 1.0) < 0.5 { ',' } else { '`' };
+		state.add_decal(0, r as usize, c as usize, DARK_BROWN, ch);
+	}
+}
+
 fn place_fort(world_map: &mut Vec<Vec<Tile>>,
 			island_info: &IslandInfo,
-			items: &mut ItemsTable) {
+			items: &mut ItemsTable) -> Option<(usize, usize)> {
 
 	// Find all grass, dirt, sand or trees
 	let mut potentials = VecDeque::new();
@@ -829,11 +1157,13 @@ fn place_fort(world_map: &mut Vec<Vec<Tile>>,
 
 		if good_sqs > 10 {
 			write_fort_sqs(sq, world_map, items);
-			break;
+			return Some(sq);
 		}	
 
 		count += 1;
 	}	
+
+	None
 }
 
 fn add_shipwreck(state: &mut GameState,
@@ -841,10 +1171,18 @@ fn add_shipwreck(state: &mut GameState,
 			items: &mut ItemsTable,
 			cache: Vec<Item>,
 			guarantee_cache: bool) -> String {
-	let curr_map = state.map.get_mut(&0).unwrap();
 	let loc = rand::thread_rng().gen_range(0, island_info.coastline.len());
 	let centre = island_info.coastline[loc];	
 
+	wreck_at(state, centre, items, cache, guarantee_cache)
+}
+
+fn wreck_at(state: &mut GameState,
+			centre: (usize, usize),
+			items: &mut ItemsTable,
+			cache: Vec<Item>,
+			guarantee_cache: bool) -> String {
+	let curr_map = state.map.get_mut(&0).unwrap();
 	let wreck_name = ship::random_name(true);
 	let deck = Tile::Shipwreck(ship::DECK_ANGLE, wreck_name.clone()); 
 	curr_map[centre.0][centre.1] = deck;
@@ -905,6 +1243,8 @@ fn add_shipwreck(state: &mut GameState,
 		curr_map[part_r][part_c] = Tile::Mast(ship::BOW_SW);
 	}
 
+	scatter_debris(state, centre, 3, 6);
+
 	// merfolk like to hang out near shipwrecks
  	if rand::thread_rng().gen_range(0.0, 1.0) < 0.20 {
 		let count = rand::thread_rng().gen_range(1, 3);
@@ -935,6 +1275,121 @@ fn place_mermaid(state: &mut GameState, loc: (usize, usize)) {
 }
 
 
+// Coral grows in the shallows around an atoll, mostly along the seaward edge
+// where it's most likely to catch out a careless captain.
+fn place_reefs(world_map: &mut Vec<Vec<Tile>>, island_info: &IslandInfo) {
+	for r in island_info.offset_r..island_info.offset_r + island_info.length {
+		for c in island_info.offset_c..island_info.offset_c + island_info.length {
+			if !map::in_bounds(world_map, r as i32, c as i32) { continue; }
+			if world_map[r][c] != Tile::Water { continue; }
+
+			let mut seaward = false;
+			for dr in -1..=1 {
+				for dc in -1..=1 {
+					let nr = r as i32 + dr;
+					let nc = c as i32 + dc;
+					if map::in_bounds(world_map, nr, nc) 
+							&& world_map[nr as usize][nc as usize] == Tile::DeepWater {
+						seaward = true;
+					}
+				}
+			}
+
+			let chance = if seaward { 0.2 } else { 0.03 };
+			if rand::thread_rng().gen_range(0.0, 1.0) < chance {
+				world_map[r][c] = Tile::Reef;
+			}
+		}
+	}
+}
+
+// Sandbars lurk in the deep water just off the shallows, so coming straight
+// in at an island is a gamble. They look just like the rest of the sea.
+fn place_sandbars(state: &mut GameState, island_info: &IslandInfo) {
+	let world_map = &state.map[&0];
+	let mut sandbars = Vec::new();
+	let start_r = island_info.offset_r.saturating_sub(3);
+	let start_c = island_info.offset_c.saturating_sub(3);
+	for r in start_r..island_info.offset_r + island_info.length + 3 {
+		for c in start_c..island_info.offset_c + island_info.length + 3 {
+			if !map::in_bounds(world_map, r as i32, c as i32) { continue; }
+			if world_map[r][c] != Tile::DeepWater { continue; }
+
+			let mut near_shallows = false;
+			for dr in -1..=1 {
+				for dc in -1..=1 {
+					let nr = r as i32 + dr;
+					let nc = c as i32 + dc;
+					if map::in_bounds(world_map, nr, nc) 
+							&& world_map[nr as usize][nc as usize] == Tile::Water {
+						near_shallows = true;
+					}
+				}
+			}
+
+			if near_shallows && rand::thread_rng().gen_range(0.0, 1.0) < 0.05 {
+				sandbars.push((r, c));
+			}
+		}
+	}
+
+	state.sandbars.extend(sandbars);
+}
+
+// An atoll's lagoon is sheltered from the open sea so it's where a wreck is
+// likely to have settled, and the merfolk like it there too. The ring of the
+// atoll is in the way, so the player will have to swim or find a gap in the
+// reef to get there.
+fn populate_lagoon(state: &mut GameState, items: &mut ItemsTable, lagoon: &HashSet<(usize, usize)>) {
+	// Only bother if the wreck has a bit of room around it
+	let sqs = lagoon.iter()
+		.filter(|sq| {
+			for dr in -2..=2 {
+				for dc in -2..=2 {
+					let loc = ((sq.0 as i32 + dr) as usize, (sq.1 as i32 + dc) as usize);
+					if !lagoon.contains(&loc) {
+						return false;
+					}
+				}
+			}
+			true
+		})
+		.cloned()
+		.collect::<Vec<(usize, usize)>>();
+
+	if sqs.is_empty() {
+		return;
+	}
+
+	let centre = sqs[rand::thread_rng().gen_range(0, sqs.len())];
+	if rand::thread_rng().gen_range(0.0, 1.0) < 0.66 {
+		wreck_at(state, centre, items, get_cache_items(), true);
+	}
+
+	let npcs = state.npcs.get_mut(&0).unwrap();
+	for _ in 0..rand::thread_rng().gen_range(0, 4) {
+		let loc = sqs[rand::thread_rng().gen_range(0, sqs.len())];
+		if state.map[&0][loc.0][loc.1] == Tile::Water && !npcs.is_npc_at(loc.0, loc.1) {
+			npcs.new_merperson(loc.0, loc.1);
+		}
+	}
+}
+
+// A fresh spring out on one of the atoll's islets, for the player who goes
+// looking for it
+fn place_islet_spring(state: &mut GameState, islets: &[HashSet<(usize, usize)>]) {
+	let islet = &islets[rand::thread_rng().gen_range(0, islets.len())];
+	let sqs = islet.iter()
+		.filter(|sq| state.map[&0][sq.0][sq.1] != Tile::Sand)
+		.cloned()
+		.collect::<Vec<(usize, usize)>>();
+
+	if !sqs.is_empty() {
+		let loc = sqs[rand::thread_rng().gen_range(0, sqs.len())];
+		state.map.get_mut(&0).unwrap()[loc.0][loc.1] = Tile::Spring;
+	}
+}
+
 fn place_spring(state: &mut GameState, island_info: &IslandInfo) {
 	let trees = largest_contiguous_block(&state.map[&0], &Tile::Tree, island_info.offset_r,
 							island_info.offset_c, island_info.length); 
@@ -961,38 +1416,52 @@ fn place_spring(state: &mut GameState, island_info: &IslandInfo) {
 }
 
 // Some map analytics functions
-fn is_hidden_valley(map: &Vec<Vec<Tile>>, r: usize, c: usize) -> HashSet<(usize, usize)> {
+//
+// Floodfill out from a tree, marking every square we reach as checked so the
+// caller doesn't flood the same stand of trees over and over. If the block is
+// fenced in entirely by mountains it's a valley, otherwise return nothing.
+fn is_hidden_valley(map: &Vec<Vec<Tile>>, r: usize, c: usize,
+		checked: &mut HashSet<(usize, usize)>) -> HashSet<(usize, usize)> {
 	let mut valley = HashSet::new();
 	let mut queue = VecDeque::new();
+	let mut enclosed = true;
 	queue.push_back((r, c));
+	valley.insert((r, c));
 
 	while queue.len() > 0 {
 		let loc = queue.pop_front().unwrap();
-		valley.insert(loc);
 
 		for r in -1..=1 {
 			for c in -1..=1 {
 				if r == 0 && c == 0 { continue; }
+				if !map::in_bounds(map, loc.0 as i32 + r, loc.1 as i32 + c) {
+					enclosed = false;
+					continue;
+				}
 				let nr = (loc.0 as i32 + r) as usize;
 				let nc = (loc.1 as i32 + c) as usize;
 
-				if !map::in_bounds(map, nr as i32, nc as i32) {
-					return HashSet::new();
-				}
-
-				if map[nr][nc] != Tile::Tree && map[nr][nc] != Tile::Mountain 
-						&& map[nr][nc] != Tile::SnowPeak {
-					return HashSet::new();
-				}
-
-				if map[nr][nc] == Tile::Tree && !valley.contains(&(nr, nc)) {
-					queue.push_back((nr, nc));
+				match map[nr][nc] {
+					Tile::Tree | Tile::Spring => {
+						if !valley.contains(&(nr, nc)) {
+							valley.insert((nr, nc));
+							queue.push_back((nr, nc));
+						}
+					},
+					Tile::Mountain | Tile::SnowPeak => { },
+					_ => enclosed = false,
 				}
 			}
 		}
 	}
 
-	valley
+	checked.extend(valley.iter());
+
+	if enclosed {
+		valley
+	} else {
+		HashSet::new()
+	}
 }
 
 // Sometimes the map generator will create pockets of (almost
@@ -1003,18 +1472,21 @@ fn is_hidden_valley(map: &Vec<Vec<Tile>>, r: usize, c: usize) -> HashSet<(usize,
 // Look for any blocks of trees where all their neighbours are 
 // either trees, mountains or snow peeaks. (And maybe I should 
 // include lava?) Another floodfill type search...
-fn find_hidden_valleys(map: &Vec<Vec<Tile>>, island_info: &IslandInfo) -> HashSet<(usize, usize)> {
-	let mut valleys = HashSet::new();
+fn find_hidden_valleys(map: &Vec<Vec<Tile>>, island_info: &IslandInfo) -> Vec<HashSet<(usize, usize)>> {
+	let mut valleys = Vec::new();
+	let mut checked = HashSet::new();
 
 	for r in island_info.offset_r..island_info.length + island_info.offset_r {
         for c in island_info.offset_c..island_info.length + island_info.offset_c {
-            if valleys.contains(&(r, c)) {
+            if checked.contains(&(r, c)) {
                 continue;
             }
 
 			if map[r][c] == Tile::Tree {
-				let valley = is_hidden_valley(map, r, c);
-                valleys.extend(valley);
+				let valley = is_hidden_valley(map, r, c, &mut checked);
+				if !valley.is_empty() {
+					valleys.push(valley);
+				}
 			}
 		}
 	}	
@@ -1148,12 +1620,152 @@ fn find_coastline(world_map: &Vec<Vec<Tile>>, island_info: &mut IslandInfo) {
 	}
 }
 
+// Floodfill inland from the beaches to find everywhere a land monster could
+// walk to from where the player is likely to come ashore
+fn find_shore_reachable(world_map: &[Vec<Tile>], island_info: &IslandInfo) -> HashSet<(usize, usize)> {
+	let walkable = |t: &Tile| *t == Tile::Grass || *t == Tile::Dirt || *t == Tile::Tree 
+		|| *t == Tile::Sand || *t == Tile::Floor || *t == Tile::Water;
+	let mut queue = VecDeque::new();
+	let mut reachable = HashSet::new();
+
+	for loc in island_info.coastline.iter() {
+		if walkable(&world_map[loc.0][loc.1]) && !reachable.contains(loc) {
+			reachable.insert(*loc);
+			queue.push_back(*loc);
+		}
+	}
+
+	let south_edge = (island_info.offset_r + island_info.length) as i32;
+	let east_edge = (island_info.offset_c + island_info.length) as i32;
+	while !queue.is_empty() {
+		let curr = queue.pop_front().unwrap();
+
+		for r in -1..=1 {
+			for c in -1..=1 {
+				if r == 0 && c == 0 { continue; }
+				let nr = curr.0 as i32 + r;
+				let nc = curr.1 as i32 + c;
+
+				if nr < island_info.offset_r as i32 || nr >= south_edge { continue }
+				if nc < island_info.offset_c as i32 || nc >= east_edge { continue }
+
+				let loc = (nr as usize, nc as usize);
+				if walkable(&world_map[loc.0][loc.1]) && !reachable.contains(&loc) {
+					reachable.insert(loc);
+					queue.push_back(loc);
+				}
+			}
+		}
+	}
+
+	reachable
+}
+
+// The lagoon is whatever water inside the island's bounds can't be reached
+// by floodfilling in from the open sea. If the ring of the atoll has gaps
+// there won't be one.
+fn find_lagoon(world_map: &[Vec<Tile>], island_info: &IslandInfo) -> HashSet<(usize, usize)> {
+	let is_water = |t: &Tile| *t == Tile::Water || *t == Tile::DeepWater || *t == Tile::Reef;
+	let mut queue = VecDeque::new();
+	let mut sea = HashSet::new();
+
+	let south_edge = island_info.offset_r + island_info.length;
+	let east_edge = island_info.offset_c + island_info.length;
+	for r in island_info.offset_r..south_edge {
+		for c in island_info.offset_c..east_edge {
+			let on_edge = r == island_info.offset_r || r == south_edge - 1
+				|| c == island_info.offset_c || c == east_edge - 1;
+			if on_edge && is_water(&world_map[r][c]) {
+				sea.insert((r, c));
+				queue.push_back((r, c));
+			}
+		}
+	}
+
+	while !queue.is_empty() {
+		let curr = queue.pop_front().unwrap();
+		for r in -1..=1 {
+			for c in -1..=1 {
+				let nr = curr.0 as i32 + r;
+				let nc = curr.1 as i32 + c;
+				if nr < island_info.offset_r as i32 || nr >= south_edge as i32 { continue }
+				if nc < island_info.offset_c as i32 || nc >= east_edge as i32 { continue }
+
+				let loc = (nr as usize, nc as usize);
+				if is_water(&world_map[loc.0][loc.1]) && !sea.contains(&loc) {
+					sea.insert(loc);
+					queue.push_back(loc);
+				}
+			}
+		}
+	}
+
+	let mut lagoon = HashSet::new();
+	for r in island_info.offset_r..south_edge {
+		for c in island_info.offset_c..east_edge {
+			if is_water(&world_map[r][c]) && !sea.contains(&(r, c)) {
+				lagoon.insert((r, c));
+			}
+		}
+	}
+
+	lagoon
+}
+
+// Any bits of land cut off from the main body of the island. Monsters
+// placed there would never be able to reach the player.
+fn find_islets(world_map: &Vec<Vec<Tile>>, island_info: &IslandInfo) -> Vec<HashSet<(usize, usize)>> {
+	let mut checked = HashSet::new();
+	let mut blocks = Vec::new();
+
+	for r in island_info.offset_r..island_info.offset_r + island_info.length {
+		for c in island_info.offset_c..island_info.offset_c + island_info.length {
+			if !map::is_land(&world_map[r][c]) || checked.contains(&(r, c)) {
+				continue;
+			}
+
+			let mut block = HashSet::new();
+			let mut queue = VecDeque::new();
+			block.insert((r, c));
+			queue.push_back((r, c));
+			while !queue.is_empty() {
+				let curr = queue.pop_front().unwrap();
+				for dr in -1..=1 {
+					for dc in -1..=1 {
+						let nr = curr.0 as i32 + dr;
+						let nc = curr.1 as i32 + dc;
+						if !map::in_bounds(world_map, nr, nc) { continue; }
+						let loc = (nr as usize, nc as usize);
+						if map::is_land(&world_map[loc.0][loc.1]) && !block.contains(&loc) {
+							block.insert(loc);
+							queue.push_back(loc);
+						}
+					}
+				}
+			}
+
+			checked.extend(block.iter().cloned());
+			blocks.push(block);
+		}
+	}
+
+	// The biggest chunk of land is the island proper
+	if let Some(j) = (0..blocks.len()).max_by_key(|&j| blocks[j].len()) {
+		blocks.remove(j);
+	}
+
+	blocks
+}
+
 // Should probably switch to a more generic "Find any of these tile types that are accessible from
 // the shore"
 fn mountains_reachable_by_shore(map: &Vec<Vec<Tile>>, island_info: &IslandInfo) -> VecDeque<(usize, usize)> {
     let mut reachable = VecDeque::new();
 
-    let hidden_valleys = find_hidden_valleys(map, island_info);
+    let hidden_valleys: HashSet<(usize, usize)> = find_hidden_valleys(map, island_info)
+                                .into_iter()
+                                .flatten()
+                                .collect();
 
     // Criteria for a mountain that's reachable from the shore is:
     // any mountain that isn't completely surrounded by mountains or 
@@ -1244,44 +1856,231 @@ fn place_old_temple(state: &mut GameState,
 	}
 }
 
+// A valley cut off by mountains gets something worth the trouble of finding
+// it, and a tunnel through the mountains so it can actually be reached.
+fn populate_hidden_valleys(state: &mut GameState,
+			items: &mut HashMap<u8, ItemsTable>,
+			island_info: &IslandInfo,
+			ships: &mut HashMap<u8, ShipsTable>) {
+	let valleys = find_hidden_valleys(&state.map[&0], island_info);
+
+	for valley in valleys.iter().filter(|v| v.len() >= 4).take(2) {
+		if !tunnel_to_valley(state, items, island_info, ships, valley) {
+			continue;
+		}
+
+		let sqs: Vec<&(usize, usize)> = valley.iter()
+			.filter(|sq| state.map[&0][sq.0][sq.1] == Tile::Tree)
+			.collect();
+		let roll = rand::thread_rng().gen_range(0.0, 1.0);
+		if roll < 0.33 {
+			let sq = sqs[rand::thread_rng().gen_range(0, sqs.len())];
+			state.map.get_mut(&0).unwrap()[sq.0][sq.1] = Tile::Spring;
+		} else if roll < 0.66 {
+			for _ in 0..rand::thread_rng().gen_range(2, 5) {
+				let sq = sqs[rand::thread_rng().gen_range(0, sqs.len())];
+				items.get_mut(&0).unwrap().add(sq.0, sq.1, Item::get_item("mango").unwrap());
+			}
+		} else {
+			let sq = sqs[rand::thread_rng().gen_range(0, sqs.len())];
+			for item in get_hidden_chamber_items() {
+				items.get_mut(&0).unwrap().add(sq.0, sq.1, item);
+			}
+		}
+	}
+}
+
+// Join a valley to the rest of the island with a short tunnel, entered from a
+// mountain the player can get at and coming out on the valley's edge. Returns
+// false if there was nowhere to put either end.
+fn tunnel_to_valley(state: &mut GameState,
+			items: &mut HashMap<u8, ItemsTable>,
+			island_info: &IslandInfo,
+			ships: &mut HashMap<u8, ShipsTable>,
+			valley: &HashSet<(usize, usize)>) -> bool {
+	let borders_valley = |r: usize, c: usize| {
+		valley.contains(&(r - 1, c)) || valley.contains(&(r + 1, c))
+			|| valley.contains(&(r, c - 1)) || valley.contains(&(r, c + 1))
+	};
+
+	let curr_map = &state.map[&0];
+	let mut exits = Vec::new();
+	for sq in valley.iter() {
+		for (r, c) in [(sq.0 - 1, sq.1), (sq.0 + 1, sq.1), (sq.0, sq.1 - 1), (sq.0, sq.1 + 1)] {
+			if curr_map[r][c] == Tile::Mountain {
+				exits.push((r, c));
+			}
+		}
+	}
+
+	let entrances: Vec<(usize, usize)> = mountains_reachable_by_shore(curr_map, island_info)
+		.into_iter()
+		.filter(|sq| !borders_valley(sq.0, sq.1))
+		.collect();
+
+	if exits.is_empty() || entrances.is_empty() {
+		return false;
+	}
+
+	let exit = exits[rand::thread_rng().gen_range(0, exits.len())];
+	let entrance = entrances[rand::thread_rng().gen_range(0, entrances.len())];
+	let next_map_id = state.map.len() as u8;
+
+	let mut tunnel_map = vec![vec![Tile::Wall; 15]; 3];
+	for sq in &mut tunnel_map[1][1..=13] {
+		*sq = Tile::StoneFloor;
+	}
+	tunnel_map[1][1] = Tile::Portal((entrance.0, entrance.1, 0));
+	tunnel_map[1][13] = Tile::Portal((exit.0, exit.1, 0));
+
+	let curr_map = state.map.get_mut(&0).unwrap();
+	curr_map[entrance.0][entrance.1] = Tile::Portal((1, 1, next_map_id));
+	curr_map[exit.0][exit.1] = Tile::Portal((1, 13, next_map_id));
+
+	state.map.insert(next_map_id, tunnel_map);
+	state.npcs.insert(next_map_id, NPCTracker::new());
+	items.insert(next_map_id, ItemsTable::new());
+	ships.insert(next_map_id, ShipsTable::new());
+	state.weather.insert(next_map_id, Weather::new());
+
+	true
+}
+
 fn place_cave(state: &mut GameState, 
 			items: &mut HashMap<u8, ItemsTable>, 
 			island_info: &IslandInfo,
 			ships: &mut HashMap<u8, ShipsTable>) {
     let reachable = mountains_reachable_by_shore(&state.map[&state.map_id], island_info);
-    let next_map_id = state.map.len() as u8;
-    let curr_map = state.map.get_mut(&state.map_id).unwrap();
-	let cave_length = 20;
-	let cave_width = 30;
 
     if reachable.len() > 0 {
         let cave_loc_id = rand::thread_rng().gen_range(0, reachable.len());
         let cave_loc = reachable[cave_loc_id];
         println!("{:?}", cave_loc);
-        let mut cave_map = map::generate_cave(cave_width, cave_length);
-
-        let exit = find_cave_exit(&cave_map, cave_length, cave_width);
-        if exit.0 != 0 && exit.1 != 0 {
-            cave_map[exit.0][exit.1] = Tile::Portal((cave_loc.0, cave_loc.1, state.map_id));
-            curr_map[cave_loc.0][cave_loc.1] = Tile::Portal((exit.0, exit.1, next_map_id));
-            state.map.insert(next_map_id, cave_map);
-
-            state.npcs.insert(next_map_id, NPCTracker::new());
-            items.insert(next_map_id, ItemsTable::new());
-			ships.insert(next_map_id, ShipsTable::new());
-            state.weather.insert(next_map_id, Weather::new());
-        }
 
-		for _ in 0..3 {
-			loop {
-				let r = rand::thread_rng().gen_range(0, cave_length); 
-				let c = rand::thread_rng().gen_range(0, cave_width); 
-				if state.map.get_mut(&next_map_id).unwrap()[r][c] == Tile::StoneFloor {
-					state.npcs.get_mut(&next_map_id).unwrap().new_rat(r, c);
+		// Each level leads down to the next until we reach the bottom or fail
+		// to find room for another passage
+		let mut entrance = (cave_loc.0, cave_loc.1, state.map_id);
+		for depth in 1..=CAVE_DEPTH {
+			match add_cave_level(state, items, ships, entrance, depth) {
+				Some(down) => entrance = down,
+				None => {
+					// Seal up the passage from the level above since there's
+					// nothing below it after all
+					if depth > 1 {
+						state.map.get_mut(&entrance.2).unwrap()[entrance.0][entrance.1] = Tile::Wall;
+					}
 					break;
-				}
+				},
 			}
 		}
     }
 }
 
+// Create a new cave level whose way out leads back to entrance, which becomes
+// a portal down into it. Returns the location of the passage further down, if
+// this level has one.
+fn add_cave_level(state: &mut GameState,
+			items: &mut HashMap<u8, ItemsTable>,
+			ships: &mut HashMap<u8, ShipsTable>,
+			entrance: (usize, usize, u8),
+			depth: usize) -> Option<(usize, usize, u8)> {
+    let next_map_id = state.map.len() as u8;
+	let cave_length = 20;
+	let cave_width = 30;
+	let mut cave_map = map::generate_cave(cave_width, cave_length);
+
+	let exit = find_cave_exit(&cave_map, cave_length, cave_width);
+	// No usable exit means no cave; otherwise we'd be placing monsters on a
+	// level that was never created
+	if exit.0 == 0 || exit.1 == 0 {
+		return None;
+	}
+
+	cave_map[exit.0][exit.1] = Tile::Portal(entrance);
+	state.map.get_mut(&entrance.2).unwrap()[entrance.0][entrance.1] = Tile::Portal((exit.0, exit.1, next_map_id));
+
+	// Look for a spot for the passage down that's a decent hike from where the
+	// player arrives
+	let mut down = None;
+	if depth < CAVE_DEPTH {
+		for _ in 0..10 {
+			let loc = find_cave_exit(&cave_map, cave_length, cave_width);
+			if loc.0 != 0 && loc.1 != 0 && util::cartesian_d(loc.0, loc.1, exit.0, exit.1) > 10 {
+				down = Some((loc.0, loc.1, next_map_id));
+				break;
+			}
+		}
+	}
+	if let Some((r, c, _)) = down {
+		// Placeholder until the next level exists and we know where it leads
+		cave_map[r][c] = Tile::Portal((exit.0, exit.1, next_map_id));
+	}
+
+	state.map.insert(next_map_id, cave_map);
+	state.npcs.insert(next_map_id, NPCTracker::new());
+	items.insert(next_map_id, ItemsTable::new());
+	ships.insert(next_map_id, ShipsTable::new());
+	state.weather.insert(next_map_id, Weather::new());
+
+	populate_cave_level(state, items, next_map_id, exit, depth);
+
+	down
+}
+
+fn cave_floor_sq(state: &GameState, map_id: u8, exit: (usize, usize)) -> (usize, usize) {
+	let cave_map = &state.map[&map_id];
+	loop {
+		let r = rand::thread_rng().gen_range(0, cave_map.len()); 
+		let c = rand::thread_rng().gen_range(0, cave_map[0].len()); 
+		// Keep monsters from spawning right on top of the way out
+		if util::cartesian_d(r, c, exit.0, exit.1) < 3 { continue; }
+		if cave_map[r][c] == Tile::StoneFloor && !state.npcs[&map_id].is_npc_at(r, c) {
+			return (r, c);
+		}
+	}
+}
+
+// The deeper the player goes the nastier the residents get, but so does the
+// loot improve.
+fn populate_cave_level(state: &mut GameState,
+			items: &mut HashMap<u8, ItemsTable>,
+			map_id: u8,
+			exit: (usize, usize),
+			depth: usize) {
+	let (rat_count, skellie_count) = match depth {
+		1 => (3, 0),
+		2 => (5, 3),
+		_ => (2, 5),
+	};
+
+	// The rats in a cave are all one big, unhappy family
+	let pack = state.npcs.get_mut(&map_id).unwrap().new_pack();
+	for _ in 0..rat_count {
+		let loc = cave_floor_sq(state, map_id, exit);
+		state.npcs.get_mut(&map_id).unwrap().new_rat(loc.0, loc.1);
+		state.npcs.get_mut(&map_id).unwrap().add_to_pack(loc.0, loc.1, pack);
+	}
+
+	// At the very bottom an undead captain holds court over its crew
+	let mut boss_id = 0;
+	if depth == CAVE_DEPTH {
+		let loc = cave_floor_sq(state, map_id, exit);
+		boss_id = state.npcs.get_mut(&map_id).unwrap().new_undead_boss(loc.0, loc.1, skellie_count);
+		for item in get_hidden_chamber_items() {
+			items.get_mut(&map_id).unwrap().add_or_spill(&state.map[&map_id], loc.0, loc.1, item);
+		}
+	}
+
+	for _ in 0..skellie_count {
+		let loc = cave_floor_sq(state, map_id, exit);
+		state.npcs.get_mut(&map_id).unwrap().new_skeleton(loc.0, loc.1, boss_id);
+	}
+
+	if depth > 1 {
+		let loc = cave_floor_sq(state, map_id, exit);
+		for mut item in get_cache_items() {
+			item.hidden = false;
+			items.get_mut(&map_id).unwrap().add(loc.0, loc.1, item);
+		}
+	}
+}
@@ -19,9 +19,11 @@ use std::collections::{HashMap, HashSet, VecDeque};
 
 use crate::items::Item;
 use crate::map;
+use crate::ship;
+use crate::util;
 use super::{Cmd, GameState, FOV_WIDTH, FOV_HEIGHT};
 
-use sdl2::event::Event;
+use sdl2::event::{Event, WindowEvent};
 use sdl2::EventPump;
 use sdl2::keyboard::Mod;
 use sdl2::keyboard::Keycode;
@@ -40,15 +42,24 @@ pub static BROWN: (u8, u8, u8) = (150, 75, 0);
 pub static DARK_BROWN: (u8, u8, u8) = (101, 67, 33);
 pub static BLUE: (u8, u8, u8) = (0, 0, 200);
 pub static LIGHT_BLUE: (u8, u8, u8) = (55, 198, 255);
+pub static REEF_BLUE: (u8, u8, u8) = (70, 150, 210);
 pub static BEIGE: (u8, u8, u8) = (255, 178, 127);
 pub static BRIGHT_RED: (u8, u8, u8) = (208, 28, 31);
+pub static DARK_RED: (u8, u8, u8) = (139, 0, 0);
 pub static GOLD: (u8, u8, u8) = (255, 215, 0);
 pub static YELLOW: (u8, u8, u8) = (255, 225, 53);
 pub static YELLOW_ORANGE: (u8, u8, u8,) = (255, 159, 0);
 
+// Point sizes for the (main, small) fonts at each of the scales the player 
+// can switch between
+pub const FONT_SCALES: [(u16, u16); 3] = [(18, 14), (24, 18), (30, 22)];
+
 const SCREEN_WIDTH: u32 = 58;
 const SCREEN_HEIGHT: u32 = 22;
 const BACKSPACE_CH: char = '\u{0008}';
+const LOW_FUEL: u16 = 20;
+const BEARINGS: [&str; 16] = ["N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE",
+	"S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW"];
 
 #[derive(Debug)]
 pub struct SidebarInfo {
@@ -56,19 +67,24 @@ pub struct SidebarInfo {
 	ac: u8,
 	curr_hp: u8,
 	max_hp: u8,
-	wheel: i8,
-	bearing: i8,
+	helm: Option<(u8, i8, u8)>,
 	turn: u32,
 	charmed: bool,
 	poisoned: bool,
 	drunkeness: u8,
+	thirst: u8,
 	weapon: Option<String>,
 	firearm: Option<String>,
+	light: Option<(String, u16)>,
 }
 
 impl SidebarInfo {
-	pub fn new(name: String, ac: u8, curr_hp: u8, max_hp: u8, wheel: i8, bearing: i8, turn: u32, 
-			charmed: bool, poisoned: bool, drunkeness: u8, w: String, f: String) -> SidebarInfo {
+	// helm is the ship's (bearing, wheel) when the player is steering, plus
+	// the direction the wind is blowing from. light is the lit light source
+	// and how much fuel it has left.
+	pub fn new(name: String, ac: u8, curr_hp: u8, max_hp: u8, helm: Option<(u8, i8, u8)>, turn: u32, 
+			charmed: bool, poisoned: bool, drunkeness: u8, thirst: u8, w: String, f: String,
+			light: Option<(String, u16)>) -> SidebarInfo {
 		let weapon = if w == "" {
 			None
 		} else {
@@ -80,8 +96,8 @@ impl SidebarInfo {
 			Some(f)
 		};
 
-		SidebarInfo { name, ac, curr_hp, max_hp, wheel, bearing, turn, charmed, poisoned, drunkeness,
-			weapon, firearm }
+		SidebarInfo { name, ac, curr_hp, max_hp, helm, turn, charmed, poisoned, drunkeness,
+			thirst, weapon, firearm, light }
 	}
 }
 
@@ -89,6 +105,62 @@ fn tuple_to_sdl2_color(ct: &(u8, u8, u8)) -> Color {
 	Color::RGBA(ct.0, ct.1, ct.2, 255)
 }
 
+// Plain ASCII for the fancier characters we draw the map with, for when the
+// player asks for them or the font simply doesn't have the real thing
+fn ascii_stand_in(ch: char) -> char {
+	match ch {
+		'\u{03D9}' => 'T',
+		'\u{0316}' => ',',
+		'\u{039B}' => '^',
+		'Ո' => 'O',
+		ship::DECK_STRAIGHT | ship::DECK_ANGLE => 'o',
+		ship::BOW_N => 'A',
+		ship::BOW_S => 'V',
+		ship::BOW_E => '>',
+		ship::BOW_W => '<',
+		ship::BOW_NE | ship::BOW_SW => '/',
+		ship::BOW_NW | ship::BOW_SE => '\\',
+		_ => ch,
+	}
+}
+
+// The font might not cover every character we use, in which case it'd draw
+// an empty box (or nothing at all) and leave a ship or portal invisible
+fn renderable(font: &Font, ch: char) -> char {
+	if font.find_glyph(ch).is_some() {
+		ch
+	} else {
+		let stand_in = ascii_stand_in(ch);
+		if stand_in != ch { stand_in } else { '?' }
+	}
+}
+
+// The numeric keypad as an alternative to the vi-keys. At the helm, 4 and 6
+// put the wheel over to port and starboard and 5 or 8 hold the course.
+fn keypad_cmd(keycode: Keycode, on_ship: bool) -> Option<Cmd> {
+	if on_ship {
+		match keycode {
+			Keycode::Kp4 => Some(Cmd::TurnWheelAnticlockwise),
+			Keycode::Kp6 => Some(Cmd::TurnWheelClockwise),
+			Keycode::Kp5 | Keycode::Kp8 => Some(Cmd::Pass),
+			_ => None,
+		}
+	} else {
+		match keycode {
+			Keycode::Kp8 => Some(Cmd::Move(String::from("N"))),
+			Keycode::Kp2 => Some(Cmd::Move(String::from("S"))),
+			Keycode::Kp6 => Some(Cmd::Move(String::from("E"))),
+			Keycode::Kp4 => Some(Cmd::Move(String::from("W"))),
+			Keycode::Kp7 => Some(Cmd::Move(String::from("NW"))),
+			Keycode::Kp9 => Some(Cmd::Move(String::from("NE"))),
+			Keycode::Kp1 => Some(Cmd::Move(String::from("SW"))),
+			Keycode::Kp3 => Some(Cmd::Move(String::from("SE"))),
+			Keycode::Kp5 => Some(Cmd::Pass),
+			_ => None,
+		}
+	}
+}
+
 // I have literally zero clue why Rust wants two lifetime parameters
 // here for the Font ref but this shuts the compiler the hell up...
 pub struct GameUI<'a, 'b> {
@@ -100,14 +172,24 @@ pub struct GameUI<'a, 'b> {
 	sm_font_width: u32,
 	sm_font_height: u32,
 	sm_font: &'a Font<'a, 'b>,
+	fonts: Vec<(&'a Font<'a, 'b>, &'a Font<'a, 'b>)>,
+	font_scale: usize,
 	canvas: WindowCanvas,
 	event_pump: EventPump,
 	pub v_matrix: Vec<map::Tile>,
 	surface_cache: HashMap<(char, Color), Surface<'a>>,
+	// Plain ASCII glyphs in brighter colours, for fonts (or eyes) that don't
+	// get along with the fancier characters
+	pub plain_glyphs: bool,
+	// Treasure maps only show terrain the player has actually seen
+	pub faded_charts: bool,
 }
 
 impl<'a, 'b> GameUI<'a, 'b> {
-	pub fn init(font: &'b Font, sm_font: &'b Font) -> Result<GameUI<'a, 'b>, String> {
+	// fonts has a (main, small) pair for each entry in FONT_SCALES
+	pub fn init(fonts: Vec<(&'b Font, &'b Font)>, font_scale: usize) -> Result<GameUI<'a, 'b>, String> {
+		let font_scale = font_scale.min(fonts.len() - 1);
+		let (font, sm_font) = fonts[font_scale];
 		let (font_width, font_height) = font.size_of_char(' ').unwrap();
 		let screen_width_px = SCREEN_WIDTH * font_width + 50;
 		let screen_height_px = SCREEN_HEIGHT * font_height;
@@ -119,24 +201,59 @@ impl<'a, 'b> GameUI<'a, 'b> {
 		let window = video_subsystem.window("YarrL", screen_width_px, screen_height_px)
 			.position_centered()
 			.opengl()
+			.resizable()
 			.build()
 			.map_err(|e| e.to_string())?;
 
 		let v_matrix = vec![map::Tile::Blank; FOV_WIDTH * FOV_HEIGHT];
-		let canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
+		let mut canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
+		// Everything is drawn at the fixed size and if the player resizes the 
+		// window SDL scales it to fit, letterboxed so it isn't stretched
+		canvas.set_logical_size(screen_width_px, screen_height_px).map_err(|e| e.to_string())?;
 		let gui = GameUI { 
 			screen_width_px, screen_height_px, 
 			font, font_width, font_height, 
 			canvas,
 			event_pump: sdl_context.event_pump().unwrap(),
 			sm_font, sm_font_width, sm_font_height,
+			fonts, font_scale,
 			v_matrix,
 			surface_cache: HashMap::new(),
+			plain_glyphs: false,
+			faded_charts: false,
 		};
 
 		Ok(gui)
 	}
 
+	// Step up to the next font size (wrapping back around to the smallest) and
+	// resize the window to fit. The screen is still the same number of 
+	// characters across so nothing else needs to change.
+	pub fn cycle_font_scale(&mut self) -> Result<(), String> {
+		self.font_scale = (self.font_scale + 1) % self.fonts.len();
+		let (font, sm_font) = self.fonts[self.font_scale];
+		let (font_width, font_height) = font.size_of_char(' ').map_err(|e| e.to_string())?;
+		let (sm_font_width, sm_font_height) = sm_font.size_of_char(' ').map_err(|e| e.to_string())?;
+
+		self.font = font;
+		self.font_width = font_width;
+		self.font_height = font_height;
+		self.sm_font = sm_font;
+		self.sm_font_width = sm_font_width;
+		self.sm_font_height = sm_font_height;
+		self.screen_width_px = SCREEN_WIDTH * font_width + 50;
+		self.screen_height_px = SCREEN_HEIGHT * font_height;
+		self.canvas.window_mut().set_size(self.screen_width_px, self.screen_height_px)
+			.map_err(|e| e.to_string())?;
+		self.canvas.set_logical_size(self.screen_width_px, self.screen_height_px)
+			.map_err(|e| e.to_string())?;
+
+		// The cached glyphs were rendered at the old size
+		self.surface_cache.clear();
+
+		Ok(())
+	}
+
 	// I need to handle quitting the app actions here too
 	fn wait_for_key_input(&mut self) -> Option<char> {
 		loop {
@@ -169,6 +286,7 @@ impl<'a, 'b> GameUI<'a, 'b> {
 
 		let curr_map = &state.map[&map.of_map_id];
 		let red = tuple_to_sdl2_color(&BRIGHT_RED);
+		let faded = tuple_to_sdl2_color(&DARK_BROWN);
 		let screen_col = SCREEN_WIDTH / 2 - 7;
 		for r in 0..25 {
 			for c in 0..30 {
@@ -177,6 +295,11 @@ impl<'a, 'b> GameUI<'a, 'b> {
 				let actual_c = screen_col as usize + c;
 				if loc_r == map.x_coord.0 && loc_c == map.x_coord.1 {
 					self.write_map_sq(1 + r, actual_c, ('X', Color::RGBA(0, 0, 0, 255)));
+				} else if self.faded_charts && map.of_map_id == 0 
+						&& !state.world_seen.contains(&(loc_r, loc_c)) {
+					// Like an old chart, the parts of it the player hasn't
+					// been to yet are too smudged and stained to make out
+					self.write_map_sq(1 + r, actual_c, ('.', faded));
 				} else {
 					let tile = &curr_map[loc_r][loc_c];
 					let (mut ch, _) = GameUI::sq_info_for_tile(&tile);
@@ -192,8 +315,10 @@ impl<'a, 'b> GameUI<'a, 'b> {
 		self.wait_for_key_input();
 	}
 
-	pub fn show_world_map(&mut self, _state: &GameState) {
-		/*
+	// The parts of the world the player has seen, one little block per square,
+	// with the weather systems out there and which way the wind is blowing
+	pub fn show_world_map(&mut self, state: &GameState) {
+		self.canvas.set_draw_color(tuple_to_sdl2_color(&BLACK));
 		self.canvas.clear();
 
 		let title = "~Ye Olde World Map~";
@@ -205,17 +330,74 @@ impl<'a, 'b> GameUI<'a, 'b> {
 		line.push_str(title);
 		self.write_line(0, &line, false);
 
-		for sq in state.world_seen.iter() {
-			let (_, color) = GameUI::sq_info_for_tile(&state.map[sq.0][sq.1]);
-			
-			self.canvas.set_draw_color(color);
-			self.canvas.fill_rect(Rect::new(sq.1 as i32 * 3, (self.font_height + sq.0 as u32) as i32 * 3, 3, 3))
-                        .expect("Unable to draw screen!");
+		// Scale the world to fit between the title and the wind line
+		let world = &state.map[&0];
+		let avail_h = self.screen_height_px - 2 * self.font_height;
+		let sq = u32::max(1, u32::min(avail_h / world.len() as u32, 
+			self.screen_width_px / world[0].len() as u32));
+		let left = (self.screen_width_px as i32 - (sq * world[0].len() as u32) as i32) / 2;
+		let top = self.font_height as i32;
+
+		for loc in state.world_seen.iter() {
+			let (_, colour) = GameUI::sq_info_for_tile(&world[loc.0][loc.1]);
+			self.canvas.set_draw_color(colour);
+			self.canvas.fill_rect(Rect::new(left + loc.1 as i32 * sq as i32, 
+					top + loc.0 as i32 * sq as i32, sq, sq))
+				.expect("Unable to draw screen!");
+		}
+
+		// Storms get their edge outlined and a swirl at their centre
+		if let Some(weather) = state.weather.get(&0) {
+			let grey = tuple_to_sdl2_color(&LIGHT_GREY);
+			for (row, col, radius) in weather.system_locs() {
+				self.canvas.set_draw_color(grey);
+				for pt in util::bresenham_circle(row as i32, col as i32, radius) {
+					if !map::in_bounds(world, pt.0, pt.1) { continue; }
+					self.canvas.draw_point((left + pt.1 * sq as i32, top + pt.0 * sq as i32))
+						.expect("Unable to draw screen!");
+				}
+
+				let x = left + col as i32 * sq as i32 - self.sm_font_width as i32 / 2;
+				let y = top + row as i32 * sq as i32 - self.sm_font_height as i32 / 2;
+				self.write_char_at_px(x, y, '@', grey);
+			}
+		}
+
+		if state.map_id == 0 {
+			let size = u32::max(sq, 3);
+			let x = left + (state.player.col as u32 * sq) as i32 - (size / 2) as i32;
+			let y = top + (state.player.row as u32 * sq) as i32 - (size / 2) as i32;
+			self.canvas.set_draw_color(tuple_to_sdl2_color(&WHITE));
+			self.canvas.fill_rect(Rect::new(x, y, size, size))
+				.expect("Unable to draw screen!");
 		}
 
+		// The arrow points the way the wind is blowing, ie. away from where
+		// it's coming from
+		let arrows = ['\u{2191}', '\u{2197}', '\u{2192}', '\u{2198}', 
+			'\u{2193}', '\u{2199}', '\u{2190}', '\u{2196}'];
+		let towards = (state.wind as usize + 8) % 16;
+		let s = format!("Wind from the {}:", BEARINGS[state.wind as usize % 16]);
+		let row = (self.screen_height_px / self.font_height) as i32 - 1;
+		self.write_line(row, &s, false);
+		let x = 10 + ((s.len() + 1) as u32 * self.font_width) as i32;
+		self.write_char_at_px(x, row * self.font_height as i32, arrows[((towards + 1) % 16) / 2], 
+			tuple_to_sdl2_color(&WHITE));
+
 		self.canvas.present();
 		self.wait_for_key_input();
-		*/
+	}
+
+	fn write_char_at_px(&mut self, x: i32, y: i32, ch: char, colour: sdl2::pixels::Color) {
+		let surface = self.sm_font.render_char(renderable(self.sm_font, ch))
+			.blended(colour)
+			.expect("Error creating character!");
+		let texture_creator = self.canvas.texture_creator();
+		let texture = texture_creator.create_texture_from_surface(&surface)
+			.expect("Error creating texture!");
+		let rect = Rect::new(x, y, self.sm_font_width, self.sm_font_height);
+		self.canvas.copy(&texture, None, Some(rect))
+			.expect("Error copying to canvas!");
 	}
 
 	pub fn query_single_response(&mut self, question: &str, sbi: &SidebarInfo) -> Option<char> {
@@ -251,12 +433,90 @@ impl<'a, 'b> GameUI<'a, 'b> {
 				Some('u') => { return Some((-1, 1)); },
 				Some('b') => { return Some((1, -1)); },
 				Some('n') => { return Some((1, 1)); },
+				// keypad digits, if num lock is on
+				Some('4') => { return Some((0, -1)); },
+				Some('2') => { return Some((1, 0)); },
+				Some('8') => { return Some((-1, 0)); },
+				Some('6') => { return Some((0, 1)); },
+				Some('7') => { return Some((-1, -1)); },
+				Some('9') => { return Some((-1, 1)); },
+				Some('1') => { return Some((1, -1)); },
+				Some('3') => { return Some((1, 1)); },
 				Some(_) => { continue; },
 				None => { return None; },
 			}
 		}
 	}
 
+	// Lets the player move a cursor around the view to pick what to shoot at. The
+	// line of fire is drawn from the player to the cursor. start and the returned
+	// target are offsets from the player's location.
+	pub fn pick_target(&mut self, msg: &str, start: (i32, i32), range: u8, 
+				sbi: &SidebarInfo) -> Option<(i32, i32)> {
+		let half_height = (FOV_HEIGHT / 2) as i32;
+		let half_width = (FOV_WIDTH / 2) as i32;
+		let saved_v_matrix = self.v_matrix.clone();
+		let mut cursor = start;
+		let target;
+
+		loop {
+			self.v_matrix = saved_v_matrix.clone();
+			let line = util::bresenham_line(0, 0, cursor.0, cursor.1);
+			for pt in line.iter().skip(1) {
+				let i = ((pt.0 + half_height) * FOV_WIDTH as i32 + pt.1 + half_width) as usize;
+				match self.v_matrix[i] {
+					map::Tile::Blank | map::Tile::Creature(_, _) => { },
+					_ => self.v_matrix[i] = map::Tile::Bullet('*'),
+				}
+			}
+
+			// Highlight whatever is under the cursor
+			let cursor_i = ((cursor.0 + half_height) * FOV_WIDTH as i32 + cursor.1 + half_width) as usize;
+			let cursor_ch = match &saved_v_matrix[cursor_i] {
+				map::Tile::Creature(_, ch) => *ch,
+				_ => 'X',
+			};
+			self.v_matrix[cursor_i] = map::Tile::Cursor(cursor_ch);
+
+			let mut s = String::from(msg);
+			if i32::max(cursor.0.abs(), cursor.1.abs()) > range as i32 {
+				s.push_str(" (out of range)");
+			}
+			let mut m = VecDeque::new();
+			m.push_front(s);
+			self.write_screen(&mut m, sbi);
+
+			let mv = match self.wait_for_key_input() {
+				Some('h') => (0, -1),
+				Some('j') => (1, 0),
+				Some('k') => (-1, 0),
+				Some('l') => (0, 1),
+				Some('y') => (-1, -1),
+				Some('u') => (-1, 1),
+				Some('b') => (1, -1),
+				Some('n') => (1, 1),
+				Some('f') | Some('\n') => {
+					target = Some(cursor);
+					break;
+				},
+				Some(_) => (0, 0),
+				None => {
+					target = None;
+					break;
+				},
+			};
+
+			let next = (cursor.0 + mv.0, cursor.1 + mv.1);
+			if next.0.abs() <= half_height && next.1.abs() <= half_width {
+				cursor = next;
+			}
+		}
+
+		self.v_matrix = saved_v_matrix;
+
+		target
+	}
+
 	pub fn query_natural_num(&mut self, query: &str, sbi: &SidebarInfo) -> Option<u8> {
 		let mut answer = String::from("");
 
@@ -284,7 +544,7 @@ impl<'a, 'b> GameUI<'a, 'b> {
 		if answer.len() == 0 {
 			Some(0)
 		} else {
-			Some(answer.parse::<u8>().unwrap())
+			Some(answer.parse::<u8>().unwrap_or(u8::MAX))
 		}
 	}
 
@@ -320,10 +580,17 @@ impl<'a, 'b> GameUI<'a, 'b> {
 			for event in self.event_pump.poll_iter() {
 				match event {
 					Event::Quit {..} => { return Cmd::Quit },
+					Event::Window {win_event: WindowEvent::SizeChanged(..), .. } |
+					Event::Window {win_event: WindowEvent::Exposed, .. } => { return Cmd::Redraw },
 					Event::KeyDown {keycode: Some(Keycode::H), keymod: Mod::LCTRLMOD, .. } |
 					Event::KeyDown {keycode: Some(Keycode::H), keymod: Mod::RCTRLMOD, .. } => { 
 						return Cmd::MsgHistory; 
 					},
+					Event::KeyDown {keycode: Some(keycode), .. } => {
+						if let Some(cmd) = keypad_cmd(keycode, state.player.on_ship) {
+							return cmd;
+						}
+					},
 					Event::TextInput { text:val, .. } => {
 						if val == "Q" {
 							return Cmd::Quit;	
@@ -333,6 +600,8 @@ impl<'a, 'b> GameUI<'a, 'b> {
 							return Cmd::ShowCharacterSheet;	
 						} else if val == "w" {
 							return Cmd::ToggleEquipment;
+						} else if val == "W" {
+							return Cmd::AutoEquip;
 						} else if val == "." {
 							return Cmd::Pass;
 						} else if val == "B" {
@@ -341,10 +610,14 @@ impl<'a, 'b> GameUI<'a, 'b> {
 							return Cmd::Quaff;
 						} else if val == "f" {
 							return Cmd::FireGun;
+						} else if val == "v" {
+							return Cmd::QuickFire;
 						} else if val == "r" {
 							return Cmd::Reload;
 						} else if val == "M" {
 							return Cmd::WorldMap;
+						} else if val == "L" {
+							return Cmd::ShowLedger;
 						} else if val == "R" {
 							return Cmd::Read;
 						} else if val == "E" {
@@ -355,8 +628,36 @@ impl<'a, 'b> GameUI<'a, 'b> {
 							return Cmd::Chat;
 						} else if val == "U" {
                             return Cmd::Use;
-                        } else if val == "?" {
+						} else if val == "H" {
+							return Cmd::Hold;
+						} else if val == "K" {
+							return Cmd::Bestiary;
+						} else if val == "T" {
+							return Cmd::ToggleSafeStep;
+						} else if val == "t" {
+							return Cmd::Throw;
+						} else if val == "z" {
+							return Cmd::Carpentry;
+						} else if val == "Z" {
+							return Cmd::Salvage;
+						} else if val == "#" {
+							return Cmd::DumpMap;
+						} else if val == "%" {
+							return Cmd::RegenIsland;
+						} else if val == "D" {
+							return Cmd::DisarmTrap;
+						} else if val == ":" {
+							return Cmd::ExamineGround;
+						} else if val == "g" {
+							return Cmd::Give;
+						} else if val == "G" {
+							return Cmd::ToggleRide;
+						} else if val == "F" {
+							return Cmd::Flee;
+						} else if val == "?" {
 							return Cmd::Help;
+						} else if val == "+" {
+							return Cmd::CycleFontScale;
 						}
 
 						if state.player.on_ship {
@@ -366,6 +667,14 @@ impl<'a, 'b> GameUI<'a, 'b> {
 								return Cmd::TurnWheelAnticlockwise;
 							} else if val == "j" {
 								return Cmd::TurnWheelClockwise;
+							} else if val == "k" {
+								return Cmd::KedgeOff;
+							} else if val == "x" {
+								return Cmd::StepAshore;
+							} else if val == "o" {
+								return Cmd::ScanHorizon;
+							} else if val == "c" {
+								return Cmd::HandOverHelm;
 							}
 						} else {
 							if val == "k" {
@@ -392,7 +701,9 @@ impl<'a, 'b> GameUI<'a, 'b> {
 								return Cmd::Search;
 							} else if val == "e" {
                                 return Cmd::EnterPortal;
-                            }
+                            } else if val == "p" {
+								return Cmd::Peek;
+							}
 						}
 					},
 					_ => { continue },
@@ -496,6 +807,7 @@ impl<'a, 'b> GameUI<'a, 'b> {
 			map::Tile::Player(colour) => ('@', tuple_to_sdl2_color(colour)),
 			map::Tile::Water => ('}', tuple_to_sdl2_color(&LIGHT_BLUE)),
 			map::Tile::DeepWater => ('}', tuple_to_sdl2_color(&BLUE)),
+			map::Tile::Reef => ('}', tuple_to_sdl2_color(&REEF_BLUE)),
 			map::Tile::WorldEdge => ('}', tuple_to_sdl2_color(&BLUE)),
 			map::Tile::Sand => ('.', tuple_to_sdl2_color(&BEIGE)),
 			map::Tile::StoneFloor => ('.', tuple_to_sdl2_color(&GREY)),
@@ -510,13 +822,16 @@ impl<'a, 'b> GameUI<'a, 'b> {
 			map::Tile::Shipwreck(ch, _) => (*ch, tuple_to_sdl2_color(&BROWN)),
 			map::Tile::Mast(ch) => (*ch, tuple_to_sdl2_color(&BROWN)),
 			map::Tile::Bullet(ch) => (*ch, tuple_to_sdl2_color(&WHITE)),
+			map::Tile::Cursor(ch) => (*ch, tuple_to_sdl2_color(&YELLOW)),
 			map::Tile::OldFirePit => ('"', tuple_to_sdl2_color(&GREY)),
 			map::Tile::FirePit => ('"', tuple_to_sdl2_color(&BRIGHT_RED)),
 			map::Tile::Floor => ('.', tuple_to_sdl2_color(&BEIGE)),
 			map::Tile::Window(ch) => (*ch, tuple_to_sdl2_color(&BROWN)),
+			map::Tile::Barricade(_) => ('+', tuple_to_sdl2_color(&BROWN)),
 			map::Tile::Spring => ('~', tuple_to_sdl2_color(&LIGHT_BLUE)),
             map::Tile::Portal(_) => ('Ո', tuple_to_sdl2_color(&GREY)),
             map::Tile::Fog => ('#', tuple_to_sdl2_color(&LIGHT_GREY)),
+			map::Tile::Decal(colour, ch) => (*ch, tuple_to_sdl2_color(colour)),
 			map::Tile::BoulderTrap(colour, hidden, _, _, _) => {
 				if *hidden {
 					('.', tuple_to_sdl2_color(colour))
@@ -529,11 +844,25 @@ impl<'a, 'b> GameUI<'a, 'b> {
 		ti
 	}
 
+	fn tile_info(&self, tile: &map::Tile) -> (char, sdl2::pixels::Color) {
+		let (ch, colour) = GameUI::sq_info_for_tile(tile);
+		if !self.plain_glyphs {
+			return (ch, colour);
+		}
+
+		let ch = ascii_stand_in(ch);
+
+		// Brighten everything up so it stands out against the black
+		let boost = |v: u8| u16::min(v as u16 * 3 / 2, 255) as u8;
+		(ch, Color::RGBA(boost(colour.r), boost(colour.g), boost(colour.b), 255))
+	}
+
 	fn write_map_sq(&mut self, r: usize, c: usize, tile_info: (char, sdl2::pixels::Color)) {
 		let rect = Rect::new(c as i32 * self.sm_font_width as i32, 
 			(r as i32 + 1) * self.sm_font_height as i32, self.sm_font_width, self.sm_font_height);
 
 		let (ch, char_colour) = tile_info;
+		let ch = renderable(self.sm_font, ch);
 			
 		let surface = self.sm_font.render_char(ch)
 				.shaded(char_colour, tuple_to_sdl2_color(&BEIGE))
@@ -553,7 +882,7 @@ impl<'a, 'b> GameUI<'a, 'b> {
 		let (ch, char_colour) = tile_info;
 
 		if !self.surface_cache.contains_key(&tile_info) {
-			let s = self.font.render_char(ch)
+			let s = self.font.render_char(renderable(self.font, ch))
 				.blended(char_colour)
 				.expect("Error creating character!");  
 			self.surface_cache.insert(tile_info, s);
@@ -582,12 +911,25 @@ impl<'a, 'b> GameUI<'a, 'b> {
 			.expect("Error copying sbi to canvas!");
 	}
 
+	// The keys do entirely different things at the helm than on foot, so keep
+	// a reminder of which mode the player is in at the end of the message line.
+	fn write_mode(&mut self, sbi: &SidebarInfo) {
+		let (mode, colour) = if sbi.helm.is_some() {
+			("[Helm]", tuple_to_sdl2_color(&BROWN))
+		} else {
+			("[Foot]", tuple_to_sdl2_color(&GREY))
+		};
+		let x = (SCREEN_WIDTH as usize - mode.len() - 1) as i32 * self.font_width as i32;
+		self.write_sidebar_line(mode, x, 0, colour);
+	}
+
 	fn write_sidebar(&mut self, sbi: &SidebarInfo) {
 		let brown = tuple_to_sdl2_color(&BROWN);
-		let grey = tuple_to_sdl2_color(&GREY);
 		let white = tuple_to_sdl2_color(&WHITE);
 		let green = tuple_to_sdl2_color(&GREEN);
 		let gold = tuple_to_sdl2_color(&GOLD);
+		let light_blue = tuple_to_sdl2_color(&LIGHT_BLUE);
+		let red = tuple_to_sdl2_color(&BRIGHT_RED);
 
 		let fov_w = (FOV_WIDTH + 1) as i32 * self.font_width as i32; 
 		self.write_sidebar_line(&sbi.name, fov_w, 1, white);
@@ -607,6 +949,12 @@ impl<'a, 'b> GameUI<'a, 'b> {
 			line_num += 1;
 			self.write_sidebar_line(&firearm, fov_w, line_num, white);
 		}
+		if let Some((name, fuel)) = &sbi.light {
+			line_num += 1;
+			let s = format!("{}: {}", name, fuel);
+			let colour = if *fuel < LOW_FUEL { red } else { white };
+			self.write_sidebar_line(&s, fov_w, line_num, colour);
+		}
 
 
 		let s = format!("Turn: {}", sbi.turn);
@@ -623,53 +971,78 @@ impl<'a, 'b> GameUI<'a, 'b> {
 		}
 		if sbi.drunkeness > 20 {
 			self.write_sidebar_line("TIPSY", fov_w, l, brown);
+			l -= 1;
+		}
+		match sbi.thirst {
+			0 => { },
+			1 => self.write_sidebar_line("THIRSTY", fov_w, l, light_blue),
+			2 => self.write_sidebar_line("PARCHED", fov_w, l, light_blue),
+			_ => self.write_sidebar_line("DEHYDRATED", fov_w, l, red),
 		}
 
-		if sbi.bearing > -1 {
-			let mut s = String::from("Bearing: ");
-			match sbi.bearing {
-				0 => s.push_str("N"),
-				1 => s.push_str("NNE"),
-				2 => s.push_str("NE"),
-				3 => s.push_str("ENE"),
-				4 => s.push_str("E"),
-				5 => s.push_str("ESE"),
-				6 => s.push_str("SE"),
-				7 => s.push_str("SSE"),
-				8 => s.push_str("S"),
-				9 => s.push_str("SSW"),
-				10 => s.push_str("SW"),
-				11 => s.push_str("WSW"),
-				12 => s.push_str("W"),
-				13 => s.push_str("WNW"),
-				14 => s.push_str("NW"),
-				15 => s.push_str("NNW"),
-				_ => s.push_str(""),
-			}
+		if let Some((bearing, wheel, wind)) = sbi.helm {
+			self.write_helm(bearing, wheel, line_num + 2);
+			let s = format!("Wind: from {}", BEARINGS[wind as usize % 16]);
+			self.write_sidebar_line(&s, fov_w, line_num + 8, brown);
+		}
+	}
 
-			self.write_sidebar_line(&s, fov_w, line_num + 2, brown);
+	// The helm widget: the ship's heading (as a compass point plus the same bow
+	// glyph the ship is drawn with) over the wheel itself, with the spoke 
+	// marking the wheel's position picked out, and the rudder spelled out
+	// underneath. Each square is drawn once so nothing is overwritten.
+	fn write_helm(&mut self, bearing: u8, wheel: i8, row: usize) {
+		let brown = tuple_to_sdl2_color(&BROWN);
+		let white = tuple_to_sdl2_color(&WHITE);
+		let fov_w = (FOV_WIDTH + 1) as i32 * self.font_width as i32; 
 
-			let s = "      \\|/".to_string();
-			self.write_sidebar_line(&s, fov_w, line_num + 3, brown);
-			
-			let s = "      -o-".to_string();
-			self.write_sidebar_line(&s, fov_w, line_num + 4, brown);
-
-			let s = "      /|\\".to_string();
-			self.write_sidebar_line(&s, fov_w, line_num + 5, brown);
-
-			if sbi.wheel == 0 {
-				self.write_sq(line_num + 2, FOV_WIDTH + 8, ('|', grey));
-			} else if sbi.wheel == -1 {
-				self.write_sq(line_num + 2, FOV_WIDTH + 7, ('\\', grey));
-			} else if sbi.wheel == 1 {
-				self.write_sq(line_num + 2, FOV_WIDTH + 9, ('/', grey));
-			} else if sbi.wheel == 2 {
-				self.write_sq(line_num + 3, FOV_WIDTH + 9, ('-', grey));
-			} else if sbi.wheel == -2 {
-				self.write_sq(line_num + 3, FOV_WIDTH + 7, ('-', grey));
+		let s = format!("Bearing: {}", BEARINGS[bearing as usize % 16]);
+		self.write_sidebar_line(&s, fov_w, row, brown);
+		let bow = match bearing % 16 {
+			0 | 1 | 15 => ship::BOW_N,
+			2 => ship::BOW_NE,
+			3..=5 => ship::BOW_E,
+			6 => ship::BOW_SE,
+			7..=9 => ship::BOW_S,
+			10 => ship::BOW_SW,
+			11..=13 => ship::BOW_W,
+			_ => ship::BOW_NW,
+		};
+		// write_sq() rows are offset by one from sidebar rows
+		self.write_sq(row - 1, FOV_WIDTH + 2 + s.len(), (bow, white));
+
+		let spokes = [['\\', '|', '/'], ['-', 'o', '-'], ['/', '|', '\\']];
+		let marked = if wheel <= -2 {
+			(1, 0)
+		} else if wheel == -1 {
+			(0, 0)
+		} else if wheel == 0 {
+			(0, 1)
+		} else if wheel == 1 {
+			(0, 2)
+		} else {
+			(1, 2)
+		};
+
+		for (r, spoke_row) in spokes.iter().enumerate() {
+			for (c, spoke) in spoke_row.iter().enumerate() {
+				let colour = if (r, c) == marked { white } else { brown };
+				self.write_sq(row + r, FOV_WIDTH + 7 + c, (*spoke, colour));
 			}
 		}
+
+		let rudder = if wheel <= -2 {
+			"Hard a-port"
+		} else if wheel == -1 {
+			"To port"
+		} else if wheel == 0 {
+			"Steady"
+		} else if wheel == 1 {
+			"To starboard"
+		} else {
+			"Hard a-starboard"
+		};
+		self.write_sidebar_line(rudder, fov_w, row + 4, brown);
 	}
 
 	fn draw_frame(&mut self, msg: &str, sbi: &SidebarInfo) {
@@ -679,7 +1052,7 @@ impl<'a, 'b> GameUI<'a, 'b> {
 		self.write_line(0, msg, false);
 		for row in 0..FOV_HEIGHT {
 			for col in 0..FOV_WIDTH {
-				let ti = GameUI::sq_info_for_tile(&self.v_matrix[row * FOV_WIDTH + col]);
+				let ti = self.tile_info(&self.v_matrix[row * FOV_WIDTH + col]);
 				self.write_sq(row, col, ti);
 			}
 			self.write_sq(row, FOV_WIDTH, GameUI::sq_info_for_tile(&map::Tile::Separator));
@@ -687,6 +1060,7 @@ impl<'a, 'b> GameUI<'a, 'b> {
 
 		if sbi.name != "" {
 			self.write_sidebar(sbi);
+			self.write_mode(sbi);
 		}
 
 		self.canvas.present();
@@ -729,6 +1103,20 @@ impl<'a, 'b> GameUI<'a, 'b> {
 		}
 	}
 
+	// Show a list with a prompt underneath and return whichever key the 
+	// player presses (None if they hit Esc)
+	pub fn menu_query(&mut self, menu: &[String], prompt: &str, small_font: bool) -> Option<char> {
+		self.canvas.clear();
+		for (line, s) in menu.iter().enumerate() {
+			self.write_line(line as i32, s, small_font);
+		}
+		self.write_line(menu.len() as i32 + 1, "", small_font);
+		self.write_line(menu.len() as i32 + 2, prompt, small_font);
+		self.canvas.present();
+
+		self.wait_for_key_input()
+	}
+
 	// Making the assumption I'll never display a menu with more options than there are 
 	// lines on the screen...
 	pub fn menu_picker(&mut self, menu: &Vec<String>, answer_count: u8,
@@ -15,14 +15,12 @@
 
 use std::collections::{HashMap, HashSet};
 
-use crate::actor::NPCTracker;
-use crate::display::{WHITE, LIGHT_BLUE, BROWN};
+use crate::display::{WHITE, LIGHT_BLUE, BROWN, BRIGHT_RED};
 use crate::map;
-use super::{GameState, Map};
+use super::GameState;
 use crate::items::{ItemsTable, TileInfo};
 use crate::ship::Ship;
 use crate::util;
-use crate::weather::Weather;
 use super::{FOV_WIDTH, FOV_HEIGHT};
 
 // Kind of ugly by why recalculate these everytime?
@@ -96,25 +94,39 @@ fn radius_full() -> Vec<(i32, i32)> {
 // since they cover three tiles. Oh well! Just gotta get 7DRL done!
 // (That said, Rust doesn't really have objects which would make the crashRun
 // scheme complicated, I think)
-fn calc_actual_tile(r: usize, c: usize, map: &Map, 
-		npcs: &NPCTracker, items: &ItemsTable, weather: &Weather,
+fn calc_actual_tile(r: usize, c: usize, state: &GameState, items: &ItemsTable, 
             no_fog: &HashSet<(usize, usize)>) -> map::Tile {
+	let map = &state.map[&state.map_id];
+	let npcs = &state.npcs[&state.map_id];
+	let weather = &state.weather[&state.map_id];
+	let no_corpses = HashMap::new();
+	let corpses = state.corpses.get(&state.map_id).unwrap_or(&no_corpses);
+	let no_decals = HashMap::new();
+	let decals = state.decals.get(&state.map_id).unwrap_or(&no_decals);
+
+	// Decals sit on top of the terrain but underneath everything else
+	let ground = || match decals.get(&(r, c)) {
+		Some((colour, ch)) => map::Tile::Decal(*colour, *ch),
+		None => map[r][c].clone(),
+	};
 
     if weather.clouds.contains(&(r, c)) && !no_fog.contains(&(r, c)) {
         map::Tile::Fog
     } else if npcs.is_npc_at(r, c) {
 		let ti = npcs.tile_info(r, c);
 		map::Tile::Creature(ti.1, ti.0)
+	} else if corpses.contains_key(&(r, c)) {
+		map::Tile::Thing(BRIGHT_RED, '%')
 	} else if items.count_at(r, c) > 0 {
 		let i = items.peek_top(r, c);
 		if !i.hidden {
 			let ti = i.get_tile_info();
 			map::Tile::Thing(ti.0, ti.1)
 		} else {
-			map[r][c].clone()
+			ground()
 		}
 	} else {
-		map[r][c].clone()
+		ground()
 	}
 }
 
@@ -130,11 +142,14 @@ fn calc_actual_tile(r: usize, c: usize, map: &Map,
 // As well, I wanted to have the trees obscure/reduce the FOV instead of outright
 // blocking vision and I couldn't think of a simple way to do that with 
 // shadowcasting.
+// centre is the player's location, which usually but not always (when they are
+// peeking around a corner) is where the beam starts from
 fn mark_visible(r1: i32, c1: i32, r2: i32, c2: i32, 
 		state: &mut GameState, 
 		v_matrix: &mut Vec<bool>, 
         width: usize,
         no_fog: &HashSet<(usize, usize)>) {
+	let centre = (state.player.row as i32, state.player.col as i32);
 	let curr_map = &state.map[&state.map_id];
     let curr_weather = &state.weather[&state.map_id];
 
@@ -171,8 +186,13 @@ fn mark_visible(r1: i32, c1: i32, r2: i32, c2: i32,
 				return;
 			}
 
-			let vm_r = r - r1 + 10;
-			let vm_c = c - c1 + 20;
+			let vm_r = r - centre.0 + 10;
+			let vm_c = c - centre.1 + 20;
+			// Beams cast from somewhere other than the player (like a signal fire) 
+			// can run off the edge of the view
+			if vm_r < 0 || vm_c < 0 || vm_c >= width as i32 || vm_r >= (v_matrix.len() / width) as i32 {
+				return;
+			}
             let vmi = (vm_r * width as i32 + vm_c) as usize;
 			v_matrix[vmi] = true;
 			state.world_seen.insert((r as usize, c as usize));
@@ -213,8 +233,13 @@ fn mark_visible(r1: i32, c1: i32, r2: i32, c2: i32,
 				return;
 			}
 
-			let vm_r = r - r1 + 10;
-			let vm_c = c - c1 + 20;
+			let vm_r = r - centre.0 + 10;
+			let vm_c = c - centre.1 + 20;
+			// Beams cast from somewhere other than the player (like a signal fire) 
+			// can run off the edge of the view
+			if vm_r < 0 || vm_c < 0 || vm_c >= width as i32 || vm_r >= (v_matrix.len() / width) as i32 {
+				return;
+			}
             let vmi = (vm_r * width as i32 + vm_c) as usize;
 			v_matrix[vmi] = true;
 			state.world_seen.insert((r as usize, c as usize));
@@ -353,7 +378,35 @@ pub fn calc_v_matrix(
 		let actual_r = pr + loc.0;
 		let actual_c = pc + loc.1;
 
-		mark_visible(pr, pc, actual_r as i32, actual_c as i32, state, &mut visible, width, &no_fog);
+		mark_visible(pr, pc, actual_r, actual_c, state, &mut visible, width, &no_fog);
+	}
+
+	// If the player is peeking around a corner, cast a few more beams from the
+	// square they're poking their head into
+	if let Some(dir) = state.peek {
+		let peek_r = pr + dir.0;
+		let peek_c = pc + dir.1;
+		if map::in_bounds(&state.map[&state.map_id], peek_r, peek_c)
+				&& map::is_clear(&state.map[&state.map_id][peek_r as usize][peek_c as usize]) {
+			for loc in radius_3() {
+				mark_visible(peek_r, peek_c, peek_r + loc.0, peek_c + loc.1, 
+					state, &mut visible, width, &no_fog);
+			}
+		}
+	}
+
+	// A signal fire the player can see lights up the ground around it
+	if let Some((map_id, fire_r, fire_c, _)) = state.signal_fire {
+		let vm_r = fire_r as i32 - pr + fov_center_r as i32;
+		let vm_c = fire_c as i32 - pc + fov_center_c as i32;
+		if map_id == state.map_id && vm_r >= 0 && vm_c >= 0 
+				&& vm_r < height as i32 && vm_c < width as i32
+				&& visible[(vm_r * width as i32 + vm_c) as usize] {
+			for loc in radius_5() {
+				mark_visible(fire_r as i32, fire_c as i32, fire_r as i32 + loc.0, fire_c as i32 + loc.1, 
+					state, &mut visible, width, &no_fog);
+			}
+		}
 	}
 
     // Now we know which locations are actually visible from the player's loc, 
@@ -362,6 +415,7 @@ pub fn calc_v_matrix(
     // lava and so they can see neighbouring enemies)
     let mut v_matrix = vec![map::Tile::Blank; size];
 	let curr_map = &state.map[&state.map_id];
+	let mut spotted = Vec::new();
     for r in 0..height {
         for c in 0..width {
             let j = r * width + c;
@@ -370,11 +424,12 @@ pub fn calc_v_matrix(
                 let col = pc - fov_center_c as i32 + c as i32;
                 if map::in_bounds(&state.map[&state.map_id], row as i32, col as i32) {
                     v_matrix[j] = calc_actual_tile(row as usize, col as usize, 
-                                                   &state.map[&state.map_id], 
-                                                   &state.npcs[&state.map_id], 
-                                                   items, 
-                                                   &state.weather[&state.map_id],
-                                                   &no_fog);
+                                                   state, items, &no_fog);
+                    if let map::Tile::Creature(_, _) = v_matrix[j] {
+                        if let Some(t) = state.npcs[&state.map_id].type_at(row as usize, col as usize) {
+                            spotted.push(t);
+                        }
+                    }
                 }
             }
         }
@@ -383,6 +438,11 @@ pub fn calc_v_matrix(
 	add_ships_to_v_matrix(curr_map, &mut v_matrix, ships, 
 			state.player.row, state.player.col, height, width);
 
+	// Anything seen for the first time gets noted in the bestiary
+	for t in spotted {
+		state.bestiary.insert(t);
+	}
+
 	// This is where we insert the player into the view matrix. Normally the player token
 	// is always visible but when they are hit by a boulder, for comedic/dramatic effect
 	// I wanted the boulder to appear over top of them.
@@ -19,6 +19,16 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use serde::{Serialize, Deserialize};
 
 use crate::display;
+use crate::map;
+use crate::util;
+
+// Any more than this on a single square and the pick up menu runs out of
+// letters, so that's a natural limit
+pub const MAX_PILE_SIZE: usize = 26;
+
+// How far a dropped item will roll, wash or tumble looking for somewhere
+// it could be picked up again
+const MAX_SPILL_DIST: i32 = 3;
 
 pub trait TileInfo {
 	fn get_tile_info(&self) -> ((u8, u8, u8), char);
@@ -84,14 +94,18 @@ impl Inventory {
 	}
 
 	pub fn active_light_source(&self) -> bool {
+		self.active_light().is_some()
+	}
+
+	pub fn active_light(&self) -> Option<Item> {
 		for slot in self.inv.keys() {
 			let w = self.inv.get(&slot).unwrap();
 			if w.0.item_type == ItemType::Light && w.0.activated {
-				return true;
+				return Some(w.0.clone());
 			}
 		}
 
-		false
+		None
 	}
 
 	pub fn check_fueled_items(&mut self) -> Option<Vec<Item>> {
@@ -126,6 +140,30 @@ impl Inventory {
 		Some(drained)
 	}
 
+	// When a torch burns down, light the next one in the pack if there is
+	// one. Returns whether we found a torch to light.
+	pub fn light_spare_torch(&mut self) -> bool {
+		let slot = self.inv.iter()
+			.find(|(_, v)| v.0.name == "torch" && !v.0.activated && v.0.fuel > 0)
+			.map(|(k, _)| *k);
+
+		match slot {
+			Some(slot) => {
+				if self.inv[&slot].1 > 1 {
+					self.light_torch_from_stack(slot);
+				} else {
+					self.inv.get_mut(&slot).unwrap().0.activated = true;
+				}
+				true
+			},
+			None => false,
+		}
+	}
+
+	pub fn equiped_captains_hat(&self) -> bool {
+		self.inv.values().any(|v| v.0.equiped && v.0.name == "captain's tricorn")
+	}
+
 	pub fn equiped_magic_eye_patch(&self) -> bool {
 		for slot in self.inv.keys() {
 			let w = self.inv.get(&slot).unwrap();
@@ -137,6 +175,21 @@ impl Inventory {
 		false
 	}
 
+	pub fn equiped_flotation(&self) -> bool {
+		for slot in self.inv.keys() {
+			let w = self.inv.get(slot).unwrap();
+			if w.0.equiped && w.0.item_type == ItemType::Flotation {
+				return true;
+			}
+		}
+
+		false
+	}
+
+	pub fn equiped_earplugs(&self) -> bool {
+		self.type_already_equiped(ItemType::Earplugs)
+	}
+
 	pub fn get_equiped_firearm(&self) -> Option<Item> {
 		for slot in self.inv.keys() {
 			let w = self.inv.get(&slot).unwrap();
@@ -159,6 +212,17 @@ impl Inventory {
 		None
 	}
 
+	pub fn get_equiped(&self, i_type: ItemType) -> Option<Item> {
+		for slot in self.inv.keys() {
+			let w = self.inv.get(slot).unwrap();
+			if w.0.equiped && w.0.item_type == i_type {
+				return Some(w.0.clone());
+			}
+		}
+
+		None
+	}
+
 	fn type_already_equiped(&self, i_type: ItemType) -> bool {
 		for slot in self.inv.keys() {
 			let v = self.inv.get(&slot).unwrap();
@@ -170,6 +234,57 @@ impl Inventory {
 		false
 	}
 
+	pub fn equiped_slot(&self, i_type: ItemType) -> Option<char> {
+		self.inv.iter()
+			.find(|(_, v)| v.0.equiped && v.0.item_type == i_type)
+			.map(|(k, _)| *k)
+	}
+
+	// The best item of the given type the player is carrying. On a tie, what
+	// they've already got on wins so we don't shuffle gear for nothing.
+	pub fn best_gear(&self, i_type: ItemType) -> Option<char> {
+		let mut slots = self.inv.keys()
+			.filter(|k| self.inv[k].0.item_type == i_type)
+			.copied()
+			.collect::<Vec<char>>();
+		slots.sort();
+
+		let mut best: Option<(char, i32, bool)> = None;
+		for slot in slots {
+			let item = &self.inv[&slot].0;
+			let score = item.gear_score();
+			best = match best {
+				Some((_, b_score, b_equiped)) if score < b_score 
+					|| (score == b_score && (b_equiped || !item.equiped)) => best,
+				_ => Some((slot, score, item.equiped)),
+			};
+		}
+
+		best.map(|b| b.0)
+	}
+
+	pub fn enchantable_slots(&self) -> Vec<char> {
+		let mut slots = self.inv.iter()
+			.filter(|(_, v)| v.0.enchantable())
+			.map(|(k, _)| *k)
+			.collect::<Vec<char>>();
+		slots.sort();
+
+		slots
+	}
+
+	// Weapons hit harder and armour turns more blows
+	pub fn enchant(&mut self, slot: char) {
+		if let Some(v) = self.inv.get_mut(&slot) {
+			let item = &mut v.0;
+			item.enchantment += 1;
+			match item.item_type {
+				ItemType::Weapon | ItemType::Firearm => item.bonus += 1,
+				_ => item.armour_value += 1,
+			}
+		}
+	}
+
 	pub fn total_armour_value(&self) -> i8 {
 		let mut sum = 0;
 		for slot in self.inv.keys() {
@@ -212,8 +327,12 @@ impl Inventory {
 				ItemType::Firearm => String::from("You are already holding a gun."),
 				ItemType::Hat => String::from("You are already wearing a hat."),
 				ItemType::Coat => String::from("You are already wearing a coat."),
+				ItemType::Shoes => String::from("You are already wearing shoes."),
+				ItemType::Gloves => String::from("You are already wearing gloves."),
 				ItemType::EyePatch => String::from("You are already wearing an eye patch."),
 				ItemType::Fetish => String::from("Ye can benefit from just one fetish at a time."),
+				ItemType::Flotation => String::from("One cork vest will keep ye afloat well enough."),
+				ItemType::Earplugs => String::from("Your ears are already stopped up."),
 				_ => panic!("We shouldn't hit this option"),
 			}, false);
 		}
@@ -348,7 +467,26 @@ impl Inventory {
 		None
 	}
 
-	pub fn add(&mut self, item: Item) {
+	pub fn items_of_type(&self, item_type: ItemType) -> Vec<Item> {
+		self.inv.values()
+			.filter(|v| v.0.item_type == item_type)
+			.map(|v| v.0.clone())
+			.collect()
+	}
+
+	// Will the item fit? Either it joins a stack already in the pack or there
+	// must be a free slot left for it.
+	pub fn has_room_for(&self, item: &Item) -> bool {
+		if item.stackable && self.inv.values().any(|v| v.0 == *item && v.0.stackable) {
+			return true;
+		}
+
+		self.next_slot != '\0'
+	}
+
+	// Returns false (and the item isn't added) if there was no room for it, 
+	// so callers who haven't checked has_room_for() first can deal with it
+	pub fn add(&mut self, item: Item) -> bool {
 		if item.stackable {
 			// since the item is stackable, let's see if there's a stack we can add it to
 			// Super cool normal programming language way to loop over the keys of a hashtable :?
@@ -359,7 +497,7 @@ impl Inventory {
 				let mut val = self.inv.get_mut(&slot).unwrap();
 				if val.0 == item && val.0.stackable {
 					val.1 += 1;
-					return;
+					return true;
 				}
 			}
 		} 
@@ -367,11 +505,19 @@ impl Inventory {
 		// If the last slot the item occupied is still available, use that
 		// instead of the next available slot.
 		if item.prev_slot != '\0' && !self.inv.contains_key(&item.prev_slot) {
-			self.inv.insert(item.prev_slot, (item, 1));
-		} else {
+			let slot = item.prev_slot;
+			self.inv.insert(slot, (item, 1));
+			if slot == self.next_slot {
+				self.set_next_slot();
+			}
+		} else if self.next_slot != '\0' {
 			self.inv.insert(self.next_slot, (item, 1));
 			self.set_next_slot();
+		} else {
+			return false;
 		}
+
+		true
 	}
 
 	pub fn get_menu(&self) -> Vec<String> {
@@ -384,19 +530,53 @@ impl Inventory {
 		slots.sort();
 
 		for slot in slots {
-			let mut s = String::from("");
-			s.push(slot);
-			s.push_str(") ");
-			let val = self.inv.get(&slot).unwrap();
-			if val.1 == 1 {
-				s.push_str("a ");
-				s.push_str(&val.0.get_full_name());
-			} else {
-				s.push_str(&val.0.get_full_name());
-				s.push_str(" x");
-				s.push_str(&val.1.to_string());
+			menu.push(self.menu_line(slot));
+		}
+
+		menu
+	}
+
+	fn menu_line(&self, slot: char) -> String {
+		let mut s = String::from("");
+		s.push(slot);
+		s.push_str(") ");
+		let val = self.inv.get(&slot).unwrap();
+		if val.1 == 1 {
+			s.push_str("a ");
+			s.push_str(&val.0.get_full_name());
+		} else {
+			s.push_str(&val.0.get_full_name());
+			s.push_str(" x");
+			s.push_str(&val.1.to_string());
+		}
+
+		s
+	}
+
+	// Same as get_menu() but with the items sorted into categories, which is
+	// easier to scan when the player is lugging around a lot of stuff. The
+	// slot letters are unchanged.
+	pub fn get_grouped_menu(&self) -> Vec<String> {
+		let groups = ["Weapons", "Armour", "Food & Drink", "Tools", "Misc"];
+		let mut menu = Vec::new();
+
+		for (j, group) in groups.iter().enumerate() {
+			let mut slots = self.inv.iter()
+				.filter(|(_, v)| v.0.item_type.category() == j)
+				.map(|(k, _)| *k)
+				.collect::<Vec<char>>();
+			if slots.is_empty() {
+				continue;
+			}
+			slots.sort_by(|a, b| self.inv[a].0.name.cmp(&self.inv[b].0.name));
+
+			if !menu.is_empty() {
+				menu.push(String::from(""));
+			}
+			menu.push(format!("{}:", group));
+			for slot in slots {
+				menu.push(format!("  {}", self.menu_line(slot)));
 			}
-			menu.push(s);
 		}
 
 		menu
@@ -422,6 +602,101 @@ impl ItemsTable {
 		stack.push_front(item);
 	}
 
+	// The indices of the items on a square, with stackable ones grouped 
+	// together so a heap of doubloons only takes up one line of the menu
+	// (same idea as Ship::hold_lines())
+	fn pile_lines(&self, r: usize, c: usize) -> Vec<Vec<usize>> {
+		let mut lines: Vec<Vec<usize>> = Vec::new();
+		let pile = match self.table.get(&(r, c)) {
+			Some(pile) => pile,
+			None => return lines,
+		};
+
+		for j in 0..pile.len() {
+			let item = &pile[j];
+			let mut grouped = false;
+			if item.stackable {
+				for line in lines.iter_mut() {
+					if pile[line[0]] == *item {
+						line.push(j);
+						grouped = true;
+						break;
+					}
+				}
+			}
+
+			if !grouped {
+				lines.push(vec![j]);
+			}
+		}
+
+		lines
+	}
+
+	pub fn pile_size(&self, r: usize, c: usize) -> usize {
+		self.pile_lines(r, c).len()
+	}
+
+	// Stackable items can always join a stack already on the square
+	pub fn has_room(&self, r: usize, c: usize, item: &Item) -> bool {
+		if item.stackable {
+			if let Some(pile) = self.table.get(&(r, c)) {
+				if pile.iter().any(|i| i.stackable && i == item) {
+					return true;
+				}
+			}
+		}
+
+		self.pile_size(r, c) < MAX_PILE_SIZE
+	}
+
+	// Find the nearest square to (r, c) where an item can come to rest and the
+	// player could stand to pick it up again: not in the depths (where it would
+	// sink out of reach) and not on a pile that's already full. It has to be
+	// able to roll there, so it won't end up on the far side of a wall.
+	pub fn drop_loc(&self, map: &Vec<Vec<map::Tile>>, r: usize, c: usize, item: &Item) -> Option<(usize, usize)> {
+		let mut visited = HashSet::new();
+		let mut queue = VecDeque::new();
+		queue.push_back((r, c));
+		visited.insert((r, c));
+
+		while let Some(curr) = queue.pop_front() {
+			let tile = &map[curr.0][curr.1];
+			if map::is_passable(tile) && *tile != map::Tile::DeepWater 
+					&& self.has_room(curr.0, curr.1, item) {
+				return Some(curr);
+			}
+
+			for dr in -1..=1 {
+				for dc in -1..=1 {
+					if dr == 0 && dc == 0 { continue; }
+					let nr = curr.0 as i32 + dr;
+					let nc = curr.1 as i32 + dc;
+					if !map::in_bounds(map, nr, nc) { continue; }
+					let next = (nr as usize, nc as usize);
+					if visited.contains(&next) { continue; }
+					if (nr - r as i32).abs() > MAX_SPILL_DIST || (nc - c as i32).abs() > MAX_SPILL_DIST {
+						continue;
+					}
+					if !map::is_passable(&map[next.0][next.1]) { continue; }
+					visited.insert(next);
+					queue.push_back(next);
+				}
+			}
+		}
+
+		None
+	}
+
+	// For things like a monster's loot falling to the ground: if the square is
+	// already heaped high (or is somewhere it can't be reached), it tumbles 
+	// onto the nearest square that will take it
+	pub fn add_or_spill(&mut self, map: &Vec<Vec<map::Tile>>, r: usize, c: usize, item: Item) {
+		// Nowhere else for it to go so just heap it up
+		let (r, c) = self.drop_loc(map, r, c, &item).unwrap_or((r, c));
+		self.add(r, c, item);
+	}
+
 	pub fn reveal_hidden(&mut self, loc: &(usize, usize)) {
 		if !self.table.contains_key(loc) {
 			return;
@@ -433,6 +708,25 @@ impl ItemsTable {
 		}
 	}
 
+	// Throw out every pile within the rows and columns given (end exclusive)
+	pub fn clear_area(&mut self, start_r: usize, start_c: usize, end_r: usize, end_c: usize) {
+		self.table.retain(|loc, _| loc.0 < start_r || loc.0 >= end_r 
+			|| loc.1 < start_c || loc.1 >= end_c);
+	}
+
+	// Returns how many items were newly hidden
+	pub fn bury(&mut self, loc: &(usize, usize)) -> usize {
+		let mut count = 0;
+		if let Some(pile) = self.table.get_mut(loc) {
+			for item in pile.iter_mut().filter(|i| !i.hidden) {
+				item.hidden = true;
+				count += 1;
+			}
+		}
+
+		count
+	}
+
 	pub fn macguffin_here(&self, loc: &(usize, usize)) -> bool {
 		if !self.table.contains_key(loc) {
 			return false;
@@ -482,7 +776,7 @@ impl ItemsTable {
 			self.count_visible((r, c))
 		};
 
-		res as u8
+		usize::min(res, u8::MAX as usize) as u8
 	}
 
 	pub fn peek_top(&self, r: usize, c: usize) -> &Item {
@@ -495,11 +789,16 @@ impl ItemsTable {
 		stack.pop_front().unwrap()
 	}
 
-	// Putting the burden of ensuring slots sent actually exist 
+	// Putting the burden of ensuring slots sent actually exist. Picking a line
+	// of stacked items takes the whole stack.
 	pub fn get_many_at(&mut self, r: usize, c: usize, slots: &HashSet<u8>) -> Vec<Item> {
-		let mut indices = slots.iter()
-								.map(|v| *v as usize)
-								.collect::<Vec<usize>>();
+		let lines = self.pile_lines(r, c);
+		let mut indices = Vec::new();
+		for s in slots {
+			if let Some(line) = lines.get(*s as usize) {
+				indices.extend(line);
+			}
+		}
 		indices.sort();
 		indices.reverse();
 
@@ -514,15 +813,37 @@ impl ItemsTable {
 		items
 	}
 
+	// The visible items on a square, with stackable ones lumped together
+	pub fn pile_summary(&self, r: usize, c: usize) -> Vec<(Item, usize)> {
+		let mut summary: Vec<(Item, usize)> = Vec::new();
+		if !self.table.contains_key(&(r, c)) {
+			return summary;
+		}
+
+		for item in self.table[&(r, c)].iter().filter(|i| !i.hidden) {
+			match summary.iter_mut().find(|e| item.stackable && e.0 == *item) {
+				Some(entry) => entry.1 += 1,
+				None => summary.push((item.clone(), 1)),
+			}
+		}
+
+		summary
+	}
+
 	pub fn get_menu(&self, r: usize, c: usize) -> Vec<String> {
 		let mut menu = Vec::new();
 		let items = self.table.get(&(r, c)).unwrap();
+		let lines = self.pile_lines(r, c);
 		
-		for j in 0..items.len() {
+		for j in 0..usize::min(lines.len(), MAX_PILE_SIZE) {
 			let mut s = String::from("");
 			s.push(('a' as u8 + j as u8) as char);
 			s.push_str(") ");
-			s.push_str(&items[j].name);
+			s.push_str(&items[lines[j][0]].name);
+			if lines[j].len() > 1 {
+				s.push_str(" x");
+				s.push_str(&lines[j].len().to_string());
+			}
 	
 			menu.push(s);
 		}
@@ -537,6 +858,7 @@ pub enum ItemType {
 	Coat,
 	Hat,
 	Shoes,
+	Gloves,
 	Drink,
 	Firearm,
 	Bullet,
@@ -549,6 +871,46 @@ pub enum ItemType {
     Light,
     Fuel,
 	Fetish,
+	Flotation,
+	Timber,
+	Tool,
+	Earplugs,
+}
+
+impl ItemType {
+	// Which heading an item is listed under in the grouped inventory menu
+	fn category(&self) -> usize {
+		match self {
+			ItemType::Weapon | ItemType::Firearm | ItemType::Bullet => 0,
+			ItemType::Coat | ItemType::Hat | ItemType::Shoes | ItemType::Gloves
+				| ItemType::EyePatch | ItemType::Flotation | ItemType::Earplugs => 1,
+			ItemType::Drink | ItemType::Food => 2,
+			ItemType::Light | ItemType::Fuel | ItemType::Timber | ItemType::Tool => 3,
+			ItemType::Coin | ItemType::TreasureMap | ItemType::Note 
+				| ItemType::MacGuffin | ItemType::Fetish => 4,
+		}
+	}
+}
+
+// What sort of hurt a blow does. Some foes shrug off one sort of damage but
+// are easily broken by another.
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum DmgType {
+	Blunt,
+	#[default]
+	Slashing,
+	Piercing,
+	Fire,
+}
+
+// Tools all share ItemType::Tool, so this says which one an item actually is
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ToolKind {
+	Spyglass,
+	Compass,
+	Barometer,
+	Shovel,
+	Sextant,
 }
 
 // Cleaning up this struct and making it less of a dog's 
@@ -581,6 +943,12 @@ pub struct Item {
     pub activated: bool,
     pub fuel: u16,
 	pub stat_bonus: (u8, i8),
+	pub tool_kind: Option<ToolKind>,
+	#[serde(default)]
+	pub dmg_type: DmgType,
+	// How many times the sea witch has worked her magic on it
+	#[serde(default)]
+	pub enchantment: u8,
 }
 
 impl Item {
@@ -590,7 +958,23 @@ impl Item {
 				dmg: 1, dmg_dice: 1, bonus: 0, range: 0, armour_value: 0, 
 				equiped: false, loaded: false, hidden: false, nw_corner: (0, 0),
 				x_coord: (0, 0), of_map_id: 0, activated: false, fuel: 0,
-				stat_bonus: (0, 0) }
+				stat_bonus: (0, 0), tool_kind: None, dmg_type: DmgType::Slashing,
+				enchantment: 0 }
+	}
+
+	// Rough measure of how useful a weapon or piece of armour is: twice the
+	// average damage plus the bonus for weapons, the armour value otherwise
+	pub fn gear_score(&self) -> i32 {
+		match self.item_type {
+			ItemType::Weapon | ItemType::Firearm => 
+				self.dmg_dice as i32 * (self.dmg as i32 + 1) + 2 * self.bonus as i32,
+			_ => self.armour_value as i32,
+		}
+	}
+
+	pub fn enchantable(&self) -> bool {
+		matches!(self.item_type, ItemType::Weapon | ItemType::Firearm | ItemType::Coat 
+			| ItemType::Hat | ItemType::Shoes | ItemType::Gloves)
 	}
 
 	pub fn get_indefinite_article(&self) -> String {
@@ -617,12 +1001,88 @@ impl Item {
 
 	pub fn equipable(&self) -> bool {
 		match self.item_type {
-			ItemType::Weapon | ItemType::Coat | ItemType::Hat 
-				| ItemType::Firearm | ItemType::EyePatch | ItemType::Fetish => true,
+			ItemType::Weapon | ItemType::Coat | ItemType::Hat | ItemType::Shoes
+				| ItemType::Gloves | ItemType::Firearm | ItemType::EyePatch 
+				| ItemType::Fetish | ItemType::Flotation | ItemType::Earplugs => true,
 			_ => false, 
 		}
 	}
 
+	fn flavour_text(&self) -> &str {
+		match self.item_type {
+			ItemType::Weapon if self.dmg_type == DmgType::Blunt => "Good for knocking heads, or bones.",
+			ItemType::Weapon => "A blade, for when words fail ye.",
+			ItemType::Firearm => if self.name == "corroded flintlock" {
+				"Pitted with rust but it'll still fire, once loaded."
+			} else {
+				"A fine pistol. Fearsome, if slow to reload."
+			},
+			ItemType::Coat | ItemType::Hat | ItemType::Shoes | ItemType::Gloves =>
+				"Something to keep the weather, and the odd cutlass, off ye.",
+			ItemType::Drink => "Sweet, sweet rum.",
+			ItemType::Bullet => "Shot for a flintlock.",
+			ItemType::Coin => "Gold! Pieces of eight!",
+			ItemType::TreasureMap => "A crudely drawn map with an X on it.",
+			ItemType::Food => "Something to fill yer belly.",
+			ItemType::EyePatch => "Said to let its wearer see what's hidden.",
+			ItemType::Note => "A scrap of paper with some writing on it.",
+			ItemType::MacGuffin => "The treasure ye've been seeking!",
+			ItemType::Light => "Something to keep back the dark.",
+			ItemType::Fuel => "Oil to keep a lantern burning.",
+			ItemType::Fetish => "A strange charm. Some say they hold a little magic.",
+			ItemType::Flotation => "Bulky, but it'll keep ye afloat.",
+			ItemType::Earplugs => "Stop yer ears and no siren's song can lure ye.",
+			ItemType::Timber => "Good for building, or barricading.",
+			ItemType::Tool => match self.tool_kind {
+				Some(ToolKind::Spyglass) => "For spotting land from far out at sea.",
+				Some(ToolKind::Compass) => "It always knows which way is north.",
+				Some(ToolKind::Barometer) => "A storm glass. Watch it fall before bad weather.",
+				Some(ToolKind::Shovel) => "Every pirate needs one for burying treasure.",
+				Some(ToolKind::Sextant) => "With a good chart, it'll fix just where ye are.",
+				None => "A handy tool.",
+			},
+		}
+	}
+
+	// The lines shown when the player examines an item from their inventory
+	pub fn describe(&self) -> Vec<String> {
+		let mut lines = vec![util::capitalize_word(&self.get_full_name()), String::from("")];
+		lines.push(format!("  Type: {:?}", self.item_type));
+		lines.push(format!("  Weight: {}", self.weight));
+
+		match self.item_type {
+			ItemType::Weapon => lines.push(format!("  Damage: {}d{} ({:?})", self.dmg_dice, self.dmg, self.dmg_type)),
+			ItemType::Firearm => {
+				lines.push(format!("  Damage: {}d{} ({:?})", self.dmg_dice, self.dmg, self.dmg_type));
+				lines.push(format!("  Range: {}", self.range));
+				let s = if self.loaded { "  Loaded" } else { "  Unloaded" };
+				lines.push(String::from(s));
+			},
+			ItemType::Coat | ItemType::Hat | ItemType::Shoes | ItemType::Gloves 
+				| ItemType::EyePatch => {
+				lines.push(format!("  Armour: {}", self.armour_value));
+			},
+			ItemType::Drink | ItemType::Food => 
+				lines.push(format!("  Restores up to {} stamina", self.bonus)),
+			ItemType::Light => lines.push(format!("  Fuel left: {} turns", self.fuel)),
+			ItemType::Fetish => {
+				let stat = match self.stat_bonus.0 {
+					0 => "strength",
+					1 => "constitution",
+					2 => "dexterity",
+					_ => "verve",
+				};
+				lines.push(format!("  {:+} {} while active", self.stat_bonus.1, stat));
+			},
+			_ => { },
+		}
+
+		lines.push(String::from(""));
+		lines.push(String::from(self.flavour_text()));
+
+		lines
+	}
+
 	pub fn get_map(nw_corner: (usize, usize), x_coord: (usize, usize),
 				of_map_id: u8) -> Item {
 		let mut map = Item::new("treasure map", ItemType::TreasureMap, 0, false, '?', display::WHITE);
@@ -703,11 +1163,23 @@ impl Item {
 				i.dmg = 5;
 				Some(i)
 			},
+			"belaying pin" => {
+				let mut i = Item::new(name, ItemType::Weapon, 2, false, '|', display::BROWN);
+				i.dmg = 4;
+				i.dmg_type = DmgType::Blunt;
+				Some(i)
+			},
 			"battered tricorn" => {
 				let mut i = Item::new(name, ItemType::Hat, 1, false, '[', display::BROWN);
 				i.armour_value = 1;
 				Some(i)
 			},
+			"captain's tricorn" => {
+				let mut i = Item::new(name, ItemType::Hat, 1, false, '[', display::YELLOW_ORANGE);
+				i.armour_value = 1;
+				i.stat_bonus = (3, 2);
+				Some(i)
+			},
 			"leather jerkin" => {
 				let mut i = Item::new(name, ItemType::Coat, 2, false, '[', display::BROWN);
 				i.armour_value = 1;
@@ -723,6 +1195,41 @@ impl Item {
 				i.armour_value = 2;
 				Some(i)
 			},
+			"leather gloves" => {
+				let mut i = Item::new(name, ItemType::Gloves, 1, false, '[', display::BROWN);
+				i.armour_value = 1;
+				Some(i)
+			},
+			"spyglass" => {
+				let mut i = Item::new(name, ItemType::Tool, 1, false, '(', display::GOLD);
+				i.tool_kind = Some(ToolKind::Spyglass);
+				Some(i)
+			},
+			"compass" => {
+				let mut i = Item::new(name, ItemType::Tool, 0, false, '(', display::GREY);
+				i.tool_kind = Some(ToolKind::Compass);
+				Some(i)
+			},
+			"barometer" => {
+				let mut i = Item::new(name, ItemType::Tool, 2, false, '(', display::LIGHT_BLUE);
+				i.tool_kind = Some(ToolKind::Barometer);
+				Some(i)
+			},
+			"sextant" => {
+				let mut i = Item::new(name, ItemType::Tool, 1, false, '(', display::GOLD);
+				i.tool_kind = Some(ToolKind::Sextant);
+				Some(i)
+			},
+			"shovel" => {
+				let mut i = Item::new(name, ItemType::Tool, 5, false, '(', display::BROWN);
+				i.tool_kind = Some(ToolKind::Shovel);
+				Some(i)
+			},
+			"plank" => Some(Item::new(name, ItemType::Timber, 2, true, '/', display::BROWN)),
+			"cork vest" => {
+				let i = Item::new(name, ItemType::Flotation, 8, false, '[', display::BEIGE);
+				Some(i)
+			},
 			"magic eye patch" => {
 				let mut i = Item::new(name, ItemType::EyePatch, 0, false, '[', display::BRIGHT_RED);
 				i.armour_value = 0;
@@ -730,6 +1237,7 @@ impl Item {
 			},
 			"flintlock pistol" => {
 				let mut i = Item::new(name, ItemType::Firearm, 2, false, '-', display::GREY);
+				i.dmg_type = DmgType::Piercing;
 				i.loaded = true;
 				i.dmg = 6;
 				i.dmg_dice = 2;
@@ -738,6 +1246,7 @@ impl Item {
 			},
 			"corroded flintlock" => {
 				let mut i = Item::new(name, ItemType::Firearm, 2, false, '-', display::GREY);
+				i.dmg_type = DmgType::Piercing;
 				i.loaded = false;
 				i.dmg = 5;
 				i.dmg_dice = 2;
@@ -751,6 +1260,11 @@ impl Item {
 				i.bonus = 7;
 				Some(i)
 			},
+			"mango" => {
+				let mut i = Item::new(name, ItemType::Food, 1, true, '%', display::YELLOW_ORANGE);
+				i.bonus = 12;
+				Some(i)
+			},
 			"banana" => {
 				let mut i = Item::new(name, ItemType::Food, 1, true, '(', display::YELLOW);
 				i.bonus = 5;
@@ -775,6 +1289,7 @@ impl Item {
 				let f = Item::new(name, ItemType::Fuel, 1, true, '!', display::YELLOW);
 				Some(f)
             },
+			"wax earplugs" => Some(Item::new(name, ItemType::Earplugs, 0, false, '\'', display::YELLOW)),
 			"fetish" => {
 				let mut f = Item::new(&Item::fetish_name(), ItemType::Fetish, 1, false, ';', display::YELLOW_ORANGE);
 				f.stat_bonus = (rand::thread_rng().gen_range(0, 4), 2);
@@ -787,11 +1302,16 @@ impl Item {
 
 	pub fn get_full_name(&self) -> String {
 		let mut s = String::from(&self.name);
+		if self.enchantment > 0 {
+			s.push_str(&format!(" +{}", self.enchantment));
+		}
 
 		if self.equiped {
 			match self.item_type {
 				ItemType::Weapon | ItemType::Firearm => s.push_str(" (in hand)"),
-				ItemType::Coat | ItemType::Hat | ItemType::EyePatch => s.push_str(" (being worn)"),
+				ItemType::Coat | ItemType::Hat | ItemType::EyePatch 
+					| ItemType::Shoes | ItemType::Gloves 
+					| ItemType::Flotation | ItemType::Earplugs => s.push_str(" (being worn)"),
 				ItemType::Fetish => s.push_str(" (active)"),
 				_ => panic!("Should never hit this option..."),
 			}
@@ -819,3 +1339,74 @@ impl PartialEq for Item {
 	}
 }
 
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn full_pack_refuses_pickup() {
+		let mut inv = Inventory::new();
+		for _ in 0..26 {
+			assert!(inv.add(Item::get_item("rusty cutlass").unwrap()));
+		}
+
+		let mut items = ItemsTable::new();
+		items.add(0, 0, Item::get_item("lantern").unwrap());
+
+		// The same dance pick_up() does
+		let item = items.get_at(0, 0);
+		assert!(!inv.has_room_for(&item));
+		assert!(!inv.add(item.clone()));
+		items.add(0, 0, item);
+
+		assert_eq!(inv.get_menu().len(), 26);
+		assert_eq!(items.count_at(0, 0), 1);
+	}
+
+	#[test]
+	fn big_drops_spill_over() {
+		let map = vec![vec![map::Tile::Grass; 10]; 10];
+		let mut items = ItemsTable::new();
+		for _ in 0..100 {
+			items.add_or_spill(&map, 5, 5, Item::get_item("rusty cutlass").unwrap());
+		}
+
+		assert_eq!(items.pile_size(5, 5), MAX_PILE_SIZE);
+		let mut total = 0;
+		for r in 0..10 {
+			for c in 0..10 {
+				assert!(items.pile_size(r, c) <= MAX_PILE_SIZE);
+				total += items.pile_size(r, c);
+			}
+		}
+		assert_eq!(total, 100);
+	}
+
+	#[test]
+	fn stackables_share_a_pile_line() {
+		let map = vec![vec![map::Tile::Grass; 10]; 10];
+		let mut items = ItemsTable::new();
+		for _ in 0..100 {
+			items.add_or_spill(&map, 5, 5, Item::get_item("doubloon").unwrap());
+		}
+		items.add_or_spill(&map, 5, 5, Item::get_item("rusty cutlass").unwrap());
+
+		assert_eq!(items.pile_size(5, 5), 2);
+		assert_eq!(items.count_at(5, 5), 101);
+		assert_eq!(items.get_menu(5, 5).len(), 2);
+	}
+
+	#[test]
+	fn full_pack_still_stacks() {
+		let mut inv = Inventory::new();
+		inv.add(Item::get_item("doubloon").unwrap());
+		for _ in 0..25 {
+			inv.add(Item::get_item("rusty cutlass").unwrap());
+		}
+
+		let coin = Item::get_item("doubloon").unwrap();
+		assert!(inv.has_room_for(&coin));
+		assert!(inv.add(coin));
+		assert_eq!(inv.count_of_item("doubloon").unwrap().0, 2);
+	}
+}
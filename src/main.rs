@@ -19,6 +19,7 @@ extern crate serde;
 
 #[allow(dead_code)]
 mod actor;
+mod config;
 #[allow(dead_code)]
 mod content_factory;
 mod dice;
@@ -35,13 +36,15 @@ mod weather;
 
 use serde::{Serialize, Deserialize};
 
-use crate::actor::{Monster, NPCTracker, Player, PirateType};
-use crate::content_factory::generate_world;
-use crate::display::{GameUI, SidebarInfo};
-use crate::items::{Item, ItemType, ItemsTable};
+use crate::actor::{Monster, NPCTracker, Player, PirateType, Resistance, BACKGROUNDS};
+use crate::config::{Config, Endgame, Verbosity};
+use crate::content_factory::{carve_hidden_chamber, generate_world, get_hidden_chamber_items, 
+	regenerate_island, Biome};
+use crate::display::{DARK_RED, FONT_SCALES, GameUI, SidebarInfo};
+use crate::items::{DmgType, Item, ItemType, ItemsTable, ToolKind};
 use crate::map::Tile;
 use crate::pathfinding::find_path;
-use crate::ship::Ship;
+use crate::ship::{Ship, HOLD_CAPACITY};
 use crate::weather::Weather;
 
 use rand::Rng;
@@ -55,9 +58,24 @@ use std::path::Path;
 const MSG_HISTORY_LENGTH: usize = 50;
 const FOV_WIDTH: usize = 41;
 const FOV_HEIGHT: usize = 21;
+const CORPSE_LIFESPAN: u16 = 50;
+const FIRE_LIFESPAN: u16 = 8;
+const SIGNAL_FIRE_LIFESPAN: u16 = 150;
+const SIGNAL_FIRE_PLANKS: u8 = 3;
+const RAFT_PLANKS: u8 = 6;
+const SALVAGE_YIELD: u8 = 3;
+const THROW_RANGE: u8 = 6;
+const BARRICADE_HP: u8 = 12;
+const FLEE_STEPS: u8 = 4;
+const PARRY_DC: u8 = 20;
+const MERCHANT_WARY_DIST: usize = 12;
+const MAX_PURSUERS: usize = 3;
+const CAPTAINCY_SCORE: u8 = 100;
 
 pub type Map = Vec<Vec<map::Tile>>;
 pub type ShipsTable = HashMap<(usize, usize), Ship>;
+pub type Decals = HashMap<(usize, usize), ((u8, u8, u8), char)>;
+pub type Fires = HashMap<(usize, usize), (map::Tile, u16)>;
 
 pub enum ExitReason {
 	Save,
@@ -70,6 +88,7 @@ pub enum Cmd {
 	Quit,
 	Move(String),
 	MsgHistory,
+	ShowLedger,
 	PickUp,
 	ShowInventory,
 	DropItem,
@@ -82,6 +101,7 @@ pub enum Cmd {
 	ToggleHelm,
 	Quaff,
 	FireGun,
+	QuickFire,
 	Reload,
 	WorldMap,
 	Search,
@@ -92,6 +112,27 @@ pub enum Cmd {
 	Chat,
     Use,
 	Help,
+	Hold,
+	Bestiary,
+	AutoEquip,
+	ToggleSafeStep,
+	Throw,
+	Carpentry,
+	Salvage,
+	DumpMap,
+	RegenIsland,
+	DisarmTrap,
+	ExamineGround,
+	Give,
+	ToggleRide,
+	Peek,
+	Flee,
+	KedgeOff,
+	StepAshore,
+	ScanHorizon,
+	HandOverHelm,
+	CycleFontScale,
+	Redraw,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -113,6 +154,84 @@ pub struct GameState {
 	springs_drunk: HashSet<(usize, usize)>,
 	vision_radius: u8,
     weather: HashMap<u8, Weather>,
+	#[serde(default)]
+	corpses: HashMap<u8, HashMap<(usize, usize), u16>>,
+	#[serde(default)]
+	decals: HashMap<u8, Decals>,
+	#[serde(default)]
+	searched_walls: HashSet<(u8, usize, usize)>,
+	#[serde(default)]
+	hidden_chambers: HashSet<u8>,
+	last_target: Option<usize>,
+	#[serde(default)]
+	bestiary: HashSet<actor::NPCType>,
+	#[serde(default)]
+	safe_step: bool,
+	#[serde(default)]
+	fires: HashMap<u8, Fires>,
+	#[serde(default)]
+	announced: HashSet<(u8, usize)>,
+	#[serde(default)]
+	kills: HashMap<actor::NPCType, u16>,
+	#[serde(default)]
+	islands: Vec<(usize, usize, usize)>,
+	// Same order as islands
+	#[serde(default)]
+	biomes: Vec<Biome>,
+	#[serde(default)]
+	landfalls: HashSet<usize>,
+	// Shoals hidden under deep water near the islands. Only on the world map.
+	#[serde(default)]
+	sandbars: HashSet<(usize, usize)>,
+	#[serde(default)]
+	wind: u8,
+	#[serde(default)]
+	peek: Option<(i32, i32)>,
+	#[serde(default)]
+	fleeing: u8,
+	#[serde(default)]
+	signal_fire: Option<(u8, usize, usize, u16)>,
+	// Notes the player has set adrift in bottles. Only on the world map.
+	#[serde(default)]
+	bottles: Vec<(usize, usize, Item)>,
+	// How many times each bit of wreckage has been picked over
+	#[serde(default)]
+	salvaged: HashMap<(usize, usize), u8>,
+	// What was under each barricade, so it's back once one is smashed down
+	#[serde(default)]
+	barricaded: HashMap<(u8, usize, usize), Tile>,
+	// The last price the player was quoted for each sort of goods, as
+	// (currency, price). Currency is 0 for doubloons and 1 for rum.
+	#[serde(default)]
+	ledger: HashMap<String, (u8, u8)>,
+	// What navigators have told the player, kept in the back of the ledger
+	#[serde(default)]
+	navigator_hints: Vec<String>,
+	// The player found the treasure but chose to keep on playing
+	#[serde(default)]
+	won: bool,
+	// Playing the escape ending, where the treasure has to be got out of the 
+	// strait before the game is won
+	#[serde(skip)]
+	escape_ending: bool,
+	// The player has the treasure and the Navy's after them
+	#[serde(default)]
+	navy_alerted: bool,
+	#[serde(skip)]
+	verbosity: Verbosity,
+	#[serde(skip)]
+	terrain_costs: bool,
+	#[serde(skip)]
+	extra_npc_moves: u32,
+	#[serde(skip)]
+	show_damage: bool,
+	#[serde(skip)]
+	relight_torches: bool,
+	#[serde(skip)]
+	crew_aboard: usize,
+	// Where the player has parried a blow from and gets a free swing back at
+	#[serde(skip)]
+	riposte: Option<(usize, usize)>,
 }
 
 impl GameState {
@@ -135,20 +254,32 @@ impl GameState {
 			player_ship: String::from(""), pirate_lord_ship: String::from(""),
 			starter_clue: 0, notes: HashMap::new(), note_count: 0,
 			springs_drunk: HashSet::new(), vision_radius: 3, 
-            weather: HashMap::new(),
+            weather: HashMap::new(), corpses: HashMap::new(), decals: HashMap::new(),
+			searched_walls: HashSet::new(), hidden_chambers: HashSet::new(),
+			last_target: None, bestiary: HashSet::new(), safe_step: true,
+			fires: HashMap::new(), announced: HashSet::new(),
+			kills: HashMap::new(), islands: Vec::new(), biomes: Vec::new(),
+			landfalls: HashSet::new(), sandbars: HashSet::new(),
+			wind: rand::thread_rng().gen_range(0, 16), peek: None,
+			fleeing: 0, signal_fire: None, bottles: Vec::new(), salvaged: HashMap::new(), barricaded: HashMap::new(), ledger: HashMap::new(), navigator_hints: Vec::new(), won: false, escape_ending: false, navy_alerted: false, verbosity: Verbosity::Normal, terrain_costs: true, extra_npc_moves: 0,
+			show_damage: false, relight_torches: true, crew_aboard: 0, riposte: None,
+		}
+	}
+
+	// For players who like to see the numbers behind a hit
+	fn with_dmg(&self, msg: &str, dmg: u8) -> String {
+		if self.show_damage {
+			format!("{} ({} damage)", msg, dmg)
+		} else {
+			String::from(msg)
 		}
 	}
 
 	pub fn curr_sidebar_info(&self) -> SidebarInfo {
-		let bearing: i8;
-		let wheel: i8;  
-		
-		if self.player.on_ship {
-			bearing = self.player.bearing as i8;
-			wheel = self.player.wheel;
+		let helm = if self.player.on_ship {
+			Some((self.player.bearing, self.player.wheel, self.wind))
 		} else {
-			bearing = -1;
-			wheel = -1;
+			None
 		};
 
 		let w = match self.player.inventory.get_equiped_weapon() {
@@ -161,9 +292,68 @@ impl GameState {
 			Some(item) => util::capitalize_word(&item.name),
 		};
 
-		SidebarInfo::new(self.player.name.clone(), self.player.ac,
-			self.player.curr_stamina, self.player.max_stamina, wheel, bearing, self.turn,
-			self.player.charmed, self.player.poisoned, self.player.drunkeness, w, f)
+		let light = self.player.inventory.active_light()
+			.map(|item| (util::capitalize_word(&item.name), item.fuel));
+
+		SidebarInfo::new(self.title_name(), self.player.ac,
+			self.player.curr_stamina, self.player.max_stamina, helm, self.turn,
+			self.player.charmed, self.player.poisoned, self.player.drunkeness,
+			self.player.thirst_level(), w, f, light)
+	}
+
+	// The player can call themselves captain once they've sent an undead one
+	// back to his grave or made enough of a name for themselves
+	pub fn earned_captaincy(&self) -> bool {
+		self.kills.contains_key(&actor::NPCType::UndeadCaptain) 
+			|| self.player.score >= CAPTAINCY_SCORE 
+	}
+
+	pub fn title_name(&self) -> String {
+		if self.player.inventory.equiped_captains_hat() {
+			format!("Capt. {}", self.player.name)
+		} else {
+			self.player.name.clone()
+		}
+	}
+
+	pub fn record_kill(&mut self, npc_type: actor::NPCType) {
+		let count = self.kills.entry(npc_type).or_insert(0);
+		*count += 1;
+	}
+
+	// An island counts as discovered once the player has laid eyes on
+	// some of its land, not just the water around it.
+	pub fn islands_discovered(&self) -> usize {
+		(0..self.islands.len()).filter(|i| self.island_discovered(*i)).count()
+	}
+
+	pub fn island_discovered(&self, island: usize) -> bool {
+		let world = &self.map[&0];
+		let (o_r, o_c, length) = self.islands[island];
+		self.world_seen.iter().any(|(r, c)| {
+			*r >= o_r && *r < o_r + length && *c >= o_c && *c < o_c + length
+				&& map::is_land(&world[*r][*c])
+		})
+	}
+
+	// The first time the player sets foot on an island they get a sense of
+	// what sort of place it is
+	fn check_landfall(&mut self) {
+		if self.map_id != 0 || self.player.on_ship 
+				|| !map::is_land(&self.map[&0][self.player.row][self.player.col]) {
+			return;
+		}
+
+		let (pr, pc) = (self.player.row, self.player.col);
+		let island = self.islands.iter()
+			.position(|(o_r, o_c, length)| pr >= *o_r && pr < o_r + length && pc >= *o_c && pc < o_c + length);
+		if let Some(j) = island {
+			if j < self.biomes.len() && !self.landfalls.contains(&j) {
+				self.landfalls.insert(j);
+				let s = String::from(self.biomes[j].landfall_msg());
+				self.write_msg_buff(&s);
+			}
+		}
 	}
 
 	pub fn write_msg_buff(&mut self, msg: &str) {
@@ -183,9 +373,125 @@ impl GameState {
 		}
 	}
 
+	// For low-importance messages the player can opt out of seeing. At normal 
+	// verbosity we at least don't repeat the same one over and over.
+	pub fn write_flavour_msg(&mut self, msg: &str) {
+		match self.verbosity {
+			Verbosity::Terse => { },
+			Verbosity::Normal => {
+				if self.msg_history.is_empty() || msg != self.msg_history[0].0 {
+					self.write_msg_buff(msg);
+				}
+			},
+			Verbosity::Verbose => self.write_msg_buff(msg),
+		}
+	}
+
+	pub fn note_price(&mut self, item_name: &str, currency: u8, price: u8) {
+		self.ledger.insert(item_name.to_string(), (currency, price));
+	}
+
+	pub fn add_corpse(&mut self, row: usize, col: usize) {
+		let corpses = self.corpses.entry(self.map_id).or_default();
+		corpses.insert((row, col), CORPSE_LIFESPAN);
+	}
+
+	// Purely for looks: bloodstains, scattered wreckage and such. They only go
+	// on open ground, so there's no risk of them hiding deep water or lava.
+	pub fn add_decal(&mut self, map_id: u8, row: usize, col: usize, colour: (u8, u8, u8), ch: char) {
+		if !map::in_bounds(&self.map[&map_id], row as i32, col as i32) {
+			return;
+		}
+
+		match self.map[&map_id][row][col] {
+			Tile::Dirt | Tile::Grass | Tile::Sand | Tile::StoneFloor | Tile::Floor => {
+				let decals = self.decals.entry(map_id).or_default();
+				decals.insert((row, col), (colour, ch));
+			},
+			_ => { },
+		}
+	}
+
+	pub fn corpse_at(&self, row: usize, col: usize) -> bool {
+		match self.corpses.get(&self.map_id) {
+			Some(corpses) => corpses.contains_key(&(row, col)),
+			None => false,
+		}
+	}
+
+	// Find the corpse on the current map closest to the given square, returning
+	// its location and how far away it is
+	pub fn nearest_corpse(&self, row: usize, col: usize) -> Option<((usize, usize), usize)> {
+		let mut best = None;
+		if let Some(corpses) = self.corpses.get(&self.map_id) {
+			for loc in corpses.keys() {
+				let d = util::cartesian_d(row, col, loc.0, loc.1);
+				match best {
+					Some((_, best_d)) if best_d <= d => { },
+					_ => best = Some((*loc, d)),
+				}
+			}
+		}
+
+		best
+	}
+
+	pub fn decay_corpses(&mut self) {
+		for corpses in self.corpses.values_mut() {
+			corpses.retain(|_, turns_left| {
+				*turns_left -= 1;
+				*turns_left > 0
+			});
+		}
+	}
+
+	// Set a square on the current map alight for a while. We remember what was
+	// there so it can be put back once the fire burns out.
+	pub fn start_fire(&mut self, row: usize, col: usize) -> bool {
+		self.start_fire_for(row, col, FIRE_LIFESPAN)
+	}
+
+	pub fn start_fire_for(&mut self, row: usize, col: usize, lifespan: u16) -> bool {
+		let tile = self.map[&self.map_id][row][col].clone();
+		match tile {
+			Tile::Water | Tile::DeepWater | Tile::Reef | Tile::Lava | Tile::FirePit
+				| Tile::Portal(_) | Tile::Spring => return false,
+			_ => if !map::is_passable(&tile) { return false; },
+		}
+
+		let fires = self.fires.entry(self.map_id).or_default();
+		fires.insert((row, col), (tile, lifespan));
+		self.map.get_mut(&self.map_id).unwrap()[row][col] = Tile::FirePit;
+
+		true
+	}
+
+	pub fn burn_out_fires(&mut self) {
+		for (map_id, fires) in self.fires.iter_mut() {
+			let map = self.map.get_mut(map_id).unwrap();
+			fires.retain(|loc, (tile, turns_left)| {
+				*turns_left -= 1;
+				if *turns_left == 0 {
+					map[loc.0][loc.1] = tile.clone();
+				}
+				*turns_left > 0
+			});
+		}
+	}
+
+    // A hundred turns to the hour, and the game opens at noon
+    pub fn hour(&self) -> u32 {
+        (self.turn / 100 + 12) % 24
+    }
+
+    pub fn is_night(&self) -> bool {
+        let hour = self.hour();
+        !(5..21).contains(&hour)
+    }
+
     pub fn calc_vision_radius(&mut self) {
         let prev_vr = self.vision_radius;
-        let curr_time = (self.turn / 100 + 12) % 24;
+        let curr_time = self.hour();
         self.vision_radius = if curr_time >= 6 && curr_time <= 19 {
             99
         } else if curr_time >= 20 && curr_time <= 21 {
@@ -210,6 +516,11 @@ impl GameState {
 		if self.player.inventory.active_light_source() {
 			self.vision_radius += 2;
 		}
+
+		// A hand posted as lookout can see a little further into the dark
+		if self.player.on_ship && self.crew_aboard > 0 && self.vision_radius < 99 {
+			self.vision_radius += 2;
+		}
     }
 }
 
@@ -313,17 +624,20 @@ fn attack_npc(state: &mut GameState, items: &mut ItemsTable, npc_row: usize, npc
 		}
 	}
 
+	if let Some(w) = state.player.inventory.get_equiped_weapon() {
+		state.player.exert(w.weight / 2);
+	}
+
 	if do_ability_check(str_mod, npc.ac, state.player.prof_bonus as i8) {
 		let mut dmg: i8;
+		let hit_msg;
 		match state.player.inventory.get_equiped_weapon() {
 			Some(w) => {
-				let s = format!("You hit the {}!", npc.name);
-				state.write_msg_buff(&s);
+				hit_msg = format!("You hit the {}!", npc.name);
 				dmg = dice::roll(w.dmg, w.dmg_dice, w.bonus as i8) as i8 + str_mod;
 			},
 			None => {
-				let s = format!("You punch the {}!", npc.name);
-				state.write_msg_buff(&s);
+				hit_msg = format!("You punch the {}!", npc.name);
 				dmg = 1 + str_mod;
 			}
 		}
@@ -333,25 +647,52 @@ fn attack_npc(state: &mut GameState, items: &mut ItemsTable, npc_row: usize, npc
 			dmg = 0;
 		}
 
+		let dmg_type = match state.player.inventory.get_equiped_weapon() {
+			Some(w) => w.dmg_type,
+			None => DmgType::Blunt,
+		};
+		let resistance = npc.resistance(dmg_type);
+		dmg = resistance.adjust(dmg as u8) as i8;
+		let s = state.with_dmg(&hit_msg, dmg as u8);
+		state.write_msg_buff(&s);
+		resistance_msg(state, &npc, resistance);
+
 		if dmg as u8 > npc.hp {
 			let s = format!("You kill the {}!", npc.name);
 			if npc.npc_type == actor::NPCType::Skeleton {
 				state.npcs.get_mut(&state.map_id).unwrap().minion_killed(npc.boss);
 			}
+			if npc.npc_type == actor::NPCType::UndeadCaptain {
+				undead_crew_collapses(state, npc.id);
+			}
 
 			let treasure = npc.treasure_drop();
 			for item in treasure {
-				items.add(npc.row, npc.col, item);
+				items.add_or_spill(&state.map[&state.map_id], npc.row, npc.col, item);
+			}
+			for item in npc.gear.drain(..) {
+				items.add_or_spill(&state.map[&state.map_id], npc.row, npc.col, item);
+			}
+			hide_on_body(state, items, &npc);
+
+			if npc.leaves_corpse() {
+				state.add_corpse(npc.row, npc.col);
+				state.add_decal(state.map_id, npc.row, npc.col, DARK_RED, '.');
 			}
 
 			state.write_msg_buff(&s);
 			state.player.score += npc.score;
+			state.record_kill(npc.npc_type.clone());
 			if npc.score > 0 {
 				state.player.max_stamina += 1;
 			}
 			state.npcs.get_mut(&state.map_id).unwrap().remove(npc.id, npc_row, npc_col);
 		} else {
 			npc.hp -= dmg as u8;
+			if npc.hp < npc.max_hp {
+				let s = format!("The {} is {}.", npc.name, npc.health_desc());
+				state.write_msg_buff(&s);
+			}
 			state.npcs.get_mut(&state.map_id).unwrap().update(npc, npc_row, npc_col);
 		}
 	} else {
@@ -362,6 +703,33 @@ fn attack_npc(state: &mut GameState, items: &mut ItemsTable, npc_row: usize, npc
 	state.turn += 1;
 }
 
+fn resistance_msg(state: &mut GameState, npc: &Monster, resistance: Resistance) {
+	match resistance {
+		Resistance::Resists => {
+			let s = format!("The {} barely seems to notice.", npc.name);
+			state.write_msg_buff(&s);
+		},
+		Resistance::Vulnerable => {
+			let s = format!("The {} reels from the blow!", npc.name);
+			state.write_msg_buff(&s);
+		},
+		Resistance::Immune => {
+			let s = format!("It does nothing to the {}.", npc.name);
+			state.write_msg_buff(&s);
+		},
+		Resistance::Normal => { },
+	}
+}
+
+fn undead_crew_collapses(state: &mut GameState, boss_id: usize) {
+	let count = state.npcs.get_mut(&state.map_id).unwrap().boss_killed(boss_id);
+	if count == 1 {
+		state.write_msg_buff("Without its captain, the skeleton collapses into a heap of bones!");
+	} else if count > 1 {
+		state.write_msg_buff("Without their captain, the skeletons collapse into bones!");
+	}
+}
+
 fn calc_bullet_ch(dir: (i32, i32)) -> char {
 	if dir == (0, -1)  || dir == (0, 1)  { return '-'; }
 	if dir == (1, 0)   || dir == (-1, 0) { return '|'; }
@@ -370,22 +738,41 @@ fn calc_bullet_ch(dir: (i32, i32)) -> char {
 	'/'
 }
 
-fn shoot(state: &mut GameState, dir: (i32, i32), gun: &Item, dex_mod: i8, gui: &mut GameUI,
-			items: &ItemsTable, ships: &ShipsTable) {
-	let mut bullet_r = state.player.row as i32;
-	let mut bullet_c = state.player.col as i32;
-	let mut distance = 0;
-	let mut travelled = (0, 0);
+// The squares (as offsets from the shooter) a bullet passes through on its way
+// to the target. It keeps flying past the target along the same line until the
+// gun's range runs out.
+fn bullet_trajectory(target: (i32, i32), range: u8) -> Vec<(i32, i32)> {
+	let leg = util::bresenham_line(0, 0, target.0, target.1);
+	let mut path = Vec::new();
+	if leg.len() < 2 {
+		return path;
+	}
+
+	let mut n = 0;
+	while path.len() < range as usize {
+		for pt in leg.iter().skip(1) {
+			path.push((pt.0 + target.0 * n, pt.1 + target.1 * n));
+		}
+		n += 1;
+	}
+	path.truncate(range as usize);
 
-	loop {
-		bullet_r += dir.0;
-		bullet_c += dir.1;
-		travelled = (travelled.0 + dir.0, travelled.1 + dir.1);
-		distance += 1;
+	path
+}
+
+fn shoot(state: &mut GameState, target: (i32, i32), gun: &Item, called: CalledShot, gui: &mut GameUI,
+			items: &mut ItemsTable, ships: &ShipsTable) {
+	let dex_mod = Player::mod_for_stat(state.player.dexterity);
+	let mut prev = (0, 0);
+
+	for travelled in bullet_trajectory(target, gun.range) {
+		let bullet_r = state.player.row as i32 + travelled.0;
+		let bullet_c = state.player.col as i32 + travelled.1;
+		let dir = (travelled.0 - prev.0, travelled.1 - prev.1);
+		prev = travelled;
 
 		if !map::in_bounds(&state.map[&state.map_id], bullet_r, bullet_c) { break; }
 		if !map::is_passable(&state.map[&state.map_id][bullet_r as usize][bullet_c as usize]) { break; }
-		if distance > gun.range { break; }
 
 		// Sophisticated animation goes here!
 		gui.v_matrix = fov::calc_v_matrix(state, items, ships, FOV_HEIGHT, FOV_WIDTH);
@@ -408,11 +795,16 @@ fn shoot(state: &mut GameState, dir: (i32, i32), gun: &Item, dex_mod: i8, gui: &
 										.unwrap()
 										.npc_at(bullet_r as usize, bullet_c as usize)
 										.unwrap();
-			if do_ability_check(dex_mod, npc.ac, state.player.prof_bonus as i8) {
-				let s = format!("Your bullet hits the {}", npc.name);
-				state.write_msg_buff(&s);
-
+			let dc = if called == CalledShot::Body { npc.ac } else { npc.ac + 4 };
+			if do_ability_check(dex_mod, dc, state.player.prof_bonus as i8) {
 				let mut dmg = dice::roll(gun.dmg, gun.dmg_dice, gun.bonus as i8) as i8 + dex_mod;
+				let mut staggered = false;
+				if called == CalledShot::Head {
+					dmg += dice::roll(gun.dmg, 1, 0) as i8;
+				} else if called == CalledShot::Legs {
+					dmg /= 2;
+					staggered = rand::thread_rng().gen_range(0.0, 1.0) < 0.5;
+				}
 
 				npc.hostile = true;
 				npc.aware_of_player = true;
@@ -422,6 +814,19 @@ fn shoot(state: &mut GameState, dir: (i32, i32), gun: &Item, dex_mod: i8, gui: &
 				if dmg < 0 {
 					dmg = 0;
 				}
+				let resistance = npc.resistance(gun.dmg_type);
+				dmg = resistance.adjust(dmg as u8) as i8;
+
+				let s = state.with_dmg(&format!("Your bullet hits the {}", npc.name), dmg as u8);
+				state.write_msg_buff(&s);
+				if called == CalledShot::Head {
+					state.write_msg_buff("Right between the eyes!");
+				} else if staggered {
+					let s = format!("The {} staggers!", npc.name);
+					state.write_msg_buff(&s);
+					npc.slowed = true;
+				}
+				resistance_msg(state, &npc, resistance);
 
 				if dmg as u8 > npc.hp {
 					let s = format!("You kill the {}!", npc.name);
@@ -430,8 +835,20 @@ fn shoot(state: &mut GameState, dir: (i32, i32), gun: &Item, dex_mod: i8, gui: &
 									.unwrap()
 									.minion_killed(npc.boss);
 					}
+					if npc.npc_type == actor::NPCType::UndeadCaptain {
+						undead_crew_collapses(state, npc.id);
+					}
 					state.write_msg_buff(&s);
+					for item in npc.gear.drain(..) {
+						items.add_or_spill(&state.map[&state.map_id], npc.row, npc.col, item);
+					}
+					hide_on_body(state, items, &npc);
+					if npc.leaves_corpse() {
+						state.add_corpse(npc.row, npc.col);
+						state.add_decal(state.map_id, npc.row, npc.col, DARK_RED, '.');
+					}
 					state.player.score += npc.score;
+					state.record_kill(npc.npc_type.clone());
                     state.player.max_stamina += 1;
 					state.npcs.get_mut(&state.map_id)
 								.unwrap()
@@ -439,6 +856,8 @@ fn shoot(state: &mut GameState, dir: (i32, i32), gun: &Item, dex_mod: i8, gui: &
 					return; 
 				} else {
 					npc.hp -= dmg as u8;
+					let s = format!("The {} is {}.", npc.name, npc.health_desc());
+					state.write_msg_buff(&s);
 					// Rust is such bullshit sometimes...
 					let npc_r = npc.row;
 					let npc_c = npc.col;
@@ -453,23 +872,93 @@ fn shoot(state: &mut GameState, dir: (i32, i32), gun: &Item, dex_mod: i8, gui: &
 	}
 }
 
-fn fire_gun(state: &mut GameState, gui: &mut GameUI, items: &ItemsTable, 
-			ships: &ShipsTable) {
-	let dex_mod = Player::mod_for_stat(state.player.dexterity);
+// Where the targeting cursor starts: on whoever the player last shot at, if
+// they're still in view, otherwise on the nearest visible creature.
+fn initial_target(state: &mut GameState, gui: &GameUI) -> (i32, i32) {
+	let half_height = (FOV_HEIGHT / 2) as i32;
+	let half_width = (FOV_WIDTH / 2) as i32;
+	let pr = state.player.row as i32;
+	let pc = state.player.col as i32;
+
+	if let Some(id) = state.last_target {
+		if let Some(npc) = state.npcs.get_mut(&state.map_id).unwrap().npc_with_id(id) {
+			let offset = (npc.row as i32 - pr, npc.col as i32 - pc);
+			if offset.0.abs() <= half_height && offset.1.abs() <= half_width {
+				let i = ((offset.0 + half_height) * FOV_WIDTH as i32 + offset.1 + half_width) as usize;
+				if let Tile::Creature(_, _) = gui.v_matrix[i] { return offset; }
+			}
+		}
+	}
+
+	let mut best = (0, 0);
+	let mut nearest = 999;
+	for j in 0..gui.v_matrix.len() {
+		if let Tile::Creature(_, _) = gui.v_matrix[j] {
+			let offset = ((j / FOV_WIDTH) as i32 - half_height, (j % FOV_WIDTH) as i32 - half_width);
+			let d = i32::max(offset.0.abs(), offset.1.abs());
+			if d < nearest {
+				nearest = d;
+				best = offset;
+			}
+		}
+	}
+
+	best
+}
+
+// Where the player is aiming. Head shots are harder to land but hit harder,
+// leg shots do less damage but can leave the target hobbled for a turn.
+#[derive(Clone, Copy, PartialEq)]
+enum CalledShot {
+	Body,
+	Head,
+	Legs,
+}
+
+fn pick_called_shot(gui: &mut GameUI) -> Option<CalledShot> {
+	let menu = vec![String::from("Aim for:"), 
+		String::from("a) Centre mass"),
+		String::from("b) The head (harder to hit, but more damage)"),
+		String::from("c) The legs (less damage, but may slow them)")];
+
+	match gui.menu_picker(&menu, 3, true, false) {
+		Some(v) if v.contains(&1) => Some(CalledShot::Head),
+		Some(v) if v.contains(&2) => Some(CalledShot::Legs),
+		Some(_) => Some(CalledShot::Body),
+		None => None,
+	}
+}
 
+fn fire_gun(state: &mut GameState, gui: &mut GameUI, items: &mut ItemsTable, 
+			ships: &ShipsTable) {
 	match state.player.inventory.get_equiped_firearm() {
 		Some(g) => {
 			if g.loaded {
+				let start = initial_target(state, gui);
 				let sbi = state.curr_sidebar_info();
-				match gui.pick_direction("In which direction?", &sbi) {
-					Some(dir) => { 
+				match gui.pick_target("Select a target (f to fire):", start, g.range, &sbi) {
+					Some(target) if target != (0, 0) => { 
+						let called = match pick_called_shot(gui) {
+							Some(called) => called,
+							None => {
+								state.write_msg_buff("Nevermind.");
+								return;
+							},
+						};
+
+						let target_r = (state.player.row as i32 + target.0) as usize;
+						let target_c = (state.player.col as i32 + target.1) as usize;
+						if let Some(npc) = state.npcs.get_mut(&state.map_id).unwrap().npc_at(target_r, target_c) {
+							state.last_target = Some(npc.id);
+						}
+
 						state.write_msg_buff("Bang!");
-						shoot(state, dir, &g, dex_mod, gui, items, ships);
+						shoot(state, target, &g, called, gui, items, ships);
+						state.player.inventory.firearm_fired();
 						state.turn += 1;
 					},
-					None => state.write_msg_buff("Nevermind."),
+					_ => state.write_msg_buff("Nevermind."),
 				}
-				state.player.inventory.firearm_fired();
 			} else {
 				state.write_msg_buff("Click, click.");
 				state.turn += 1;
@@ -479,17 +968,59 @@ fn fire_gun(state: &mut GameState, gui: &mut GameUI, items: &ItemsTable,
 	}
 }
 
+// Fire at the closest hostile creature in view without going through the
+// targeting prompt.
+fn quick_fire(state: &mut GameState, gui: &mut GameUI, items: &mut ItemsTable, 
+			ships: &ShipsTable) {
+	match state.player.inventory.get_equiped_firearm() {
+		Some(g) => {
+			if !g.loaded {
+				state.write_msg_buff("Click, click.");
+				state.turn += 1;
+				return;
+			}
+
+			let half_height = (FOV_HEIGHT / 2) as i32;
+			let half_width = (FOV_WIDTH / 2) as i32;
+			let pr = state.player.row;
+			let pc = state.player.col;
+			let mut nearest = usize::MAX;
+			let mut best = None;
+			for j in 0..gui.v_matrix.len() {
+				if let Tile::Creature(_, _) = gui.v_matrix[j] {
+					let offset = ((j / FOV_WIDTH) as i32 - half_height, (j % FOV_WIDTH) as i32 - half_width);
+					let r = pr as i32 + offset.0;
+					let c = pc as i32 + offset.1;
+					if r < 0 || c < 0 { continue; }
+					if let Some(npc) = state.npcs.get_mut(&state.map_id).unwrap().npc_at(r as usize, c as usize) {
+						if !npc.hostile { continue; }
+						let d = util::cartesian_d(pr, pc, r as usize, c as usize);
+						if d <= g.range as usize && d < nearest {
+							nearest = d;
+							best = Some((offset, npc.id));
+						}
+					}
+				}
+			}
+
+			match best {
+				Some((target, id)) => {
+					state.last_target = Some(id);
+					state.write_msg_buff("Bang!");
+					shoot(state, target, &g, CalledShot::Body, gui, items, ships);
+					state.player.inventory.firearm_fired();
+					state.turn += 1;
+				},
+				None => state.write_msg_buff("No target in sight."),
+			}
+		},
+		None => state.write_msg_buff("You don't have a firearm ready."),
+	}
+}
+
 fn action_while_charmed(state: &mut GameState, 
 			items: &mut HashMap<u8, ItemsTable>, 
 			ships: &ShipsTable, gui: &mut GameUI) -> Result<(), ExitReason> {
-	// the charmed player attempts to swim to the mermaid
-	if state.player.on_ship {
-		state.player.on_ship = false;
-		state.write_msg_buff("You walked away from the helm.");
-		state.turn += 1;
-		return Ok(());
-	} 
-
 	let mut nearest = 999;
 	let mut best = (0, 0);
 	for r in -12..12 {
@@ -511,7 +1042,22 @@ fn action_while_charmed(state: &mut GameState,
 		}
 	}
 
-	if nearest > 1 && best != (0, 0) {
+	// If the merfolk are dead or gone, there's no one left singing
+	if best == (0, 0) {
+		state.player.charmed = false;
+		state.write_msg_buff("The singing fades, and your head clears.");
+		return Ok(());
+	}
+
+	// the charmed player attempts to swim to the mermaid
+	if state.player.on_ship {
+		state.player.on_ship = false;
+		state.write_msg_buff("You walked away from the helm.");
+		state.turn += 1;
+		return Ok(());
+	} 
+
+	if nearest > 1 {
 		let passable = map::all_passable();
 		let path = find_path(state, state.player.row, state.player.col,
 			best.0, best.1, &passable, ships);
@@ -538,7 +1084,8 @@ fn check_environment_hazards(state: &mut GameState, ships: &ShipsTable) -> Resul
 	let tile = &state.map[&state.map_id][pr][pc];
 
 	if *tile == Tile::DeepWater && !state.player.on_ship
-			&& !ships.contains_key(&(state.player.row, state.player.col)) {
+			&& !ships.contains_key(&(state.player.row, state.player.col))
+			&& !state.player.inventory.equiped_flotation() {
 		player_takes_dmg(&mut state.player, 2, "swimming")?;
 	} else if *tile == Tile::FirePit {
 		let dmg = dice::roll(6, 1, 0);
@@ -550,9 +1097,198 @@ fn check_environment_hazards(state: &mut GameState, ships: &ShipsTable) -> Resul
 	Ok(())
 }
 
+// What the player would be blundering into if they stepped onto the square,
+// provided it's somewhere they aren't already (no sense nagging them with 
+// every stroke once they're swimming)
+fn step_hazard(state: &GameState, ships: &ShipsTable, row: usize, col: usize) -> Option<&'static str> {
+	if !map::in_bounds(&state.map[&state.map_id], row as i32, col as i32) {
+		return None;
+	}
+
+	let start_tile = &state.map[&state.map_id][state.player.row][state.player.col];
+	let tile = &state.map[&state.map_id][row][col];
+	if tile == start_tile {
+		return None;
+	}
+
+	match tile {
+		Tile::Lava => Some("into the lava"),
+		Tile::FirePit => Some("into the fire"),
+		Tile::DeepWater => {
+			if ships.contains_key(&(row, col)) || state.player.inventory.equiped_flotation() {
+				None
+			} else {
+				Some("into the deep water")
+			}
+		},
+		_ => None,
+	}
+}
+
+fn nearest_adj_hostile(state: &mut GameState) -> Option<(usize, usize)> {
+	let mut nearest = None;
+	for r in -1..=1 {
+		for c in -1..=1 {
+			if r == 0 && c == 0 { continue; }
+			let adj_r = (state.player.row as i32 + r) as usize;
+			let adj_c = (state.player.col as i32 + c) as usize;
+			if let Some(npc) = state.npcs.get_mut(&state.map_id).unwrap().npc_at(adj_r, adj_c) {
+				// Orthogonal neighbours are the ones most in the way
+				if npc.hostile && (nearest.is_none() || r == 0 || c == 0) {
+					nearest = Some((adj_r, adj_c));
+				}
+			}
+		}
+	}
+
+	nearest
+}
+
+fn start_fleeing(state: &mut GameState, items: &mut ItemsTable, ships: &ShipsTable, 
+			gui: &mut GameUI) -> Result<(), ExitReason> {
+	if state.player.on_ship {
+		state.write_msg_buff("Ye can't run from the helm!");
+		return Ok(());
+	}
+
+	if !hostile_adj_player(state) {
+		state.write_msg_buff("There's nothing to run from.");
+		return Ok(());
+	}
+
+	state.fleeing = FLEE_STEPS;
+	flee_step(state, items, ships, gui)
+}
+
+// Take one step directly away from the nearest foe. We keep running for a few 
+// turns (the monsters getting their moves in between) until nothing hostile 
+// is beside the player anymore. Lava and fire are never an option, but the 
+// player will plunge into deep water if there's nowhere else to go.
+fn flee_step(state: &mut GameState, items: &mut ItemsTable, ships: &ShipsTable, 
+			gui: &mut GameUI) -> Result<(), ExitReason> {
+	let foe = match nearest_adj_hostile(state) {
+		Some(loc) => loc,
+		None => {
+			state.write_msg_buff("You've put some distance between you and your foes.");
+			state.fleeing = 0;
+			return Ok(());
+		},
+	};
+	state.fleeing -= 1;
+
+	let mut safe = None;
+	let mut risky = None;
+	let mut best_safe = 0;
+	let mut best_risky = 0;
+	for r in -1..=1 {
+		for c in -1..=1 {
+			if r == 0 && c == 0 { continue; }
+			let nr = state.player.row as i32 + r;
+			let nc = state.player.col as i32 + c;
+			if !map::in_bounds(&state.map[&state.map_id], nr, nc) { continue; }
+			let (nr, nc) = (nr as usize, nc as usize);
+			if !map::is_passable(&state.map[&state.map_id][nr][nc]) || !sq_is_open(state, ships, nr, nc) {
+				continue;
+			}
+
+			let d = util::cartesian_d(nr, nc, foe.0, foe.1);
+			match state.map[&state.map_id][nr][nc] {
+				Tile::Lava | Tile::FirePit => continue,
+				Tile::BoulderTrap(_, false, false, _, _) => continue,
+				_ => { },
+			}
+			if step_hazard(state, ships, nr, nc).is_some() {
+				if d > best_risky {
+					best_risky = d;
+					risky = Some((nr, nc));
+				}
+			} else if d > best_safe {
+				best_safe = d;
+				safe = Some((nr, nc));
+			}
+		}
+	}
+
+	let dest = match (safe, risky) {
+		(Some(loc), _) => loc,
+		(None, Some(loc)) => {
+			state.write_msg_buff("There's nowhere safe to run! You dive into the deep water.");
+			loc
+		},
+		(None, None) => {
+			state.write_msg_buff("There's nowhere to run!");
+			state.fleeing = 0;
+			return Ok(());
+		},
+	};
+
+	let dir = util::dir_between_sqs(state.player.row, state.player.col, dest.0, dest.1);
+	do_move(state, items, ships, &dir, gui)
+}
+
+fn hostile_adj_player(state: &mut GameState) -> bool {
+	for r in -1..=1 {
+		for c in -1..=1 {
+			if r == 0 && c == 0 { continue; }
+			let adj_r = (state.player.row as i32 + r) as usize;
+			let adj_c = (state.player.col as i32 + c) as usize;
+			if let Some(npc) = state.npcs.get_mut(&state.map_id).unwrap().npc_at(adj_r, adj_c) {
+				if npc.hostile {
+					return true;
+				}
+			}
+		}
+	}
+
+	false
+}
+
+// Swimming in open water is slow going. A strong or nimble swimmer will make
+// headway more often.
+fn swim_check(state: &GameState) -> bool {
+	let str_mod = Player::mod_for_stat(state.player.strength);
+	let dex_mod = Player::mod_for_stat(state.player.dexterity);
+	do_ability_check(i8::max(str_mod, dex_mod), 8, 0)
+}
+
+// Whole turns for a step onto the square. A fractional cost is the chance 
+// of its taking an extra turn.
+fn terrain_turns(tile: &Tile) -> u32 {
+	let cost = map::move_cost(tile);
+	let mut turns = cost as u32;
+	if rand::thread_rng().gen_range(0.0, 1.0) < cost.fract() {
+		turns += 1;
+	}
+
+	turns
+}
+
 fn do_move(state: &mut GameState, items: &mut ItemsTable, ships: &ShipsTable, dir: &str, gui: &mut GameUI) -> Result<(), ExitReason> {
 	let mut mv = get_move_tuple(dir);
 
+	// In safe step mode, a step into something dangerous needs confirming, in 
+	// case it was just a fat-fingered key. Not when there's a monster at hand
+	// though: then it's no time to dither.
+	if state.safe_step {
+		let dest_r = state.player.row as i32 + mv.0;
+		let dest_c = state.player.col as i32 + mv.1;
+		let hazard = if map::in_bounds(&state.map[&state.map_id], dest_r, dest_c) {
+			step_hazard(state, ships, dest_r as usize, dest_c as usize)
+		} else {
+			None
+		};
+		if let Some(hazard) = hazard {
+			if !hostile_adj_player(state) {
+				let s = format!("Really step {}? (y/n)", hazard);
+				let sbi = state.curr_sidebar_info();
+				if gui.query_yes_no(&s, &sbi) != 'y' {
+					state.write_msg_buff("Nevermind.");
+					return Ok(());
+				}
+			}
+		}
+	}
+
 	// if the player is poisoned they'll sometimes stagger
 	if state.player.poisoned || state.player.drunkeness > 20 {
 		if rand::thread_rng().gen_range(0.0, 1.0) < 0.25 {
@@ -567,27 +1303,63 @@ fn do_move(state: &mut GameState, items: &mut ItemsTable, ships: &ShipsTable, di
 	let next_loc = (next_row, next_col);
 	let tile = &state.map[&state.map_id][next_row][next_col].clone();
 	
-	if state.npcs[&state.map_id].is_npc_at(next_row, next_col) {
-		attack_npc(state, items, next_row, next_col, gui);
-	} else if ships.contains_key(&next_loc) {
-		state.player.col = next_col;
+	let tame_npc = match state.npcs.get_mut(&state.map_id).unwrap().npc_at(next_row, next_col) {
+		Some(npc) => npc.tame,
+		None => false,
+	};
+
+	if tame_npc {
+		// Step past a tame animal rather than run it through
+		let mut npc = state.npcs.get_mut(&state.map_id).unwrap().npc_at(next_row, next_col).unwrap();
+		npc.row = state.player.row;
+		npc.col = state.player.col;
+		let s = format!("You swap places with the {}.", npc.name);
+		state.write_msg_buff(&s);
+		state.npcs.get_mut(&state.map_id).unwrap().update(npc, next_row, next_col);
+		state.player.row = next_row;
+		state.player.col = next_col;
+		state.turn += 1;
+	} else if state.npcs[&state.map_id].is_npc_at(next_row, next_col) {
+		attack_npc(state, items, next_row, next_col, gui);
+	} else if state.player.mount.is_some() && (ships.contains_key(&next_loc) 
+			|| *tile == map::Tile::DeepWater) {
+		let s = format!("The {} won't go that way.", state.player.mount.as_ref().unwrap().name);
+		state.write_msg_buff(&s);
+	} else if ships.contains_key(&next_loc) && ships[&next_loc].merchant {
+		let s = format!("The crew of the {} won't let ye aboard.", ships[&next_loc].name);
+		state.write_msg_buff(&s);
+	} else if ships.contains_key(&next_loc) {
+		state.player.col = next_col;
 		state.player.row = next_row;
 		let ship = ships.get(&next_loc).unwrap();
 		let s = format!("You climb aboard the {}.", ship.name);
 		state.write_msg_buff(&s);
 		state.turn += 1;
+	} else if *tile == map::Tile::DeepWater && *start_tile == map::Tile::DeepWater
+			&& !ships.contains_key(&(state.player.row, state.player.col)) && !swim_check(state) {
+		state.write_msg_buff("You struggle against the waves.");
+		state.turn += 1;
 	} else if map::is_passable(tile) {
 		state.player.col = next_col;
 		state.player.row = next_row;
+		let new_ground = tile != start_tile;
 
+		// Only describe the ground underfoot when it changes, otherwise a long
+		// wade through the shallows fills the log with splashing
 		match tile {
-			map::Tile::Water => state.write_msg_buff("You splash in the shallow water."),
+			map::Tile::Water if new_ground => state.write_flavour_msg("You splash in the shallow water."),
+			map::Tile::Reef if new_ground => state.write_flavour_msg("You pick your way over the coral."),
 			map::Tile::DeepWater => {
+				let afloat = state.player.inventory.equiped_flotation();
 				if *start_tile != map::Tile::DeepWater {
-					state.write_msg_buff("You begin to swim.");				
+					if afloat {
+						state.write_flavour_msg("You bob along in your cork vest.");
+					} else {
+						state.write_msg_buff("You begin to swim.");				
+					}
 				}
 
-				if state.player.curr_stamina < 10 {
+				if state.player.curr_stamina < 10 && !afloat {
 					state.write_msg_buff("You're getting tired...");
 				}
 			},
@@ -629,7 +1401,20 @@ fn do_move(state: &mut GameState, items: &mut ItemsTable, ships: &ShipsTable, di
 			state.write_msg_buff("You see a few items here.");
 		}	
 
-		state.turn += 1;
+		let turns = if state.terrain_costs { terrain_turns(tile) } else { 1 };
+		if turns > 1 && new_ground {
+			match tile {
+				map::Tile::Tree => state.write_flavour_msg("You push through the undergrowth."),
+				map::Tile::Sand => state.write_flavour_msg("Your feet sink into the soft sand."),
+				_ => { },
+			}
+		}
+
+		// Riding, the player covers ground twice as fast
+		if state.player.mount.is_none() || rand::thread_rng().gen_range(0.0, 1.0) < 0.5 {
+			state.turn += turns;
+			state.extra_npc_moves = turns - 1;
+		}
 	} else  {
 		state.write_msg_buff("You cannot go that way.");
 	}
@@ -637,15 +1422,119 @@ fn do_move(state: &mut GameState, items: &mut ItemsTable, ships: &ShipsTable, di
 	Ok(())
 }
 
+// Portals drop the player on the matching portal at the other end so they can
+// step right back through, but if something is standing there (a rat camped
+// out by the cave mouth, say) or the square isn't one they can stand on, we
+// nudge them to a free neighbouring square instead.
+fn portal_landing_sq(state: &GameState, ships: &ShipsTable, row: usize, col: usize) -> Option<(usize, usize)> {
+	let curr_map = &state.map[&state.map_id];
+	if map::is_passable(&curr_map[row][col]) && !state.npcs[&state.map_id].is_npc_at(row, col) {
+		return Some((row, col));
+	}
+
+	let mut sqs: Vec<(usize, usize)> = Vec::new();
+	for r in -1..2 {
+		for c in -1..2 {
+			if r == 0 && c == 0 { continue; }
+			let nr = row as i32 + r;
+			let nc = col as i32 + c;
+			if !map::in_bounds(curr_map, nr, nc) { continue; }
+
+			let (nr, nc) = (nr as usize, nc as usize);
+			match curr_map[nr][nc] {
+				Tile::Portal(_) => continue,
+				_ => if !map::is_passable(&curr_map[nr][nc]) { continue; },
+			}
+			if sq_is_open(state, ships, nr, nc) {
+				sqs.push((nr, nc));
+			}
+		}
+	}
+
+	if sqs.is_empty() {
+		None
+	} else {
+		let j = (dice::roll(sqs.len() as u8, 1, 0) - 1) as usize;
+		Some(sqs[j])
+	}
+}
+
+// Hostile monsters right on the player's heels when they go through a portal 
+// follow them through to the other side, if there's room for them there.
+fn portal_pursuers(state: &mut GameState, ships: &ShipsTable, prev_map_id: u8, prev_loc: (usize, usize)) {
+	let mut pursuers = Vec::new();
+	for r in -1..=1 {
+		for c in -1..=1 {
+			if r == 0 && c == 0 { continue; }
+			let adj_r = (prev_loc.0 as i32 + r) as usize;
+			let adj_c = (prev_loc.1 as i32 + c) as usize;
+			if let Some(npc) = state.npcs.get_mut(&prev_map_id).unwrap().npc_at(adj_r, adj_c) {
+				let can_follow = !matches!(npc.npc_type, 
+					actor::NPCType::Boulder | actor::NPCType::Shark | actor::NPCType::Merfolk);
+				if npc.hostile && npc.aware_of_player && can_follow {
+					pursuers.push(npc);
+				}
+			}
+		}
+	}
+
+	for npc in pursuers {
+		let mut dest = None;
+		let curr_map = &state.map[&state.map_id];
+		for r in -1..=1 {
+			for c in -1..=1 {
+				let nr = state.player.row as i32 + r;
+				let nc = state.player.col as i32 + c;
+				if !map::in_bounds(curr_map, nr, nc) { continue; }
+				let (nr, nc) = (nr as usize, nc as usize);
+				match curr_map[nr][nc] {
+					Tile::Portal(_) | Tile::Lava | Tile::FirePit | Tile::DeepWater => continue,
+					_ => if !map::is_passable(&curr_map[nr][nc]) { continue; },
+				}
+				if dest.is_none() && sq_is_open(state, ships, nr, nc) {
+					dest = Some((nr, nc));
+				}
+			}
+		}
+
+		if let Some(loc) = dest {
+			let s = format!("The {} follows you through!", npc.name);
+			state.write_msg_buff(&s);
+			let prev_npcs = state.npcs.get_mut(&prev_map_id).unwrap();
+			prev_npcs.remove(npc.id, npc.row, npc.col);
+			if npc.npc_type == actor::NPCType::Skeleton {
+				prev_npcs.minion_killed(npc.boss);
+			}
+			state.npcs.get_mut(&state.map_id).unwrap().adopt(npc, loc.0, loc.1);
+		}
+	}
+}
+
 fn enter_portal(state: &mut GameState, items: &HashMap<u8, ItemsTable>, 
-                ships: &ShipsTable,  gui: &mut GameUI) {
+                ships: &HashMap<u8, ShipsTable>,  gui: &mut GameUI) {
     match state.map[&state.map_id][state.player.row][state.player.col] {
         Tile::Portal((pr, pc, map_id)) => {
+			let prev_map_id = state.map_id;
+			let (prev_row, prev_col) = (state.player.row, state.player.col);
             state.map_id = map_id;
-            state.player.row = pr;
-            state.player.col = pc;
-            let map_items = ItemsTable::new();
-            gui.v_matrix = fov::calc_v_matrix(state, &map_items, ships, FOV_HEIGHT, FOV_WIDTH);
+			state.player.row = pr;
+			state.player.col = pc;
+			match portal_landing_sq(state, &ships[&map_id], pr, pc) {
+				Some((r, c)) => {
+					state.player.row = r;
+					state.player.col = c;
+				},
+				None => {
+					// The far side is completely blocked so the player stays put
+					state.map_id = prev_map_id;
+					state.player.row = prev_row;
+					state.player.col = prev_col;
+					state.write_msg_buff("Something blocks the way through.");
+					return;
+				},
+			}
+			portal_pursuers(state, &ships[&map_id], prev_map_id, (prev_row, prev_col));
+            gui.v_matrix = fov::calc_v_matrix(state, &items[&map_id], &ships[&map_id], FOV_HEIGHT, FOV_WIDTH);
             let sbi = state.curr_sidebar_info();
             gui.write_screen(&mut state.msg_buff, &sbi);
 			state.turn += 1;
@@ -654,6 +1543,42 @@ fn enter_portal(state: &mut GameState, items: &HashMap<u8, ItemsTable>,
     }
 }
 
+// What the merchants the player has dealt with were asking, so they can tell
+// whether the next offer is a bargain or a swindle
+fn show_ledger(state: &mut GameState, gui: &mut GameUI) {
+	if state.ledger.is_empty() && state.navigator_hints.is_empty() {
+		state.write_msg_buff("Ye've not been quoted any prices yet.");
+		return;
+	}
+
+	let mut names = state.ledger.keys()
+						.cloned()
+						.collect::<Vec<String>>();
+	names.sort();
+
+	let mut lines = vec!["~Merchant's ledger~".to_string(), "".to_string()];
+	for name in names {
+		let (currency, price) = state.ledger[&name];
+		let currency_name = match (currency, price) {
+			(0, 1) => "doubloon",
+			(0, _) => "doubloons",
+			(_, 1) => "draught of rum",
+			_ => "draughts of rum",
+		};
+		lines.push(format!("  {}: {} {} apiece", name, price, currency_name));
+	}
+
+	if !state.navigator_hints.is_empty() {
+		lines.push("".to_string());
+		lines.push("What the navigators told ye:".to_string());
+		for hint in state.navigator_hints.iter() {
+			lines.push(format!("  {}", hint));
+		}
+	}
+
+	gui.write_long_msg(&lines, true);
+}
+
 fn show_message_history(state: &GameState, gui: &mut GameUI) {
 	let mut lines = Vec::new();
 	lines.push("".to_string());
@@ -706,40 +1631,107 @@ fn consume_nourishment(state: &mut GameState, item: &Item) {
 	let hp = dice::roll(item.bonus, 1, 0);
 	state.player.add_stamina(hp);
 
+	// Rum and salted pork keep ye going but they'll leave ye parched
 	if item.name == "draught of rum" {
 		state.write_msg_buff("You drink some rum.");
 		state.player.drunkeness += 10;
+		state.player.thirst = state.player.thirst.saturating_add(150);
 	} else if item.name == "coconut" {
-		state.write_msg_buff("Munch munch.");
+		state.write_msg_buff("Munch munch. The milk is cool and sweet.");
+		state.player.quench(400);
 	} else if item.name == "banana" {
 		state.write_msg_buff("Munch munch.");
 	} else if item.name == "salted pork" {
-		state.write_msg_buff("Not very satisfying.");
+		state.write_msg_buff("Not very satisfying, and it's awfully salty.");
+		state.player.thirst = state.player.thirst.saturating_add(100);
 	}
+
+	if (item.name == "coconut" || item.name == "banana" || item.name == "mango")
+			&& state.player.cure_scurvy() {
+		state.write_msg_buff("Your gums stop aching. The scurvy's lifting!");
+	}
+}
+
+// Weeks at sea on salt pork and rum will give a pirate scurvy, which slowly
+// saps their strength until they eat some fruit
+fn check_scurvy(state: &mut GameState) {
+	state.player.since_fruit = state.player.since_fruit.saturating_add(1);
+	let since = state.player.since_fruit;
+	if since < actor::SCURVY_ONSET || !(since - actor::SCURVY_ONSET).is_multiple_of(actor::SCURVY_INTERVAL) {
+		return;
+	}
+
+	if state.player.scurvy >= actor::MAX_SCURVY || state.player.max_stamina <= 2 {
+		return;
+	}
+
+	if state.player.scurvy == 0 {
+		state.write_msg_buff("Your gums ache... Ye could do with some fruit.");
+	} else {
+		state.write_msg_buff("Your teeth feel loose and you tire easily. Scurvy!");
+	}
+
+	state.player.scurvy += 2;
+	state.player.max_stamina -= 2;
+	if state.player.curr_stamina > state.player.max_stamina {
+		state.player.curr_stamina = state.player.max_stamina;
+	}
+}
+
+// Thirst creeps up every turn. A thirsty pirate doesn't get their wind back,
+// and past that it starts to do real harm.
+fn check_thirst(state: &mut GameState) -> Result<(), ExitReason> {
+	let before = state.player.thirst_level();
+	state.player.thirst = state.player.thirst.saturating_add(1);
+	let level = state.player.thirst_level();
+
+	if level > before {
+		match level {
+			1 => state.write_msg_buff("You are getting thirsty."),
+			2 => state.write_msg_buff("Your throat is parched!"),
+			_ => state.write_msg_buff("You are dying of thirst!"),
+		}
+	}
+
+	if (level == 2 && state.turn.is_multiple_of(20)) || (level == 3 && state.turn.is_multiple_of(5)) {
+		player_takes_dmg(&mut state.player, 1, "thirst")?;
+	}
+
+	Ok(())
 }
 
 fn quaff_spring(state: &mut GameState) {
 	let loc = (state.player.row, state.player.col);
+	state.player.thirst = 0;
 	if state.springs_drunk.contains(&loc) {
 		state.write_msg_buff("You feel refreshed.");
+		state.write_msg_buff("This spring's magic is spent.");
 	} else {
 		let roll = rand::thread_rng().gen_range(0.0, 1.0);
 
 		if roll < 0.25 {
 			state.player.strength += 4;
 			state.write_msg_buff("You feel mighty!");
+			let s = format!("Your strength rises to {}!", state.player.strength);
+			state.write_msg_buff(&s);
 		} else if roll < 0.50 {
 			state.player.dexterity += 4;
 			state.write_msg_buff("You feel adroit!");
+			let s = format!("Your dexterity rises to {}!", state.player.dexterity);
+			state.write_msg_buff(&s);
 			state.player.calc_ac();
 		} else if roll < 0.75 {
 			state.player.constitution += 4;
 			state.write_msg_buff("You feel tough!");
+			let s = format!("Your constitution rises to {}!", state.player.constitution);
+			state.write_msg_buff(&s);
 			state.player.max_stamina += 10;
 			state.player.add_stamina(10);
 		} else {
 			state.player.verve += 4;
 			state.write_msg_buff("You feel like you have more moxie!");
+			let s = format!("Your verve rises to {}!", state.player.verve);
+			state.write_msg_buff(&s);
 		}
 
 		state.springs_drunk.insert(loc);
@@ -805,7 +1797,234 @@ fn eat(state: &mut GameState, gui: &mut GameUI) {
 	}
 }
 
-fn refuel_lantern(state: &mut GameState, slot: char, gui: &mut GameUI) {
+// Everything the player can (U)se has a handler with this signature. The slot
+// is where the item being used is in the player's inventory.
+type UseHandler = fn(&mut GameState, char, &mut ItemsTable, &ShipsTable, &mut GameUI);
+
+fn use_handler(item: &Item) -> Option<UseHandler> {
+	match item.item_type {
+		ItemType::Light => Some(toggle_light),
+		ItemType::Fuel => Some(refuel_lantern),
+		ItemType::Timber => Some(build_signal_fire),
+		ItemType::Tool => match item.tool_kind {
+			Some(ToolKind::Spyglass) => Some(use_spyglass),
+			Some(ToolKind::Compass) => Some(use_compass),
+			Some(ToolKind::Barometer) => Some(use_barometer),
+			Some(ToolKind::Shovel) => Some(use_shovel),
+			Some(ToolKind::Sextant) => Some(use_sextant),
+			None => None,
+		},
+		_ => None,
+	}
+}
+
+fn toggle_light(state: &mut GameState, slot: char, _items: &mut ItemsTable, _ships: &ShipsTable, _gui: &mut GameUI) {
+	let result = state.player.inventory.toggle_slot(slot);
+	state.write_msg_buff(&result.0);
+	state.turn += 1;
+}
+
+// The spyglass points out the nearest land when you're at sea
+fn use_spyglass(state: &mut GameState, _slot: char, _items: &mut ItemsTable, _ships: &ShipsTable, _gui: &mut GameUI) {
+	let pr = state.player.row as i32;
+	let pc = state.player.col as i32;
+	let curr_map = &state.map[&state.map_id];
+	let here = &curr_map[state.player.row][state.player.col];
+	state.turn += 1;
+
+	if *here != Tile::Water && *here != Tile::DeepWater && *here != Tile::Reef {
+		state.write_msg_buff("You don't see anything interesting through the spyglass.");
+		return;
+	}
+
+	let mut nearest = None;
+	let mut nearest_d = usize::MAX;
+	for r in pr - 60..=pr + 60 {
+		for c in pc - 60..=pc + 60 {
+			if !map::in_bounds(curr_map, r, c) { continue; }
+			match curr_map[r as usize][c as usize] {
+				Tile::Water | Tile::DeepWater | Tile::Reef | Tile::WorldEdge => continue,
+				_ => { },
+			}
+			let d = util::cartesian_d(state.player.row, state.player.col, r as usize, c as usize);
+			if d < nearest_d {
+				nearest_d = d;
+				nearest = Some((r as usize, c as usize));
+			}
+		}
+	}
+
+	match nearest {
+		Some(loc) => {
+			let dir = util::dir_between_sqs(state.player.row, state.player.col, loc.0, loc.1);
+			let s = format!("Land ho! To {}, about {} leagues off.", compass_name(&dir), nearest_d / 10 + 1);
+			state.write_msg_buff(&s);
+		},
+		None => state.write_msg_buff("Nothing but open sea as far as the spyglass can see."),
+	}
+}
+
+// From the deck on a clear day, a sharp-eyed pirate can make out a smudge of
+// land on the horizon well before it comes into view proper
+fn scan_horizon(state: &mut GameState, ships: &ShipsTable) {
+	state.turn += 1;
+
+	let bearing = match ships.get(&(state.player.row, state.player.col)) {
+		Some(ship) if state.map_id == 0 => ship.bearing,
+		_ => {
+			state.write_msg_buff("You'll need to be at sea to scan the horizon.");
+			return;
+		},
+	};
+
+	if state.vision_radius < 99 {
+		state.write_msg_buff("It's too dark to make out the horizon.");
+		return;
+	}
+	if state.weather[&0].clouds.contains(&(state.player.row, state.player.col)) {
+		state.write_msg_buff("You can't see past the fog.");
+		return;
+	}
+
+	let mut nearest = None;
+	let mut nearest_d = usize::MAX;
+	for i in 0..state.islands.len() {
+		if state.island_discovered(i) { continue; }
+		let (o_r, o_c, length) = state.islands[i];
+		let centre = (o_r + length / 2, o_c + length / 2);
+		let d = util::cartesian_d(state.player.row, state.player.col, centre.0, centre.1);
+		if d < nearest_d {
+			nearest_d = d;
+			nearest = Some(centre);
+		}
+	}
+
+	match nearest {
+		Some(loc) => {
+			let dir = util::dir_between_sqs(state.player.row, state.player.col, loc.0, loc.1);
+			let s = format!("Land ho! {}, to {}!", relative_to_bow(bearing, &dir), compass_name(&dir));
+			state.write_msg_buff(&s);
+		},
+		None => state.write_msg_buff("Ye've sighted every island in these waters."),
+	}
+}
+
+// The barometer gives warning of fog rolling in while there's still time
+// to drop anchor or change course
+fn use_barometer(state: &mut GameState, _slot: char, _items: &mut ItemsTable, _ships: &ShipsTable, _gui: &mut GameUI) {
+	state.turn += 1;
+
+	let weather = match state.weather.get(&state.map_id) {
+		Some(w) => w,
+		None => {
+			state.write_msg_buff("The glass is steady.");
+			return;
+		},
+	};
+
+	let s = match weather.nearest_system(state.player.row, state.player.col, 40) {
+		Some((_, 0, _)) => String::from("The glass has bottomed out. You're in the thick of it."),
+		Some((centre, _, intensity)) => {
+			let dir = util::dir_between_sqs(state.player.row, state.player.col, centre.0, centre.1);
+			let fog = if intensity > 0.5 { "a thick fog" } else { "a fog" };
+			format!("The glass is falling; {} brews to {}.", fog, compass_name(&dir))
+		},
+		None => String::from("The glass is steady. Fair weather for now."),
+	};
+	state.write_msg_buff(&s);
+}
+
+// With a shovel the player can bury whatever they've dropped and come back
+// for it later. The stash is hidden the same way the generated caches are so
+// it takes a search (or more digging) to turn it up again.
+const BURY_TURNS: u32 = 5;
+
+fn use_shovel(state: &mut GameState, _slot: char, items: &mut ItemsTable, _ships: &ShipsTable, _gui: &mut GameUI) {
+	if state.player.on_ship {
+		state.write_msg_buff("The captain would have words about digging up the deck.");
+		return;
+	}
+
+	match state.map[&state.map_id][state.player.row][state.player.col] {
+		Tile::Sand | Tile::Dirt | Tile::Grass => { },
+		_ => {
+			state.write_msg_buff("The ground here is too hard to dig.");
+			return;
+		},
+	}
+
+	let loc = (state.player.row, state.player.col);
+	if items.any_hidden(&loc) && !items.macguffin_here(&loc) {
+		state.write_msg_buff("You dig up a buried cache!");
+		items.reveal_hidden(&loc);
+		state.turn += BURY_TURNS;
+	} else if items.count_at(loc.0, loc.1) > 0 {
+		let n = items.bury(&loc);
+		let s = if n == 1 {
+			String::from("You dig a hole and bury your item.")
+		} else {
+			format!("You dig a hole and bury {} items.", n)
+		};
+		state.write_msg_buff(&s);
+		state.turn += BURY_TURNS;
+	} else {
+		state.write_msg_buff("You dig a hole but find nothing, so you fill it back in.");
+		state.turn += 1;
+	}
+}
+
+// Taking a sighting and working it against a treasure map's chart gives a
+// heading and distance to the X
+fn use_sextant(state: &mut GameState, _slot: char, _items: &mut ItemsTable, _ships: &ShipsTable, _gui: &mut GameUI) {
+	state.turn += 1;
+	if state.map_id != 0 {
+		state.write_msg_buff("You can't take a sighting down here.");
+		return;
+	}
+
+	let (pr, pc) = (state.player.row, state.player.col);
+	let nearest = state.player.inventory.items_of_type(ItemType::TreasureMap).iter()
+		.filter(|m| m.of_map_id == state.map_id)
+		.map(|m| m.x_coord)
+		.min_by_key(|x| util::cartesian_d(pr, pc, x.0, x.1));
+
+	match nearest {
+		Some(x) if (x.0, x.1) == (pr, pc) => state.write_msg_buff("X marks the spot. Yer standing on it!"),
+		Some(x) => {
+			let d = util::cartesian_d(pr, pc, x.0, x.1);
+			let dir = util::dir_between_sqs(pr, pc, x.0, x.1);
+			let s = format!("The X lies to {}, about {} paces.", compass_name(&dir), d.div_ceil(5) * 5);
+			state.write_msg_buff(&s);
+		},
+		None => state.write_msg_buff("Without a chart to work from, the sightings tell ye little."),
+	}
+}
+
+// The compass points the way back to the player's ship
+fn use_compass(state: &mut GameState, _slot: char, _items: &mut ItemsTable, ships: &ShipsTable, _gui: &mut GameUI) {
+	state.turn += 1;
+	if state.map_id != 0 {
+		state.write_msg_buff("The compass needle spins uselessly.");
+		return;
+	}
+
+	for ship in ships.values() {
+		if ship.name == state.player_ship {
+			if ship.row == state.player.row && ship.col == state.player.col {
+				state.write_msg_buff("You're standing on her deck!");
+			} else {
+				let dir = util::dir_between_sqs(state.player.row, state.player.col, ship.row, ship.col);
+				let s = format!("By your reckoning, the {} lies to {}.", ship.name, compass_name(&dir));
+				state.write_msg_buff(&s);
+			}
+			return;
+		}
+	}
+
+	state.write_msg_buff("Wherever your ship is, the compass can't help you now.");
+}
+
+fn refuel_lantern(state: &mut GameState, slot: char, _items: &mut ItemsTable, _ships: &ShipsTable, gui: &mut GameUI) {
     //let food = state.player.inventory.remove_count(ch, 1);
 	let sbi = state.curr_sidebar_info();
     match gui.query_single_response("Refuel which lantern?", &sbi) {
@@ -830,7 +2049,7 @@ fn refuel_lantern(state: &mut GameState, slot: char, gui: &mut GameUI) {
     }
 }
 
-fn use_item(state: &mut GameState, gui: &mut GameUI) {
+fn use_item(state: &mut GameState, items: &mut ItemsTable, ships: &ShipsTable, gui: &mut GameUI) {
 	if state.player.inventory.get_menu().len() == 0 {
 		state.write_msg_buff("You are empty handed.");
 		return
@@ -839,16 +2058,11 @@ fn use_item(state: &mut GameState, gui: &mut GameUI) {
 	let sbi = state.curr_sidebar_info();
 	match gui.query_single_response("Use which item?", &sbi) {
 		Some(ch) => {
-			match state.player.inventory.item_type_in_slot(ch) {	
-				Some(ItemType::Light) => {
-                    let result = state.player.inventory.toggle_slot(ch);
-                    state.write_msg_buff(&result.0);
-                    state.turn += 1;
+			match state.player.inventory.peek_at(ch) {	
+				Some(item) => match use_handler(&item) {
+					Some(handler) => handler(state, ch, items, ships, gui),
+					None => state.write_msg_buff("I can't think of a use for that."),
 				},
-				Some(ItemType::Fuel) => {
-                    refuel_lantern(state, ch, gui);
-				},
-				Some(_) => state.write_msg_buff("I can't think of a use for that."),
 				None => state.write_msg_buff("You do not have that item."),
 			}
 		},
@@ -856,7 +2070,7 @@ fn use_item(state: &mut GameState, gui: &mut GameUI) {
 	}
 }
 
-fn chat_with_npc(state: &mut GameState, gui: &mut GameUI) {
+fn chat_with_npc(state: &mut GameState, ships: &mut ShipsTable, gui: &mut GameUI) {
 	let sbi = state.curr_sidebar_info();
 	let mut npc;
 	match gui.pick_direction("Parley with whom?", &sbi) {
@@ -865,7 +2079,13 @@ fn chat_with_npc(state: &mut GameState, gui: &mut GameUI) {
 			let col = (state.player.col as i32 + dir.1) as usize;
 			let npcs = state.npcs.get(&state.map_id).unwrap();
 			if !npcs.is_npc_at(row, col) {
-				state.write_msg_buff("There's no one there!");
+				let merchant = ships.iter()
+					.find(|(_, s)| s.merchant && s.occupies(row, col))
+					.map(|(loc, _)| *loc);
+				match merchant {
+					Some(loc) => trade_with_ship(state, ships, loc, gui),
+					None => state.write_msg_buff("There's no one there!"),
+				}
 				return;
 			}
 			npc = state.npcs.get_mut(&state.map_id).unwrap().npc_at(row, col).unwrap();
@@ -878,29 +2098,46 @@ fn chat_with_npc(state: &mut GameState, gui: &mut GameUI) {
 
 	if npc.hostile {
 		npc.hostile_talk(state);
+	} else if npc.npc_type == actor::NPCType::Castaway && state.is_night() {
+		let s = format!("The {} is fast asleep.", npc.name);
+		state.write_msg_buff(&s);
+	} else if npc.navigator {
+		consult_navigator(state, gui);
+	} else if npc.sea_witch {
+		consult_sea_witch(state, gui);
 	} else if npc.is_merchant() {
 		if let Some(i) = npc.for_sale.clone() {
-			let mut price = npc.price.1 as i8;
+			let price = haggled_price(state, npc.price.1);
 			let currency = npc.price.0;
-			let verve_mod = Player::mod_for_stat(state.player.verve);
-			if verve_mod > 0 {
-				if price <= verve_mod {
-					price = 1;
-				} else {
-					price -= verve_mod;
-				}
-			}
+			state.note_price(&i.name, currency, price);
 
-			let mut s = format!("Ahoy, matey! If ye fancy, I have a {} for sale! Just {} ", i.name, price);
-			if npc.price.0 == 0 {
-				s.push_str("doubloons. A deal?");
+			let currency_name = if npc.price.0 == 0 {
+				"doubloons"
 			} else {
-				s.push_str("draughts of rum. A deal?");
-			}	
+				"draughts of rum"
+			};
+
 			let sbi = state.curr_sidebar_info();
-			match gui.query_yes_no(&s, &sbi) {
-				'y' => sell_item(state, npc, i, price as u8, currency),
-				_ => state.write_msg_buff("Bah!"),
+			let stock = npc.stock_left();
+			if stock > 1 {
+				let s = format!("Ahoy, matey! I've {} {} for sale, {} {} apiece.", 
+					stock, pluralize(&i.name), price, currency_name);
+				state.write_msg_buff(&s);
+				match gui.query_natural_num("Buy how many?", &sbi) {
+					Some(0) | None => state.write_msg_buff("Bah!"),
+					Some(n) if n > stock => {
+						let s = format!("I've only got {}, mate.", stock);
+						state.write_msg_buff(&s);
+					},
+					Some(n) => sell_item(state, npc, i, price, currency, n),
+				}
+			} else {
+				let s = format!("Ahoy, matey! If ye fancy, I have a {} for sale! Just {} {}. A deal?", 
+					i.name, price, currency_name);
+				match gui.query_yes_no(&s, &sbi) {
+					'y' => sell_item(state, npc, i, price, currency, 1),
+					_ => state.write_msg_buff("Bah!"),
+				}
 			}
 		}
 	} else {
@@ -910,62 +2147,420 @@ fn chat_with_npc(state: &mut GameState, gui: &mut GameUI) {
 	state.turn += 1;
 }
 
-fn sell_item(state: &mut GameState, mut npc: Monster, item: Item, price: u8, currency: u8) {
-	let currency_name = if currency == 0 {
-		"doubloon"
-	} else {
-		"draught of rum"
-	};
+const ENCHANT_FEE: u8 = 10;
+const MAX_ENCHANTMENT: u8 = 3;
 
-	if let Some(i) = state.player.inventory.count_of_item(&currency_name) {
-		if i.0 < price {
-			state.write_msg_buff("Ye're looking a bit bereft, mate.");
-		} else {
-			state.write_msg_buff("Done and done!");
-			state.player.inventory.remove_count(i.1, price);
-			state.player.inventory.add(item);
-			let row = npc.row;
-			let col = npc.col;
-			npc.for_sale = None;
-			state.npcs.get_mut(&state.map_id)
-						.unwrap()
-						.update(npc, row, col);
-		}
-	} else {
-		state.write_msg_buff("Come back when ye can meet my price!");
+// The sea witch will lay her magic on a blade or a bit of armour. She'll 
+// take coin, more for each blessing the item already has, but she'd much
+// rather have a fetish.
+fn consult_sea_witch(state: &mut GameState, gui: &mut GameUI) {
+	let slots = state.player.inventory.enchantable_slots();
+	if slots.is_empty() {
+		state.write_msg_buff("\"Bring me a blade or a coat worth my magic, dearie.\"");
+		return;
 	}
-}
 
-fn read(state: &mut GameState, gui: &mut GameUI) {
-	if state.player.inventory.get_menu().len() == 0 {
-		state.write_msg_buff("You are empty handed.");
-		return
+	let mut menu = vec![String::from("\"What shall I bless for ye, dearie?\"")];
+	for (j, slot) in slots.iter().enumerate() {
+		let item = state.player.inventory.peek_at(*slot).unwrap();
+		menu.push(format!("{}) {}", (b'a' + j as u8) as char, item.get_full_name()));
 	}
-	
+
+	let pick = match gui.menu_picker(&menu, slots.len() as u8, true, false) {
+		Some(answers) if !answers.is_empty() => *answers.iter().next().unwrap() as usize,
+		_ => {
+			state.write_msg_buff("\"Another time, then.\"");
+			return;
+		},
+	};
+
+	let slot = slots[pick];
+	let item = state.player.inventory.peek_at(slot).unwrap();
+	if item.enchantment >= MAX_ENCHANTMENT {
+		state.write_msg_buff("\"That one can hold no more of my magic.\"");
+		return;
+	}
+
 	let sbi = state.curr_sidebar_info();
-	match gui.query_single_response("Read what?", &sbi) {
-		Some(ch) => {
-			match state.player.inventory.item_type_in_slot(ch) {	
-				Some(ItemType::TreasureMap) => {
-					let map = state.player.inventory.peek_at(ch).unwrap();
-					gui.show_treasure_map(state, &map);
-					state.turn += 1;
-				},
-				Some(ItemType::Note) => {
-					let note = state.player.inventory.peek_at(ch).unwrap();
-					let txt = state.notes[&note.bonus].clone();
-					state.write_msg_buff(&txt);
-					state.turn += 1;
-				},
-				Some(_) => state.write_msg_buff("Hmm...nary a label nor instructions."),
-				None => state.write_msg_buff("You do not have that item."),
+	let fetish = state.player.inventory.count_of_item("fetish")
+		.filter(|(_, ch)| !state.player.inventory.peek_at(*ch).unwrap().equiped);
+	let paid = match fetish {
+		Some((_, ch)) if gui.query_yes_no("\"Ooh, is that a fetish? Give it here!\" (y/n)", &sbi) == 'y' => {
+			state.player.inventory.remove_count(ch, 1);
+			true
+		},
+		_ => {
+			let fee = haggled_price(state, ENCHANT_FEE * (item.enchantment + 1));
+			let purse = match state.player.inventory.count_of_item("doubloon") {
+				Some((n, slot)) => (n, slot),
+				None => (0, '\0'),
+			};
+
+			if purse.0 < fee {
+				let s = format!("\"My magic costs {} doubloons, dearie.\"", fee);
+				state.write_msg_buff(&s);
+				false
+			} else {
+				let s = format!("Bless yer {} for {} doubloons? (y/n)", item.name, fee);
+				if gui.query_yes_no(&s, &sbi) == 'y' {
+					state.player.inventory.remove_count(purse.1, fee);
+					true
+				} else {
+					state.write_msg_buff("\"Another time, then.\"");
+					false
+				}
 			}
 		},
-		None => state.write_msg_buff("Nevermind."),
+	};
+
+	if paid {
+		state.player.inventory.enchant(slot);
+		state.player.calc_ac();
+		let s = format!("She mutters over yer {} and it glows a cold sea-green!", item.name);
+		state.write_msg_buff(&s);
 	}
 }
 
-fn search_sq(state: &mut GameState, items: &mut ItemsTable, row: usize, col: usize) -> bool {
+// Roughly where in the Strait a square is, for when we want to be helpful
+// without giving the game away
+fn region_name(row: usize, col: usize) -> &'static str {
+	let third_r = content_factory::WORLD_HEIGHT / 3;
+	let third_c = content_factory::WORLD_WIDTH / 3;
+	match (row / third_r, col / third_c) {
+		(0, 0) => "north-western",
+		(0, 1) => "northern",
+		(0, _) => "north-eastern",
+		(1, 0) => "western",
+		(1, 1) => "central",
+		(1, _) => "eastern",
+		(_, 0) => "south-western",
+		(_, 1) => "southern",
+		_ => "south-eastern",
+	}
+}
+
+// The wrecks the player has some clue about, either from the tale they heard
+// in port or from notes they're carrying
+fn known_wrecks(state: &GameState) -> Vec<(String, (usize, usize))> {
+	let mut names: Vec<String> = Vec::new();
+	if state.starter_clue != 0 {
+		names.push(state.pirate_lord_ship.clone());
+	}
+
+	let notes = state.player.inventory.items_of_type(ItemType::Note);
+	let world_map = &state.map[&0];
+	let mut wrecks = Vec::new();
+	for (r, row) in world_map.iter().enumerate() {
+		for (c, tile) in row.iter().enumerate() {
+			if let Tile::Shipwreck(_, name) = tile {
+				let mentioned = names.contains(name) 
+					|| notes.iter().any(|n| match state.notes.get(&n.bonus) {
+						Some(txt) => txt.contains(name.as_str()),
+						None => false,
+					});
+				if mentioned && !wrecks.iter().any(|w: &(String, (usize, usize))| w.0 == *name) {
+					wrecks.push((name.clone(), (r, c)));
+				}
+			}
+		}
+	}
+
+	wrecks
+}
+
+const NAVIGATOR_MAP_FEE: u8 = 5;
+const NAVIGATOR_WRECK_FEE: u8 = 8;
+const NAVIGATOR_CHART_RADIUS: i32 = 6;
+
+// The navigator doesn't sell goods but knowledge: roughly where a treasure
+// map leads, or where one of the wrecks the player has heard of lies.
+fn consult_navigator(state: &mut GameState, gui: &mut GameUI) {
+	let map_fee = haggled_price(state, NAVIGATOR_MAP_FEE);
+	let wreck_fee = haggled_price(state, NAVIGATOR_WRECK_FEE);
+	let menu = vec![String::from("\"What can this old navigator do for ye?\""),
+		format!("a) Study one of yer treasure maps ({} doubloons)", map_fee),
+		format!("b) Ask after a wreck ye've heard tell of ({} doubloons)", wreck_fee)];
+
+	let pick = match gui.menu_picker(&menu, 2, true, false) {
+		Some(answers) if !answers.is_empty() => *answers.iter().next().unwrap(),
+		_ => {
+			state.write_msg_buff("Fair winds, then.");
+			return;
+		},
+	};
+
+	let hint = if pick == 0 {
+		let maps = state.player.inventory.items_of_type(ItemType::TreasureMap);
+		match maps.iter().find(|m| m.of_map_id == 0) {
+			Some(m) => {
+				// Close enough to go looking, not so close it gives the game away
+				let r = m.x_coord.0 as i32 + rand::thread_rng().gen_range(-4, 5);
+				let c = m.x_coord.1 as i32 + rand::thread_rng().gen_range(-4, 5);
+				Some((map_fee, format!("\"That X lies somewhere in the {} reaches of the Strait.\"", 
+							region_name(m.x_coord.0, m.x_coord.1)), (r, c)))
+			},
+			None => {
+				state.write_msg_buff("\"Ye've no charts for me to look over, matey.\"");
+				None
+			},
+		}
+	} else {
+		let wrecks = known_wrecks(state);
+		if wrecks.is_empty() {
+			state.write_msg_buff("\"I can't help ye find a wreck ye've no word of.\"");
+			None
+		} else {
+			let (name, loc) = &wrecks[rand::thread_rng().gen_range(0, wrecks.len())];
+			Some((wreck_fee, format!("\"The {} went down in the {} waters, I hear.\"", 
+							name, region_name(loc.0, loc.1)), (loc.0 as i32, loc.1 as i32)))
+		}
+	};
+
+	if let Some((fee, txt, centre)) = hint {
+		let purse = match state.player.inventory.count_of_item("doubloon") {
+			Some((n, slot)) => (n, slot),
+			None => (0, '\0'),
+		};
+
+		if purse.0 < fee {
+			state.write_msg_buff("\"Knowledge ain't free, mate. Come back with coin.\"");
+		} else {
+			state.player.inventory.remove_count(purse.1, fee);
+			state.write_msg_buff(&txt);
+			state.write_msg_buff("He sketches the waters in question on yer chart.");
+			chart_waters(state, centre, NAVIGATOR_CHART_RADIUS);
+			state.navigator_hints.push(txt);
+		}
+	}
+}
+
+// Mark the squares around centre on the world map as though the player had
+// sailed there themselves
+fn chart_waters(state: &mut GameState, centre: (i32, i32), radius: i32) {
+	for r in centre.0 - radius..=centre.0 + radius {
+		for c in centre.1 - radius..=centre.1 + radius {
+			if map::in_bounds(&state.map[&0], r, c) {
+				state.world_seen.insert((r as usize, c as usize));
+			}
+		}
+	}
+}
+
+// A silver-tongued pirate can talk a trader down a little
+fn haggled_price(state: &GameState, price: u8) -> u8 {
+	let verve_mod = Player::mod_for_stat(state.player.verve);
+	if verve_mod > 0 {
+		if price as i8 <= verve_mod {
+			1
+		} else {
+			price - verve_mod as u8
+		}
+	} else {
+		price
+	}
+}
+
+// What a merchant ship asks for its wares, in doubloons apiece
+fn merchant_price(item: &Item) -> u8 {
+	match item.item_type {
+		ItemType::Bullet | ItemType::Timber | ItemType::Food => 1,
+		ItemType::Drink | ItemType::Fuel => 2,
+		ItemType::Light | ItemType::Flotation => 5,
+		ItemType::Tool => 6,
+		ItemType::Firearm => 8,
+		_ => 3,
+	}
+}
+
+fn trade_with_ship(state: &mut GameState, ships: &mut ShipsTable, loc: (usize, usize), 
+		gui: &mut GameUI) {
+	let mut ship = ships.remove(&loc).unwrap();
+
+	if ship.hold.is_empty() {
+		let s = format!("The crew of the {} have nothing left to sell.", ship.name);
+		state.write_msg_buff(&s);
+		ships.insert(loc, ship);
+		return;
+	}
+
+	let mut menu = vec![format!("The {} has for sale:", ship.name)];
+	let lines = ship.get_hold_menu();
+	for (j, line) in lines.iter().enumerate() {
+		let (item, _) = ship.hold_line_item(j).unwrap();
+		let price = haggled_price(state, merchant_price(&item));
+		state.note_price(&item.name, 0, price);
+		menu.push(format!("{} ({} doubloons each)", line, price));
+	}
+
+	let pick = match gui.menu_picker(&menu, lines.len() as u8, true, true) {
+		Some(answers) if !answers.is_empty() => *answers.iter().next().unwrap() as usize,
+		_ => {
+			state.write_msg_buff("Nevermind.");
+			ships.insert(loc, ship);
+			return;
+		},
+	};
+
+	let (item, count) = ship.hold_line_item(pick).unwrap();
+	let quantity = if count > 1 {
+		let sbi = state.curr_sidebar_info();
+		match gui.query_natural_num("Buy how many?", &sbi) {
+			Some(n) => usize::min(n as usize, count),
+			None => 0,
+		}
+	} else {
+		1
+	};
+
+	let total = haggled_price(state, merchant_price(&item)) as usize * quantity;
+	let purse = match state.player.inventory.count_of_item("doubloon") {
+		Some((n, slot)) => (n as usize, slot),
+		None => (0, '\0'),
+	};
+
+	if quantity == 0 {
+		state.write_msg_buff("Nevermind.");
+	} else if purse.0 < total {
+		state.write_msg_buff("Ye're looking a bit bereft, mate.");
+	} else if !state.player.inventory.has_room_for(&item) {
+		state.write_msg_buff("Ye can carry no more!");
+	} else {
+		// Anything that won't fit in the pack stays in the hold, and the
+		// player only pays for what they take
+		let mut bought = 0;
+		for item in ship.take_from_hold_line(pick, quantity) {
+			if state.player.inventory.add(item.clone()) {
+				bought += 1;
+			} else {
+				ship.hold.push(item);
+			}
+		}
+		state.player.inventory.remove_count(purse.1, (total / quantity * bought) as u8);
+		if bought < quantity {
+			let s = format!("Ye could only carry {} of them.", bought);
+			state.write_msg_buff(&s);
+		} else {
+			state.write_msg_buff("Done and done!");
+		}
+		state.turn += 1;
+	}
+
+	ships.insert(loc, ship);
+}
+
+fn sell_item(state: &mut GameState, mut npc: Monster, item: Item, price: u8, 
+		currency: u8, quantity: u8) {
+	let currency_name = if currency == 0 {
+		"doubloon"
+	} else {
+		"draught of rum"
+	};
+
+	let total = price as u16 * quantity as u16;
+	if let Some(i) = state.player.inventory.count_of_item(currency_name) {
+		if (i.0 as u16) < total {
+			state.write_msg_buff("Ye're looking a bit bereft, mate.");
+		} else if !state.player.inventory.has_room_for(&item) {
+			state.write_msg_buff("Ye can carry no more!");
+		} else {
+			// Only pay for what the player can actually carry off
+			let mut bought = 0;
+			while bought < quantity && state.player.inventory.add(item.clone()) {
+				bought += 1;
+			}
+			state.player.inventory.remove_count(i.1, (price as u16 * bought as u16) as u8);
+			if bought < quantity {
+				let s = format!("Ye could only carry {} of them.", bought);
+				state.write_msg_buff(&s);
+			} else {
+				state.write_msg_buff("Done and done!");
+			}
+			let row = npc.row;
+			let col = npc.col;
+			npc.sold(bought);
+			state.npcs.get_mut(&state.map_id)
+						.unwrap()
+						.update(npc, row, col);
+		}
+	} else {
+		state.write_msg_buff("Come back when ye can meet my price!");
+	}
+}
+
+fn read(state: &mut GameState, gui: &mut GameUI) {
+	if state.player.inventory.get_menu().is_empty() {
+		state.write_msg_buff("You are empty handed.");
+		return
+	}
+	
+	let sbi = state.curr_sidebar_info();
+	match gui.query_single_response("Read what?", &sbi) {
+		Some(ch) => {
+			match state.player.inventory.item_type_in_slot(ch) {	
+				Some(ItemType::TreasureMap) => {
+					let map = state.player.inventory.peek_at(ch).unwrap();
+					gui.show_treasure_map(state, &map);
+					state.turn += 1;
+				},
+				Some(ItemType::Note) => {
+					let note = state.player.inventory.peek_at(ch).unwrap();
+					let txt = state.notes[&note.bonus].clone();
+					state.write_msg_buff(&txt);
+					state.turn += 1;
+				},
+				Some(_) => state.write_msg_buff("Hmm...nary a label nor instructions."),
+				None => state.write_msg_buff("You do not have that item."),
+			}
+		},
+		None => state.write_msg_buff("Nevermind."),
+	}
+}
+
+// Besides what falls from its pockets, now and then a pirate (or what's left
+// of one) has something sewn into its coat that only turns up if the player
+// takes the time to search the body: a purse of coins, or a scrap of paper 
+// naming one of the wrecks in the strait.
+fn hide_on_body(state: &mut GameState, items: &mut ItemsTable, npc: &Monster) {
+	let (pouch_chance, note_chance) = match npc.npc_type {
+		actor::NPCType::MaroonedPirate | actor::NPCType::UndeadCaptain => (0.25, 0.15),
+		actor::NPCType::Skeleton => (0.05, 0.05),
+		_ => return,
+	};
+
+	let mut hidden = Vec::new();
+	if rand::thread_rng().gen_range(0.0, 1.0) < pouch_chance {
+		for _ in 0..rand::thread_rng().gen_range(3, 9) {
+			hidden.push(Item::get_item("doubloon").unwrap());
+		}
+	}
+
+	if rand::thread_rng().gen_range(0.0, 1.0) < note_chance {
+		let mut wrecks = Vec::new();
+		for row in state.map[&0].iter() {
+			for tile in row.iter() {
+				if let Tile::Shipwreck(_, name) = tile {
+					if !name.is_empty() {
+						wrecks.push(name.clone());
+					}
+				}
+			}
+		}
+
+		if !wrecks.is_empty() {
+			let name = &wrecks[rand::thread_rng().gen_range(0, wrecks.len())];
+			hidden.push(Item::get_note(state.note_count));
+			state.notes.insert(state.note_count, Item::get_note_text(name));
+			state.note_count += 1;
+		}
+	}
+
+	for mut item in hidden {
+		item.hidden = true;
+		items.add_or_spill(&state.map[&state.map_id], npc.row, npc.col, item);
+	}
+}
+
+fn search_sq(state: &mut GameState, items: &mut ItemsTable, row: usize, col: usize) -> bool {
 	let loc = (row, col);
 
 	// For the final treasure type, a MacGuffin can only be found if the player
@@ -982,7 +2577,11 @@ fn search_sq(state: &mut GameState, items: &mut ItemsTable, row: usize, col: usi
 	if items.any_hidden(&loc) && do_ability_check(0, search_dc, state.player.prof_bonus as i8) {
 		// hmm I wonder if I should give the player a perception skill?
 		// also should have a way to have harder to find things
-		state.write_msg_buff("You find a hidden cache!");
+		if state.corpse_at(row, col) {
+			state.write_msg_buff("Searching the body, you find something tucked away!");
+		} else {
+			state.write_msg_buff("You find a hidden cache!");
+		}
 		items.reveal_hidden(&loc);
 		return true;
 	} 
@@ -997,6 +2596,29 @@ fn search_sq(state: &mut GameState, items: &mut ItemsTable, row: usize, col: usi
 				return true;
 			}
 		},
+		Tile::Wall => {
+			// Cave walls can hide a passage to a secret chamber. Each wall only gets
+			// one look so the player can't just stand there searching until one 
+			// appears, and there's at most one chamber per cave.
+			let wall = (state.map_id, row, col);
+			if state.map_id != 0 && !state.hidden_chambers.contains(&state.map_id)
+					&& !state.searched_walls.contains(&wall) {
+				state.searched_walls.insert(wall);
+				let chance = 0.02 * state.player.prof_bonus as f32;
+				if rand::thread_rng().gen_range(0.0, 1.0) < chance {
+					let dir = (row as i32 - state.player.row as i32, col as i32 - state.player.col as i32);
+					let curr_map = state.map.get_mut(&state.map_id).unwrap();
+					if let Some(centre) = carve_hidden_chamber(curr_map, (row, col), dir) {
+						for item in get_hidden_chamber_items() {
+							items.add_or_spill(&state.map[&state.map_id], centre.0, centre.1, item);
+						}
+						state.hidden_chambers.insert(state.map_id);
+						state.write_msg_buff("You find a hidden passage!");
+						return true;
+					}
+				}
+			}
+		},
 		_ => { /* No other hidden tile types yet */ },
 	}
 
@@ -1055,7 +2677,25 @@ fn drop_item(state: &mut GameState, items: &mut ItemsTable, gui: &mut GameUI) {
 			let count = state.player.inventory.count_in_slot(ch);
 			if count == 0 {
 				state.write_msg_buff("You do not have that item.");
-			} else if count > 1 {
+				return;
+			}
+
+			let player_loc = (state.player.row, state.player.col);
+			let to_drop = state.player.inventory.peek_at(ch).unwrap();
+			let loc = match items.drop_loc(&state.map[&state.map_id], player_loc.0, player_loc.1, &to_drop) {
+				Some(loc) => loc,
+				None => {
+					if state.map[&state.map_id][player_loc.0][player_loc.1] == Tile::DeepWater {
+						state.write_msg_buff("It would only sink to the bottom.");
+					} else {
+						state.write_msg_buff("There's no room for more here!");
+					}
+					return;
+				},
+			};
+			let moved = loc != player_loc;
+
+			if count > 1 {
 				match gui.query_natural_num("Drop how many?", &sbi) {
 					Some(v) => {
 						let pile = state.player.inventory.remove_count(ch, v);
@@ -1068,10 +2708,13 @@ fn drop_item(state: &mut GameState, items: &mut ItemsTable, gui: &mut GameUI) {
                                 let s = format!("You drop {} {}.", v, pluralized);
                                 state.write_msg_buff(&s);
                             }
+							if moved {
+								state.write_msg_buff("They come to rest nearby.");
+							}
 							state.turn += 1;
 							for mut item in pile {
 								item.equiped = false;
-								items.add(state.player.row, state.player.col, item);
+								items.add(loc.0, loc.1, item);
 							}
 						} else {
 							state.write_msg_buff("Nevermind.");
@@ -1080,59 +2723,988 @@ fn drop_item(state: &mut GameState, items: &mut ItemsTable, gui: &mut GameUI) {
 					None => state.write_msg_buff("Nevermind."),
 				}
 			} else {
-				let mut item = state.player.inventory.remove(ch);
-				item.equiped = false;
-				let s = format!("You drop the {}.", util::get_articled_name(true, &item));
-				items.add(state.player.row, state.player.col, item);	
-				state.write_msg_buff(&s);
-				state.turn += 1;
-			}	
-		},
-		None => state.write_msg_buff("Nevermind."),
+				let mut item = state.player.inventory.remove(ch);
+				item.equiped = false;
+				let s = format!("You drop the {}.", util::get_articled_name(true, &item));
+				items.add(loc.0, loc.1, item);	
+				state.write_msg_buff(&s);
+				if moved {
+					state.write_msg_buff("It comes to rest nearby.");
+				}
+				state.turn += 1;
+			}	
+		},
+		None => state.write_msg_buff("Nevermind."),
+	}
+
+	state.player.calc_ac();
+}
+
+// Finding the treasure wins the game, but the player can choose to stay on
+// and enjoy the islands a while. They can retire and get the victory screen 
+// later by quitting.
+fn found_macguffin(state: &mut GameState, gui: &mut GameUI) -> Result<(), ExitReason> {
+	if state.won || state.navy_alerted {
+		return Ok(());
+	}
+
+	if state.escape_ending {
+		state.navy_alerted = true;
+		state.write_msg_buff("The treasure is yers, but word of it will soon reach the Yendorian Navy!");
+		state.write_msg_buff("Make for the open sea to the north or west and escape the strait!");
+		return Ok(());
+	}
+
+	let sbi = state.curr_sidebar_info();
+	gui.write_screen(&mut state.msg_buff, &sbi);
+	match gui.query_yes_no("The treasure is yers! Keep sailing the seas a while? (y/n)", &sbi) {
+		'y' => {
+			state.won = true;
+			state.write_msg_buff("Aye! When ye're ready to retire, (Q)uit and claim yer glory.");
+			Ok(())
+		},
+		_ => Err(ExitReason::Win),
+	}
+}
+
+fn pick_up(state: &mut GameState, items: &mut ItemsTable, gui: &mut GameUI) -> Result<(), ExitReason> {
+	let item_count = items.count_at(state.player.row, state.player.col);
+	if item_count == 0 {
+		state.write_msg_buff("There is nothing here to pick up.");
+	} else if item_count == 1 {
+		let item = items.get_at(state.player.row, state.player.col);
+		if !state.player.inventory.has_room_for(&item) {
+			state.write_msg_buff("Ye can carry no more!");
+			items.add(state.player.row, state.player.col, item);
+			return Ok(());
+		}
+		let is_macguffin = item.item_type == ItemType::MacGuffin;
+		let s = format!("You pick up {}.", util::get_articled_name(true, &item));
+		state.write_msg_buff(&s);
+		state.player.inventory.add(item);
+		state.turn += 1;
+
+		if is_macguffin {
+			found_macguffin(state, gui)?;
+		}
+	} else {
+		let mut menu = items.get_menu(state.player.row, state.player.col);
+		menu.insert(0, "Pick up what: (* to get everything)".to_string());
+		let answers = gui.menu_picker(&menu, menu.len() as u8, false, false);
+		match answers {
+			None => state.write_msg_buff("Nevermind."), // Esc was pressed
+			Some(v) => {
+				state.turn += 1;
+				let picked_up = items.get_many_at(state.player.row, state.player.col, &v);
+				for item in picked_up {
+					if !state.player.inventory.has_room_for(&item) {
+						state.write_msg_buff("Ye can carry no more!");
+						items.add(state.player.row, state.player.col, item);
+						continue;
+					}
+					let is_macguffin = item.item_type == ItemType::MacGuffin;
+					let s = format!("You pick up {}.", util::get_articled_name(true, &item));
+					state.write_msg_buff(&s);
+					state.player.inventory.add(item);
+				
+					if is_macguffin {
+						found_macguffin(state, gui)?;
+					}
+				}
+			},
+		}
+	}
+
+	Ok(())
+}
+
+// A wild boar that's been beaten down far enough might just be won over with
+// a bite to eat. Tamed, it follows the player around and can be ridden.
+const TAMEABLE_HP: u8 = 4;
+
+fn give_item(state: &mut GameState, gui: &mut GameUI) {
+	let sbi = state.curr_sidebar_info();
+	let dir = match gui.pick_direction("Give to whom?", &sbi) {
+		Some(dir) => dir,
+		None => { state.write_msg_buff("Nevermind."); return; },
+	};
+
+	let r = (state.player.row as i32 + dir.0) as usize;
+	let c = (state.player.col as i32 + dir.1) as usize;
+	let mut npc = match state.npcs.get_mut(&state.map_id).unwrap().npc_at(r, c) {
+		Some(npc) => npc,
+		None => { state.write_msg_buff("There's no one there!"); return; },
+	};
+
+	let sbi = state.curr_sidebar_info();
+	let slot = match gui.query_single_response("Give what?", &sbi) {
+		Some(ch) => ch,
+		None => { state.write_msg_buff("Nevermind."); return; },
+	};
+
+	match state.player.inventory.item_type_in_slot(slot) {
+		Some(ItemType::Food) => { },
+		Some(_) => {
+			let s = format!("The {} has no use for that.", npc.name);
+			state.write_msg_buff(&s);
+			return;
+		},
+		None => { state.write_msg_buff("You do not have that item."); return; },
+	}
+
+	if npc.npc_type != actor::NPCType::Boar {
+		let s = format!("The {} has no use for that.", npc.name);
+		state.write_msg_buff(&s);
+		return;
+	}
+
+	state.player.inventory.remove_count(slot, 1);
+	state.turn += 1;
+
+	let verve_mod = Player::mod_for_stat(state.player.verve);
+	if npc.tame {
+		let s = format!("The {} grunts happily.", npc.name);
+		state.write_msg_buff(&s);
+	} else if npc.hp <= TAMEABLE_HP && do_ability_check(verve_mod, 12, state.player.prof_bonus as i8) {
+		let s = format!("The {} wolfs it down and nuzzles your hand!", npc.name);
+		state.write_msg_buff(&s);
+		npc.tame = true;
+		npc.hostile = false;
+		npc.aware_of_player = false;
+		npc.pack = 0;
+		npc.name = String::from("tame boar");
+		state.npcs.get_mut(&state.map_id).unwrap().update(npc, r, c);
+	} else {
+		let s = format!("The {} wolfs it down but stays wild.", npc.name);
+		state.write_msg_buff(&s);
+	}
+}
+
+fn toggle_ride(state: &mut GameState, ships: &ShipsTable, gui: &mut GameUI) {
+	if let Some(mut mount) = state.player.mount.take() {
+		match util::rnd_empty_adj(state, ships, state.player.row as i32, state.player.col as i32) {
+			Some(loc) => {
+				let s = format!("You hop down off the {}.", mount.name);
+				state.write_msg_buff(&s);
+				mount.row = loc.0;
+				mount.col = loc.1;
+				state.npcs.get_mut(&state.map_id).unwrap().add(mount);
+				state.turn += 1;
+			},
+			None => {
+				state.write_msg_buff("There's no room to climb down here.");
+				state.player.mount = Some(mount);
+			},
+		}
+		return;
+	}
+
+	if state.player.on_ship || ships.contains_key(&(state.player.row, state.player.col)) {
+		state.write_msg_buff("There's nothing to ride aboard ship.");
+		return;
+	}
+
+	let sbi = state.curr_sidebar_info();
+	let dir = match gui.pick_direction("Ride what?", &sbi) {
+		Some(dir) => dir,
+		None => { state.write_msg_buff("Nevermind."); return; },
+	};
+
+	let r = (state.player.row as i32 + dir.0) as usize;
+	let c = (state.player.col as i32 + dir.1) as usize;
+	match state.npcs.get_mut(&state.map_id).unwrap().npc_at(r, c) {
+		Some(npc) if npc.tame => {
+			let s = format!("You clamber onto the {}.", npc.name);
+			state.write_msg_buff(&s);
+			state.npcs.get_mut(&state.map_id).unwrap().remove(npc.id, r, c);
+			state.player.row = r;
+			state.player.col = c;
+			state.player.mount = Some(npc);
+			state.turn += 1;
+		},
+		Some(npc) => {
+			let s = format!("The {} won't let you near enough for that!", npc.name);
+			state.write_msg_buff(&s);
+		},
+		None => state.write_msg_buff("There's nothing there to ride."),
+	}
+}
+
+// Look over everything lying on the player's square without picking any of
+// it up. Doesn't take a turn.
+fn examine_ground(state: &mut GameState, items: &ItemsTable, gui: &mut GameUI) {
+	let pile = items.pile_summary(state.player.row, state.player.col);
+	if pile.is_empty() {
+		state.write_msg_buff("There's nothing here.");
+		return;
+	} else if pile.len() == 1 && pile[0].1 == 1 {
+		gui.write_long_msg(&pile[0].0.describe(), false);
+		return;
+	}
+
+	let count: usize = pile.iter().map(|e| e.1).sum();
+	let mut menu = vec![format!("There are {} things here:", count)];
+	for (j, (item, n)) in pile.iter().enumerate() {
+		let s = if *n == 1 {
+			format!("{}) {}", (b'a' + j as u8) as char, util::get_articled_name(false, item))
+		} else {
+			format!("{}) {} {}", (b'a' + j as u8) as char, n, pluralize(&item.name))
+		};
+		menu.push(s);
+	}
+
+	let small_font = menu.len() > 19;
+	loop {
+		match gui.menu_query(&menu, "Select an item to examine it, or Esc to close.", small_font) {
+			Some(ch) if ch >= 'a' && ((ch as u8 - b'a') as usize) < pile.len() => {
+				let item = &pile[(ch as u8 - b'a') as usize].0;
+				gui.write_long_msg(&item.describe(), false);
+			},
+			Some(_) => continue,
+			None => break,
+		}
+	}
+}
+
+// Cautiously poke your head around a corner. It tells the player what's in the
+// adjacent square and lets them see a little further that way (see calc_v_matrix)
+fn peek(state: &mut GameState, items: &ItemsTable, gui: &mut GameUI) {
+	let sbi = state.curr_sidebar_info();
+	let dir = match gui.pick_direction("Peek in which direction?", &sbi) {
+		Some(dir) => dir,
+		None => { state.write_msg_buff("Nevermind."); return; },
+	};
+
+	let r = state.player.row as i32 + dir.0;
+	let c = state.player.col as i32 + dir.1;
+	if !map::in_bounds(&state.map[&state.map_id], r, c) {
+		state.write_msg_buff("There's nothing to see that way.");
+		return;
+	}
+	let (r, c) = (r as usize, c as usize);
+	let mut seen = false;
+
+	if let Some(npc) = state.npcs.get_mut(&state.map_id).unwrap().npc_at(r, c) {
+		let article = if "aeiou".contains(npc.name.chars().next().unwrap_or('x')) { "an" } else { "a" };
+		let s = format!("You spot {} {}!", article, npc.name);
+		state.write_msg_buff(&s);
+		if npc.hp < npc.max_hp {
+			let s = format!("It looks {}.", npc.health_desc());
+			state.write_msg_buff(&s);
+		}
+		seen = true;
+	}
+
+	let pile = items.pile_summary(r, c);
+	if pile.len() == 1 && pile[0].1 == 1 {
+		let s = format!("You see {} there.", util::get_articled_name(false, &pile[0].0));
+		state.write_msg_buff(&s);
+		seen = true;
+	} else if !pile.is_empty() {
+		state.write_msg_buff("You see several items there.");
+		seen = true;
+	}
+
+	match state.map[&state.map_id][r][c] {
+		Tile::Lava => { state.write_msg_buff("Molten lava!"); seen = true; },
+		Tile::FirePit => { state.write_msg_buff("Fire!"); seen = true; },
+		Tile::DeepWater => { state.write_msg_buff("Deep water."); seen = true; },
+		Tile::BoulderTrap(_, false, false, _, _) => { state.write_msg_buff("A pressure plate!"); seen = true; },
+		Tile::Portal(_) => { state.write_msg_buff("Where could this lead..."); seen = true; },
+		_ => { },
+	}
+
+	if !seen {
+		state.write_msg_buff("You peek carefully but see nothing of note.");
+	}
+
+	state.peek = Some(dir);
+	state.turn += 1;
+}
+
+fn stow_in_hold(state: &mut GameState, ship: &mut Ship, gui: &mut GameUI) {
+	if state.player.inventory.get_menu().is_empty() {
+		state.write_msg_buff("You are empty handed.");
+		return
+	}
+
+	let sbi = state.curr_sidebar_info();
+	let ch = match gui.query_single_response("Stow what?", &sbi) {
+		Some(ch) => ch,
+		None => { state.write_msg_buff("Nevermind."); return; },
+	};
+
+	let count = state.player.inventory.count_in_slot(ch);
+	if count == 0 {
+		state.write_msg_buff("You do not have that item.");
+		return;
+	}
+
+	let how_many = if count > 1 {
+		match gui.query_natural_num("Stow how many?", &sbi) {
+			Some(v) => u8::min(v, count),
+			None => { state.write_msg_buff("Nevermind."); return; },
+		}
+	} else {
+		1
+	};
+
+	let item = state.player.inventory.peek_at(ch).unwrap();
+	if item.item_type == ItemType::MacGuffin {
+		state.write_msg_buff("You'd best keep that close at hand!");
+		return;
+	}
+	if ship.hold_weight() + item.weight as u16 * how_many as u16 > HOLD_CAPACITY 
+			|| !ship.has_hold_line_for(&item) {
+		state.write_msg_buff("There's no room left in the hold for that.");
+		return;
+	}
+
+	let pile = state.player.inventory.remove_count(ch, how_many);
+	if pile.is_empty() {
+		state.write_msg_buff("Nevermind.");
+		return;
+	}
+
+	let s = if pile.len() == 1 {
+		format!("You stow {} in the hold.", util::get_articled_name(true, &pile[0]))
+	} else {
+		format!("You stow {} {} in the hold.", pile.len(), pluralize(&pile[0].name))
+	};
+	state.write_msg_buff(&s);
+
+	for mut item in pile {
+		item.equiped = false;
+		ship.hold.push(item);
+	}
+
+	state.player.calc_ac();
+	state.turn += 1;
+}
+
+fn fetch_from_hold(state: &mut GameState, ship: &mut Ship, gui: &mut GameUI) {
+	if ship.hold.is_empty() {
+		let s = format!("The hold of the {} is empty.", ship.name);
+		state.write_msg_buff(&s);
+		return;
+	}
+
+	let mut menu = ship.get_hold_menu();
+	menu.insert(0, "Fetch what: (* to get everything)".to_string());
+	match gui.menu_picker(&menu, menu.len() as u8, false, false) {
+		None => state.write_msg_buff("Nevermind."), // Esc was pressed
+		Some(v) => {
+			let fetched = ship.take_from_hold(&v);
+			if fetched.is_empty() {
+				state.write_msg_buff("Nevermind.");
+				return;
+			}
+
+			state.turn += 1;
+			let mut full = false;
+			for item in fetched {
+				if !state.player.inventory.has_room_for(&item) {
+					full = true;
+					ship.hold.push(item);
+					continue;
+				}
+				let s = format!("You fetch {} from the hold.", util::get_articled_name(true, &item));
+				state.write_msg_buff(&s);
+				state.player.inventory.add(item);
+			}
+			if full {
+				state.write_msg_buff("Ye can carry no more!");
+			}
+		}
+	}
+}
+
+fn use_hold(state: &mut GameState, ships: &mut ShipsTable, gui: &mut GameUI) {
+	let player_loc = (state.player.row, state.player.col);
+	if !ships.contains_key(&player_loc) {
+		state.write_msg_buff("You need to be aboard a ship to get at its hold.");
+		return;
+	}
+
+	if ships[&player_loc].raft {
+		state.write_msg_buff("A raft doesn't have a hold.");
+		return;
+	}
+
+	let mut ship = ships.remove(&player_loc).unwrap();
+	let menu = vec![format!("The hold of the {} ({}/{} full):", ship.name, ship.hold_weight(), HOLD_CAPACITY),
+		String::from("a) Stow something"), String::from("b) Fetch something")];
+
+	match gui.menu_picker(&menu, 2, true, false) {
+		Some(v) => {
+			if v.contains(&0) {
+				stow_in_hold(state, &mut ship, gui);
+			} else {
+				fetch_from_hold(state, &mut ship, gui);
+			}
+		},
+		None => state.write_msg_buff("Nevermind."),
+	}
+
+	ships.insert(player_loc, ship);
+}
+
+// A lit bottle of rum makes a fine firebomb. It bursts into flames where it 
+// lands and might splash burning rum onto a neighbouring square too.
+fn molotov(state: &mut GameState, row: usize, col: usize) {
+	state.write_msg_buff("The bottle smashes and bursts into flames!");
+	state.start_fire(row, col);
+
+	if rand::thread_rng().gen_range(0.0, 1.0) < 0.5 {
+		let adj = util::rnd_adj();
+		let adj_r = (row as i32 + adj.0) as usize;
+		let adj_c = (col as i32 + adj.1) as usize;
+		if map::in_bounds(&state.map[&state.map_id], adj_r as i32, adj_c as i32) {
+			state.start_fire(adj_r, adj_c);
+		}
+	}
+}
+
+fn throw_item(state: &mut GameState, items: &mut ItemsTable, gui: &mut GameUI) {
+	if state.player.inventory.get_menu().is_empty() {
+		state.write_msg_buff("You are empty handed.");
+		return
+	}
+
+	let sbi = state.curr_sidebar_info();
+	let ch = match gui.query_single_response("Throw what?", &sbi) {
+		Some(ch) => ch,
+		None => { state.write_msg_buff("Nevermind."); return; },
+	};
+
+	let item = match state.player.inventory.peek_at(ch) {
+		Some(item) => item,
+		None => { state.write_msg_buff("You do not have that item."); return; },
+	};
+	if item.equiped {
+		state.write_msg_buff("You'll have to unequip it first.");
+		return;
+	}
+
+	let start = initial_target(state, gui);
+	let target = match gui.pick_target("Throw where? (f to throw):", start, THROW_RANGE, &sbi) {
+		Some(t) if t != (0, 0) => t,
+		_ => { state.write_msg_buff("Nevermind."); return; },
+	};
+
+	// The item flies until it either hits something solid, hits someone, 
+	// reaches the target square or runs out of oomph
+	let mut landing = (state.player.row, state.player.col);
+	for pt in util::bresenham_line(0, 0, target.0, target.1).iter().skip(1).take(THROW_RANGE as usize) {
+		let r = state.player.row as i32 + pt.0;
+		let c = state.player.col as i32 + pt.1;
+		if !map::in_bounds(&state.map[&state.map_id], r, c) { break; }
+		if !map::is_passable(&state.map[&state.map_id][r as usize][c as usize]) { break; }
+		landing = (r as usize, c as usize);
+		if state.npcs[&state.map_id].is_npc_at(landing.0, landing.1) { break; }
+	}
+
+	let mut item = state.player.inventory.remove_count(ch, 1).pop().unwrap();
+	item.equiped = false;
+	state.turn += 1;
+
+	if item.item_type == ItemType::Note && state.map_id == 0
+			&& floats(&state.map[&0][landing.0][landing.1]) {
+		bottle_note(state, landing, item);
+	} else if item.name == "draught of rum" {
+		if let Some(light) = state.player.inventory.active_light() {
+			let s = format!("You light the rum from your {} and hurl it!", light.name);
+			state.write_msg_buff(&s);
+			molotov(state, landing.0, landing.1);
+		} else if near_flame(state, landing.0, landing.1) {
+			molotov(state, landing.0, landing.1);
+		} else {
+			state.write_msg_buff("The bottle smashes. What a waste of good rum.");
+		}
+	} else {
+		let s = format!("You throw {}.", util::get_articled_name(true, &item));
+		state.write_msg_buff(&s);
+		if state.map[&state.map_id][landing.0][landing.1] == Tile::DeepWater {
+			state.write_msg_buff("It sinks out of sight.");
+		} else {
+			items.add_or_spill(&state.map[&state.map_id], landing.0, landing.1, item);
+		}
+	}
+}
+
+// A note tossed into the sea goes in a bottle (if the player has one to 
+// spare) and drifts off wherever the wind takes it, maybe to wash up on 
+// some far beach.
+fn bottle_note(state: &mut GameState, loc: (usize, usize), mut note: Item) {
+	match state.player.inventory.count_of_item("draught of rum") {
+		Some((_, ch)) => {
+			state.player.inventory.remove_count(ch, 1);
+			state.player.drunkeness += 10;
+			state.write_msg_buff("You drain a rum, cork the note inside and toss it to the waves.");
+			note.name = String::from("message in a bottle");
+			state.bottles.push((loc.0, loc.1, note));
+		},
+		None => {
+			state.write_msg_buff("With no bottle for it, the note soon sinks.");
+		},
+	}
+}
+
+// Bottles bob along downwind, with the odd wave pushing them off course.
+// When one reaches land it's left on the beach for whoever finds it.
+fn drift_bottles(state: &mut GameState, items: &mut ItemsTable) {
+	const DIRS: [(i32, i32); 8] = [(-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1)];
+	let world_map = &state.map[&0];
+	// The wind is named for where it blows from so the bottle goes the other way
+	let downwind = DIRS[((state.wind as usize + 8) % 16).div_ceil(2) % 8];
+
+	let mut adrift = Vec::new();
+	for (r, c, bottle) in state.bottles.drain(..) {
+		if rand::thread_rng().gen_range(0.0, 1.0) < 0.66 {
+			adrift.push((r, c, bottle));
+			continue;
+		}
+
+		let delta = if rand::thread_rng().gen_range(0.0, 1.0) < 0.25 {
+			DIRS[rand::thread_rng().gen_range(0, 8)]
+		} else {
+			downwind
+		};
+		let nr = r as i32 + delta.0;
+		let nc = c as i32 + delta.1;
+		if !map::in_bounds(world_map, nr, nc) || world_map[nr as usize][nc as usize] == Tile::WorldEdge {
+			adrift.push((r, c, bottle));
+		} else if floats(&world_map[nr as usize][nc as usize]) {
+			adrift.push((nr as usize, nc as usize, bottle));
+		} else {
+			items.add_or_spill(world_map, nr as usize, nc as usize, bottle);
+		}
+	}
+	state.bottles = adrift;
+}
+
+fn near_flame(state: &GameState, row: usize, col: usize) -> bool {
+	for r in -1..=1 {
+		for c in -1..=1 {
+			let adj_r = row as i32 + r;
+			let adj_c = col as i32 + c;
+			if map::in_bounds(&state.map[&state.map_id], adj_r, adj_c)
+					&& state.map[&state.map_id][adj_r as usize][adj_c as usize] == Tile::FirePit {
+				return true;
+			}
+		}
+	}
+
+	false
+}
+
+// Heap up some planks on the beach and set them blazing. Animals keep well
+// clear of it, but after dark it can be seen for leagues and any pirates 
+// about will come to see who lit it.
+fn build_signal_fire(state: &mut GameState, slot: char, _items: &mut ItemsTable, _ships: &ShipsTable, gui: &mut GameUI) {
+	if state.player.on_ship {
+		state.write_msg_buff("Not aboard ship!");
+		return;
+	} else if state.player.inventory.count_in_slot(slot) < SIGNAL_FIRE_PLANKS {
+		let s = format!("You need {} planks for a proper signal fire.", SIGNAL_FIRE_PLANKS);
+		state.write_msg_buff(&s);
+		return;
+	} else if state.signal_fire.is_some() {
+		state.write_msg_buff("You already have a signal fire burning.");
+		return;
+	}
+
+	let sbi = state.curr_sidebar_info();
+	let dir = match gui.pick_direction("Build the fire where?", &sbi) {
+		Some(dir) => dir,
+		None => { state.write_msg_buff("Nevermind."); return; },
+	};
+
+	let r = (state.player.row as i32 + dir.0) as usize;
+	let c = (state.player.col as i32 + dir.1) as usize;
+	if state.map[&state.map_id][r][c] != Tile::Sand {
+		state.write_msg_buff("Best to build it out on the beach.");
+		return;
+	} else if state.npcs[&state.map_id].is_npc_at(r, c) {
+		state.write_msg_buff("Something's in the way.");
+		return;
+	}
+
+	if !state.player.inventory.active_light_source() && !near_flame(state, r, c) {
+		state.write_msg_buff("You've nothing to light it with.");
+		return;
+	}
+
+	state.player.inventory.remove_count(slot, SIGNAL_FIRE_PLANKS);
+	state.start_fire_for(r, c, SIGNAL_FIRE_LIFESPAN);
+	for dr in -1..=1 {
+		for dc in -1..=1 {
+			let adj_r = r as i32 + dr;
+			let adj_c = c as i32 + dc;
+			if !map::in_bounds(&state.map[&state.map_id], adj_r, adj_c) { continue; }
+			if (adj_r as usize, adj_c as usize) == (state.player.row, state.player.col) { continue; }
+			if state.map[&state.map_id][adj_r as usize][adj_c as usize] == Tile::Sand 
+					&& rand::thread_rng().gen_range(0.0, 1.0) < 0.33 {
+				state.start_fire_for(adj_r as usize, adj_c as usize, SIGNAL_FIRE_LIFESPAN);
+			}
+		}
+	}
+
+	state.signal_fire = Some((state.map_id, r, c, SIGNAL_FIRE_LIFESPAN));
+	state.write_msg_buff("The planks catch and a great blaze roars up!");
+	if state.is_night() {
+		state.write_msg_buff("It must be visible for leagues...");
+	}
+	state.player.exert(3);
+	state.turn += 2;
+}
+
+// Now and then a line of atmosphere to set the scene: the sounds of the
+// sea, the ship or the caves depending on where the player is
+fn ambient_msg(state: &mut GameState) {
+	if rand::thread_rng().gen_range(0.0, 1.0) > 0.02 {
+		return;
+	}
+
+	let lines = if state.map_id != 0 {
+		vec!["Water drips somewhere in the dark.", "You hear a distant growl.",
+			"Something skitters off into the dark.", "A cold draft stirs from deeper in."]
+	} else if state.player.on_ship && state.is_night() {
+		vec!["Her timbers creak in the dark.", "The rigging groans overhead.",
+			"The sea slaps against the hull.", "A lantern sways on a far-off mast. Or was that a star?"]
+	} else if state.player.on_ship {
+		vec!["Gulls wheel and cry above the mast.", "Spray bursts over the bow.",
+			"The sails snap and fill.", "A flying fish skips across the waves."]
+	} else if state.is_night() {
+		vec!["Surf hisses on a far-off beach.", "Insects drone in the dark.",
+			"An owl hoots.", "The stars wheel slowly overhead."]
+	} else {
+		vec!["A seabird cries overhead.", "You hear the waves breaking on the shore.",
+			"A crab scuttles out of your way.", "The wind rustles the fronds."]
+	};
+
+	let j = rand::thread_rng().gen_range(0, lines.len());
+	state.write_flavour_msg(lines[j]);
+}
+
+fn tend_signal_fire(state: &mut GameState) {
+	if let Some((map_id, r, c, turns_left)) = state.signal_fire {
+		if turns_left <= 1 {
+			state.signal_fire = None;
+			if map_id == state.map_id {
+				state.write_msg_buff("Your signal fire has burnt down to embers.");
+			}
+			return;
+		}
+		state.signal_fire = Some((map_id, r, c, turns_left - 1));
+
+		if state.is_night() {
+			state.npcs.get_mut(&map_id).unwrap().drawn_to_fire(r, c, 50);
+		}
+	}
+}
+
+// Monsters caught in a fire get burnt at the end of their turn. Returns
+// true if the fire killed them.
+fn burn_npc(state: &mut GameState, npc: &mut actor::Monster) -> bool {
+	if state.map[&state.map_id][npc.row][npc.col] != Tile::FirePit 
+			|| npc.npc_type == actor::NPCType::Boulder {
+		return false;
+	}
+
+	let dmg = npc.resistance(DmgType::Fire).adjust(dice::roll(6, 1, 0));
+	let visible = util::cartesian_d(npc.row, npc.col, state.player.row, state.player.col) < 10;
+	if dmg >= npc.hp {
+		if visible {
+			let s = format!("The {} burns to death!", npc.name);
+			state.write_msg_buff(&s);
+		}
+		if npc.leaves_corpse() {
+			state.add_corpse(npc.row, npc.col);
+		}
+		if npc.npc_type == actor::NPCType::UndeadCaptain {
+			undead_crew_collapses(state, npc.id);
+		}
+		state.player.score += npc.score;
+		state.record_kill(npc.npc_type.clone());
+		true
+	} else {
+		if visible {
+			let s = format!("The {} is burnt!", npc.name);
+			state.write_msg_buff(&s);
+		}
+		npc.hp -= dmg;
+		npc.aware_of_player = true;
+		false
+	}
+}
+
+// Hack planks out of a tree, or nail them across a window or doorway
+fn carpentry(state: &mut GameState, ships: &mut ShipsTable, gui: &mut GameUI) {
+	let sbi = state.curr_sidebar_info();
+	let dir = match gui.pick_direction("Chop, barricade or build a raft in which direction?", &sbi) {
+		Some(dir) => dir,
+		None => { state.write_msg_buff("Nevermind."); return; },
+	};
+
+	let r = (state.player.row as i32 + dir.0) as usize;
+	let c = (state.player.col as i32 + dir.1) as usize;
+
+	if state.map[&state.map_id][r][c] == Tile::Tree {
+		if state.player.inventory.get_equiped_weapon().is_none() {
+			state.write_msg_buff("You'll need a blade to chop down a tree.");
+			return;
+		}
+		if !state.player.inventory.has_room_for(&Item::get_item("plank").unwrap()) {
+			state.write_msg_buff("Ye can carry no more!");
+			return;
+		}
+
+		state.write_msg_buff("You hack down the tree and trim a couple of planks from it.");
+		state.map.get_mut(&state.map_id).unwrap()[r][c] = Tile::Dirt;
+		for _ in 0..2 {
+			state.player.inventory.add(Item::get_item("plank").unwrap());
+		}
+		state.player.exert(2);
+		state.turn += 3;
+	} else if map::can_barricade(&state.map[&state.map_id], r, c) {
+		if state.npcs[&state.map_id].is_npc_at(r, c) {
+			state.write_msg_buff("There's someone in the way!");
+			return;
+		}
+
+		match state.player.inventory.count_of_item("plank") {
+			Some((count, slot)) if count >= 2 => {
+				state.player.inventory.remove_count(slot, 2);
+				let under = state.map[&state.map_id][r][c].clone();
+				state.barricaded.insert((state.map_id, r, c), under);
+				state.map.get_mut(&state.map_id).unwrap()[r][c] = Tile::Barricade(BARRICADE_HP);
+				state.write_msg_buff("You nail planks across the gap.");
+				state.turn += 3;
+			},
+			_ => state.write_msg_buff("You need at least two planks to build a barricade."),
+		}
+	} else if floats(&state.map[&state.map_id][r][c]) && state.map_id == 0 {
+		build_raft(state, ships, r, c);
+	} else {
+		state.write_msg_buff("There's nothing to build or chop there.");
+	}
+}
+
+// A stranded pirate can lash planks together into a raft from the shore. She's
+// slow and won't take much of a beating but she'll get them to another island.
+fn build_raft(state: &mut GameState, ships: &mut ShipsTable, r: usize, c: usize) {
+	let here = &state.map[&state.map_id][state.player.row][state.player.col];
+	if state.player.on_ship || floats(here) {
+		state.write_msg_buff("You'll need to be standing on the shore to build a raft.");
+		return;
+	}
+
+	if state.npcs[&state.map_id].is_npc_at(r, c) || !sq_is_open(state, ships, r, c) {
+		state.write_msg_buff("There's something in the way!");
+		return;
+	}
+
+	match state.player.inventory.count_of_item("plank") {
+		Some((count, slot)) if count >= RAFT_PLANKS => {
+			state.player.inventory.remove_count(slot, RAFT_PLANKS);
+			let mut raft = Ship::new_raft();
+			raft.row = r;
+			raft.col = c;
+			raft.update_loc_info();
+			ships.insert((r, c), raft);
+			state.write_msg_buff("You lash the planks together into a rickety raft.");
+			state.player.exert(4);
+			state.turn += 10;
+		},
+		_ => state.write_msg_buff(&format!("You need at least {} planks to build a raft.", RAFT_PLANKS)),
+	}
+}
+
+// Pry planks loose from the wreckage the player is standing on. Each bit of
+// wreck gives up less every time until there's nothing left but splinters.
+fn salvage(state: &mut GameState) {
+	let loc = (state.player.row, state.player.col);
+	let tile = state.map[&state.map_id][loc.0][loc.1].clone();
+	match tile {
+		Tile::Shipwreck(_, _) | Tile::Mast(_) | Tile::ShipPart(_) => { },
+		_ => {
+			state.write_msg_buff("There's nothing here to salvage.");
+			return;
+		},
+	}
+
+	if state.player.inventory.get_equiped_weapon().is_none() {
+		state.write_msg_buff("You'll need a blade to pry anything loose.");
+		return;
+	}
+	if !state.player.inventory.has_room_for(&Item::get_item("plank").unwrap()) {
+		state.write_msg_buff("Ye can carry no more!");
+		return;
+	}
+
+	let times = *state.salvaged.get(&loc).unwrap_or(&0);
+	if times >= SALVAGE_YIELD {
+		state.write_msg_buff("There's nothing left here worth salvaging.");
+		return;
+	}
+
+	let planks = SALVAGE_YIELD - times;
+	for _ in 0..planks {
+		state.player.inventory.add(Item::get_item("plank").unwrap());
+	}
+	if planks == 1 {
+		state.write_msg_buff("You pry loose one last sound plank.");
+	} else {
+		state.write_msg_buff(&format!("You pry {} planks loose from the wreck.", planks));
+	}
+	state.salvaged.insert(loc, times + 1);
+	state.player.exert(2);
+	state.turn += 5;
+
+	// Once it's been picked clean, there's only broken timbers left. The 
+	// wreck keeps its name so the clues that point to it still make sense.
+	if times + 1 == SALVAGE_YIELD {
+		let curr_map = state.map.get_mut(&state.map_id).unwrap();
+		curr_map[loc.0][loc.1] = match tile {
+			Tile::Shipwreck(_, name) => Tile::Shipwreck('_', name),
+			_ => Tile::Mast('_'),
+		};
+	}
+}
+
+// A spotted pressure plate can be jammed so it's safe to walk over. It's
+// fiddly work and a clumsy pirate might set the thing off instead.
+fn disarm_trap(state: &mut GameState, gui: &mut GameUI) {
+	let sbi = state.curr_sidebar_info();
+	let dir = match gui.pick_direction("Disarm in which direction?", &sbi) {
+		Some(dir) => dir,
+		None => { state.write_msg_buff("Nevermind."); return; },
+	};
+
+	let r = (state.player.row as i32 + dir.0) as usize;
+	let c = (state.player.col as i32 + dir.1) as usize;
+
+	match state.map[&state.map_id][r][c] {
+		Tile::BoulderTrap(colour, false, false, b_loc, b_dir) => {
+			let dex_mod = Player::mod_for_stat(state.player.dexterity);
+			state.turn += 1;
+			if do_ability_check(dex_mod, 13, state.player.prof_bonus as i8) {
+				state.write_msg_buff("You wedge the pressure plate so it can't be pressed.");
+				state.map.get_mut(&state.map_id).unwrap()[r][c] = Tile::StoneFloor;
+			} else if rand::thread_rng().gen_range(0.0, 1.0) < 0.5 {
+				state.write_msg_buff("Your hand slips... CLICK! RUMBLE");
+				state.map.get_mut(&state.map_id).unwrap()[r][c] = 
+					Tile::BoulderTrap(colour, false, true, b_loc, b_dir);
+				state.npcs.get_mut(&state.map_id)
+					.unwrap()
+					.new_boulder(b_loc.0, b_loc.1, b_dir);
+			} else {
+				state.write_msg_buff("You fiddle with the mechanism but can't jam it.");
+			}
+		},
+		Tile::BoulderTrap(_, false, true, _, _) => 
+			state.write_msg_buff("That trap has already been sprung."),
+		_ => state.write_msg_buff("You don't see a trap there."),
+	}
+}
+
+// Only a pirate who's earned it may wear a captain's hat
+fn may_put_on(state: &GameState, slot: char) -> bool {
+	match state.player.inventory.peek_at(slot) {
+		Some(item) => item.name != "captain's tricorn" || item.equiped || state.earned_captaincy(),
+		None => true,
+	}
+}
+
+// Apply (or take away) the stat bonus of a piece of gear that has just been
+// put on or taken off
+fn apply_stat_bonus(state: &mut GameState, slot: char) {
+	let item = state.player.inventory.peek_at(slot).unwrap();
+	if item.stat_bonus != (0, 0) {
+		let modifier = if item.equiped {
+			item.stat_bonus.1
+		} else {
+			-item.stat_bonus.1
+		};
+		
+		if item.stat_bonus.0 == 0 {
+			state.player.strength = (state.player.strength as i8 + modifier) as u8;
+			if modifier < 0 {
+				state.write_msg_buff("You feel a bit weaker.");
+			} else {
+				state.write_msg_buff("You feel a bit stronger.");
+			}
+		}
+		if item.stat_bonus.0 == 2 {
+			state.player.dexterity = (state.player.dexterity as i8 + modifier) as u8;
+			if modifier < 0 {
+				state.write_msg_buff("You feel a bit more klutzy.");
+			} else {
+				state.write_msg_buff("You feel a bit more deft.");
+			}
+			state.player.calc_ac();
+		}
+		if item.stat_bonus.0 == 1 {
+			state.player.constitution = (state.player.constitution as i8 + modifier) as u8;
+			if modifier < 0 {
+				state.write_msg_buff("You feel a little fatigued.");
+				state.player.max_stamina -= 10;
+				if state.player.curr_stamina > state.player.max_stamina {
+					state.player.curr_stamina = state.player.max_stamina;
+				}
+			} else {
+				state.write_msg_buff("You feel full of gusto.");
+				state.player.max_stamina += 10;
+			}
+		}
+		if item.stat_bonus.0 == 3 {
+			state.player.verve = (state.player.verve as i8 + modifier) as u8;
+			if modifier < 0 {
+				state.write_msg_buff("You feel a bit more bashful.");
+			} else {
+				state.write_msg_buff("You feel a bit more cheeky.");
+			}
+		}
 	}
-
-	state.player.calc_ac();
 }
 
-fn pick_up(state: &mut GameState, items: &mut ItemsTable, gui: &mut GameUI) -> Result<(), ExitReason> {
-	let item_count = items.count_at(state.player.row, state.player.col);
-	if item_count == 0 {
-		state.write_msg_buff("There is nothing here to pick up.");
-	} else if item_count == 1 {
-		let item = items.get_at(state.player.row, state.player.col);
-		let is_macguffin = item.item_type == ItemType::MacGuffin;
-		let s = format!("You pick up {}.", util::get_articled_name(true, &item));
-		state.write_msg_buff(&s);
-		state.player.inventory.add(item);
-		state.turn += 1;
+// Handy after looting a cache: swap in the best weapon, gun and armour the
+// player has on them
+fn auto_equip(state: &mut GameState) {
+	let kinds = [ItemType::Weapon, ItemType::Firearm, ItemType::Coat, 
+		ItemType::Hat, ItemType::Shoes, ItemType::Gloves];
+	let mut changed = false;
+
+	for kind in kinds.iter() {
+		let best = match state.player.inventory.best_gear(*kind) {
+			Some(slot) => slot,
+			None => continue,
+		};
+		if state.player.inventory.peek_at(best).unwrap().equiped || !may_put_on(state, best) {
+			continue;
+		}
 
-		if is_macguffin {
-			return Err(ExitReason::Win);
+		if let Some(curr) = state.player.inventory.equiped_slot(*kind) {
+			let result = state.player.inventory.toggle_slot(curr);
+			state.write_msg_buff(&result.0);
+			apply_stat_bonus(state, curr);
 		}
-	} else {
-		let mut menu = items.get_menu(state.player.row, state.player.col);
-		menu.insert(0, "Pick up what: (* to get everything)".to_string());
-		let answers = gui.menu_picker(&menu, menu.len() as u8, false, false);
-		match answers {
-			None => state.write_msg_buff("Nevermind."), // Esc was pressed
-			Some(v) => {
-				state.turn += 1;
-				let picked_up = items.get_many_at(state.player.row, state.player.col, &v);
-				for item in picked_up {
-					let is_macguffin = item.item_type == ItemType::MacGuffin;
-					let s = format!("You pick up {}.", util::get_articled_name(true, &item));
-					state.write_msg_buff(&s);
-					state.player.inventory.add(item);
-				
-					if is_macguffin {
-						return Err(ExitReason::Win);
-					}
-				}
-			},
+
+		let result = state.player.inventory.toggle_slot(best);
+		state.write_msg_buff(&result.0);
+		if result.1 {
+			apply_stat_bonus(state, best);
+			changed = true;
 		}
 	}
 
-	Ok(())
+	if changed {
+		state.turn += 1;
+		state.player.calc_ac();
+	} else {
+		state.write_msg_buff("You're already kitted out as well as ye can be.");
+	}
 }
 
 fn toggle_equipment(state: &mut GameState, gui: &mut GameUI) {
@@ -1143,58 +3715,14 @@ fn toggle_equipment(state: &mut GameState, gui: &mut GameUI) {
 
 	let sbi = state.curr_sidebar_info();
 	match gui.query_single_response("Ready/unready what?", &sbi) {
+		Some(ch) if !may_put_on(state, ch) => 
+			state.write_msg_buff("Don't ye be wearing a captain's hat 'afore ye've earned it!"),
 		Some(ch) => {
 			let result = state.player.inventory.toggle_slot(ch);
 			state.write_msg_buff(&result.0);
 
 			if result.1 {
-				let item = state.player.inventory.peek_at(ch).unwrap();
-				if item.stat_bonus != (0, 0) {
-					let modifier = if item.equiped {
-						item.stat_bonus.1
-					} else {
-						-1 * item.stat_bonus.1
-					};
-					
-					if item.stat_bonus.0 == 0 {
-						state.player.strength = (state.player.strength as i8 + modifier) as u8;
-						if modifier < 0 {
-							state.write_msg_buff("You feel a bit weaker.");
-						} else {
-							state.write_msg_buff("You feel a bit stronger.");
-						}
-					}
-					if item.stat_bonus.0 == 2 {
-						state.player.dexterity = (state.player.dexterity as i8 + modifier) as u8;
-						if modifier < 0 {
-							state.write_msg_buff("You feel a bit more klutzy.");
-						} else {
-							state.write_msg_buff("You feel a bit more deft.");
-						}
-						state.player.calc_ac();
-					}
-					if item.stat_bonus.0 == 1 {
-						state.player.constitution = (state.player.constitution as i8 + modifier) as u8;
-						if modifier < 0 {
-							state.write_msg_buff("You feel a little fatigued.");
-							state.player.max_stamina -= 10;
-							if state.player.curr_stamina > state.player.max_stamina {
-								state.player.curr_stamina = state.player.max_stamina;
-							}
-						} else {
-							state.write_msg_buff("You feel full of gusto.");
-							state.player.max_stamina += 10;
-						}
-					}
-					if item.stat_bonus.0 == 3 {
-						state.player.verve = (state.player.verve as i8 + modifier) as u8;
-						if modifier < 0 {
-							state.write_msg_buff("You feel a bit more bashful.");
-						} else {
-							state.write_msg_buff("You feel a bit more cheeky.");
-						}
-					}
-				}
+				apply_stat_bonus(state, ch);
 			}
 			state.turn += 1;
 		},
@@ -1205,18 +3733,48 @@ fn toggle_equipment(state: &mut GameState, gui: &mut GameUI) {
 }
 
 fn show_inventory(state: &mut GameState, gui: &mut GameUI) {
-	let mut menu = state.player.inventory.get_menu();
+	let mut menu = state.player.inventory.get_grouped_menu();
 
 	if menu.len() == 0 {
 		state.write_msg_buff("You are empty-handed.");
 	} else {
 		menu.insert(0, "You are carrying:".to_string());
-		gui.write_long_msg(&menu, false);
+		let prompt = "Select an item to examine it, or Esc to close.";
+		// A big haul won't fit on the screen in the regular font
+		let small_font = menu.len() > 19;
+		if let Some(ch) = gui.menu_query(&menu, prompt, small_font) {
+			if let Some(item) = state.player.inventory.peek_at(ch) {
+				gui.write_long_msg(&item.describe(), false);
+			}
+		}
+	}
+}
+
+fn show_bestiary(state: &GameState, gui: &mut GameUI) {
+	let mut lines = vec![String::from("Creatures ye've encountered:"), String::from("")];
+
+	if state.bestiary.is_empty() {
+		lines.push(String::from("  None yet. The seas are quiet...for now."));
+	} else {
+		let mut entries = state.bestiary.iter()
+								.map(|t| t.bestiary_entry())
+								.collect::<Vec<(char, &str, &str, &str)>>();
+		entries.sort_by(|a, b| a.1.cmp(b.1));
+		for e in entries {
+			lines.push(format!("  {}  {} ({})", e.0, e.1, e.2));
+			lines.push(format!("       {}", e.3));
+		}
 	}
+
+	gui.write_long_msg(&lines, true);
 }
 
 fn show_character_sheet(state: &GameState, gui: &mut GameUI) {
-	let s = format!("{}, a bilge rat", state.player.name);
+	let s = if state.player.inventory.equiped_captains_hat() {
+		format!("{}, master of the {}", state.title_name(), state.player_ship)
+	} else {
+		format!("{}, a bilge rat", state.player.name)
+	};
 	let mut lines = vec![s];
 	lines.push("".to_string());
 	let s = format!("Strength: {}", state.player.strength);
@@ -1230,6 +3788,36 @@ fn show_character_sheet(state: &GameState, gui: &mut GameUI) {
 	lines.push("".to_string());
 	let s = format!("AC: {}    Stamina: {}({})", state.player.ac, state.player.curr_stamina, state.player.max_stamina);
 	lines.push(s);
+	lines.push("".to_string());
+	lines.push("Equipment:".to_string());
+
+	let inv = &state.player.inventory;
+	match inv.get_equiped_weapon() {
+		Some(w) => lines.push(format!("  Weapon: {} ({}d{})", w.name, w.dmg_dice, w.dmg)),
+		None => lines.push("  Weapon: yer bare fists".to_string()),
+	}
+	if let Some(g) = inv.get_equiped_firearm() {
+		let loaded = if g.loaded { "loaded" } else { "empty" };
+		lines.push(format!("  Firearm: {} ({}d{}, {})", g.name, g.dmg_dice, g.dmg, loaded));
+	}
+
+	let worn = [(ItemType::Hat, "Hat"), (ItemType::Coat, "Coat"), (ItemType::Shoes, "Shoes"),
+			(ItemType::Gloves, "Gloves"), (ItemType::EyePatch, "Eye patch"), (ItemType::Flotation, "Vest")];
+	for (i_type, label) in worn.iter() {
+		if let Some(i) = inv.get_equiped(*i_type) {
+			lines.push(format!("  {}: {} (AC +{})", label, i.name, i.armour_value));
+		}
+	}
+
+	if let Some(f) = inv.get_equiped(ItemType::Fetish) {
+		lines.push(format!("  Fetish: {}", f.name));
+	}
+	if let Some(e) = inv.get_equiped(ItemType::Earplugs) {
+		lines.push(format!("  Ears: {}", e.name));
+	}
+	if let Some(l) = inv.active_light() {
+		lines.push(format!("  Light: {} ({} turns of fuel)", l.name, l.fuel));
+	}
 
 	gui.write_long_msg(&lines, true);
 }
@@ -1272,7 +3860,11 @@ fn get_open_sq_adj_player(state: &GameState, ships: &ShipsTable) -> Option<(usiz
 
 fn ship_hit_land(state: &mut GameState, ship: &mut Ship, ships: &ShipsTable) -> Result<(), ExitReason> {
 	state.write_msg_buff("Ye've run yer ship aground!!");
-	state.write_msg_buff("You lose control o' the wheel!");
+	if state.player.on_ship {
+		state.write_msg_buff("You lose control o' the wheel!");
+	} else {
+		state.write_msg_buff("The wheel spins out of control!");
+	}
 	let mut new_wheel = ship.wheel + 2 + dice::roll(5, 1, 0) as i8;	
 	new_wheel = new_wheel % 5 - 2;
 	ship.wheel = new_wheel;
@@ -1293,13 +3885,289 @@ fn ship_hit_land(state: &mut GameState, ship: &mut Ship, ships: &ShipsTable) ->
 	Ok(())
 }
 
-fn sail(state: &mut GameState, ships: &mut ShipsTable) -> Result<(), ExitReason> {
+fn floats(tile: &Tile) -> bool {
+	*tile == Tile::Water || *tile == Tile::DeepWater || *tile == Tile::Reef
+}
+
+// Heave a beached ship back off into open water. It's hard, sweaty work and
+// the more water there is around the ship the better the odds.
+fn kedge_off(state: &mut GameState, ships: &mut ShipsTable) {
 	let mut ship = ships.remove(&(state.player.row, state.player.col)).unwrap();
+	let curr_map = &state.map[&state.map_id];
+	if floats(&curr_map[ship.bow_row][ship.bow_col]) {
+		state.write_msg_buff("She's not aground.");
+		ships.insert((ship.row, ship.col), ship);
+		return;
+	}
+
+	let mut water_count = 0;
+	for r in -1..=1 {
+		for c in -1..=1 {
+			if r == 0 && c == 0 { continue; }
+			let adj_r = ship.row as i32 + r;
+			let adj_c = ship.col as i32 + c;
+			if map::in_bounds(curr_map, adj_r, adj_c) && floats(&curr_map[adj_r as usize][adj_c as usize]) {
+				water_count += 1;
+			}
+		}
+	}
+
+	state.turn += 5;
+	state.player.exert(4);
+	state.write_msg_buff("You heave against the grounded hull...");
+
+	let str_mod = Player::mod_for_stat(state.player.strength);
+	if !do_ability_check(str_mod, 18 - water_count, 0) {
+		state.write_msg_buff("...but she won't budge.");
+		ships.insert((ship.row, ship.col), ship);
+		return;
+	}
+
+	// Find the spot next to where she sits with the most open water in front 
+	// of her bow.
+	let curr_map = &state.map[&state.map_id];
+	let (start_r, start_c, start_bearing) = (ship.row, ship.col, ship.bearing);
+	let mut best = None;
+	let mut best_score = 0;
+	for r in -1..=1 {
+		for c in -1..=1 {
+			if !map::in_bounds(curr_map, start_r as i32 + r, start_c as i32 + c) { continue; }
+			let adj_r = (start_r as i32 + r) as usize;
+			let adj_c = (start_c as i32 + c) as usize;
+			if !floats(&curr_map[adj_r][adj_c]) { continue; }
+			if (adj_r, adj_c) != (start_r, start_c) && !sq_is_open(state, ships, adj_r, adj_c) { continue; }
+
+			for bearing in (0..16).step_by(2) {
+				ship.row = adj_r;
+				ship.col = adj_c;
+				ship.bearing = bearing;
+				ship.update_loc_info();
+				let bow = (ship.bow_row, ship.bow_col);
+				if !map::in_bounds(curr_map, bow.0 as i32, bow.1 as i32)
+						|| !map::in_bounds(curr_map, ship.aft_row as i32, ship.aft_col as i32)
+						|| !floats(&curr_map[bow.0][bow.1]) 
+						|| (bow != (start_r, start_c) && !sq_is_open(state, ships, bow.0, bow.1)) {
+					continue;
+				}
+
+				let mut score = 1;
+				for br in -1..=1 {
+					for bc in -1..=1 {
+						let r = (ship.bow_row as i32 + br) as usize;
+						let c = (ship.bow_col as i32 + bc) as usize;
+						if map::in_bounds(curr_map, r as i32, c as i32) && floats(&curr_map[r][c]) {
+							score += 1;
+						}
+					}
+				}
+				if score > best_score {
+					best_score = score;
+					best = Some((adj_r, adj_c, bearing));
+				}
+			}
+		}
+	}
+
+	match best {
+		Some((r, c, bearing)) => {
+			ship.row = r;
+			ship.col = c;
+			ship.bearing = bearing;
+			ship.wheel = 0;
+			ship.update_loc_info();
+			state.player.row = r;
+			state.player.col = c;
+			state.player.bearing = bearing;
+			state.player.wheel = 0;
+			state.write_msg_buff("...and she slides free!");
+		},
+		None => {
+			ship.row = start_r;
+			ship.col = start_c;
+			ship.bearing = start_bearing;
+			ship.update_loc_info();
+			state.write_msg_buff("...she shifts, but there's no open water to push her into.");
+		},
+	}
+
+	ships.insert((ship.row, ship.col), ship);
+}
+
+// Ramming another vessel stoves in both hulls, but it brings her alongside
+// close enough for the player to swing across and board her.
+fn ram_ship(state: &mut GameState, ship: &mut Ship, target_loc: (usize, usize), ships: &mut ShipsTable) {
+	let mut target = ships.remove(&target_loc).unwrap();
+	let s = format!("Ye ram the {}!", target.name);
+	state.write_msg_buff(&s);
+
+	let dmg = dice::roll(4, 1, 0);
+	ship.hull = ship.hull.saturating_sub(dmg);
+	let dmg = dice::roll(6, 2, 0);
+	target.hull = target.hull.saturating_sub(dmg);
+	ship.wheel = 0;
+	state.player.wheel = 0;
+
+	if ship.hull > 0 && ship.hull < ship::MAX_HULL / 4 {
+		state.write_msg_buff("She's taking on water fast!");
+	} else if ship.hull > 0 {
+		state.write_msg_buff("You hear your timbers crack.");
+	}
+
+	if target.hull == 0 {
+		let s = format!("The {} breaks apart and sinks!", target.name);
+		state.write_msg_buff(&s);
+		return;
+	}
+
+	let dex_mod = Player::mod_for_stat(state.player.dexterity);
+	if ship.hull > 0 && do_ability_check(dex_mod, 12, state.player.prof_bonus as i8) {
+		let s = format!("Ye swing across to the deck of the {}!", target.name);
+		state.write_msg_buff(&s);
+		ship.anchored = true;
+		state.player.on_ship = false;
+		state.player.row = target.row;
+		state.player.col = target.col;
+
+		if target.merchant {
+			state.write_msg_buff("'Repel boarders!'");
+			let npcs = state.npcs.get_mut(&state.map_id).unwrap();
+			for sq in [(target.bow_row, target.bow_col), (target.aft_row, target.aft_col)].iter() {
+				if !npcs.is_npc_at(sq.0, sq.1) {
+					npcs.new_sailor(sq.0, sq.1);
+				}
+			}
+		}
+	} else {
+		state.write_msg_buff("The ships grind apart before ye can get across.");
+	}
+
+	ships.insert(target_loc, target);
+}
+
+fn ship_hit_reef(state: &mut GameState, ship: &mut Ship) {
+	let dmg = dice::roll(6, 1, 0);
+	ship.hull = ship.hull.saturating_sub(dmg);
+
+	state.write_msg_buff("Ye've struck a reef!");
+	if ship.hull > 0 && ship.hull < ship::MAX_HULL / 4 {
+		state.write_msg_buff("She's taking on water fast!");
+	} else if ship.hull > 0 {
+		state.write_msg_buff("You hear the hull splinter.");
+	}
+}
+
+// How far off the wind the ship's heading is, in compass points: 0 is sailing
+// straight into it and 8 is running with it at your back.
+fn points_off_wind(bearing: u8, wind: u8) -> u8 {
+	let d = (bearing as i8 - wind as i8).rem_euclid(16) as u8;
+	if d > 8 { 16 - d } else { d }
+}
+
+// The wind decides how far the ship gets this turn. Beating into it is slow
+// going and she'll sometimes make no headway at all, while with the wind
+// behind her she'll now and then cover two squares.
+fn sail(state: &mut GameState, ships: &mut ShipsTable) -> Result<(), ExitReason> {
+	let loc = (state.player.row, state.player.col);
+	sail_ship(state, ships, loc)
+}
+
+fn sail_ship(state: &mut GameState, ships: &mut ShipsTable, loc: (usize, usize)) -> Result<(), ExitReason> {
+	// A raft has no keel or proper sail and is mostly at the mercy of the sea
+	let raft = match ships.get(&loc) {
+		Some(ship) => ship.raft && !ship.anchored,
+		None => false,
+	};
+	if raft && rand::thread_rng().gen_range(0.0, 1.0) < 0.5 {
+		state.write_flavour_msg("The raft wallows sluggishly.");
+		return Ok(());
+	}
+
+	let off_wind = match ships.get(&loc) {
+		Some(ship) if !ship.anchored => points_off_wind(ship.bearing, state.wind),
+		_ => 4,
+	};
+
+	if off_wind <= 1 && rand::thread_rng().gen_range(0.0, 1.0) < 0.5 {
+		state.write_flavour_msg("You're sailing into the wind -- slow going!");
+		return Ok(());
+	}
+
+	let in_shallows = match ships.get(&loc) {
+		Some(ship) => !ship.anchored && state.map[&state.map_id][ship.bow_row][ship.bow_col] == map::Tile::Water,
+		None => false,
+	};
+	// With a hand in the bow calling out the depth, she can be taken through
+	// the shallows with less dithering
+	let shallows_chance = if crew_aboard(state, ships) > 0 { 0.25 } else { 0.5 };
+	if in_shallows && rand::thread_rng().gen_range(0.0, 1.0) < shallows_chance {
+		state.write_flavour_msg("You ease her carefully through the shallows.");
+		return Ok(());
+	}
+
+	let loc = sail_one_sq(state, ships, loc)?;
+	let aboard = match ships.get(&loc) {
+		Some(ship) => ship.occupies(state.player.row, state.player.col),
+		None => false,
+	};
+
+	if off_wind >= 7 && aboard 
+			&& rand::thread_rng().gen_range(0.0, 1.0) < 0.33 {
+		let open_water = match ships.get(&loc) {
+			Some(ship) => {
+				let bow_tile = &state.map[&state.map_id][ship.bow_row][ship.bow_col];
+				*bow_tile == map::Tile::Water || *bow_tile == map::Tile::DeepWater
+			},
+			None => false,
+		};
+		if open_water {
+			state.write_flavour_msg("The wind fills your sails!");
+			sail_one_sq(state, ships, loc)?;
+		}
+	}
+
+	Ok(())
+}
+
+// Let the wind wander a point at a time so it's something to plan around
+// rather than something that changes under you every turn.
+fn shift_wind(state: &mut GameState) {
+	if rand::thread_rng().gen_range(0.0, 1.0) < 0.02 {
+		let delta = if rand::thread_rng().gen_range(0.0, 1.0) < 0.5 { -1 } else { 1 };
+		state.wind = (state.wind as i8 + delta).rem_euclid(16) as u8;
+		if state.player.on_ship {
+			state.write_msg_buff("The wind shifts.");
+		}
+	}
+}
+
+// Tame followers standing on the bow or stern of the player's ship count as
+// her crew
+fn crew_aboard(state: &GameState, ships: &ShipsTable) -> usize {
+	if !state.player.on_ship {
+		return 0;
+	}
+
+	match ships.get(&(state.player.row, state.player.col)) {
+		Some(ship) => {
+			let npcs = &state.npcs[&state.map_id];
+			let mut count = 0;
+			if npcs.is_follower_at(ship.bow_row, ship.bow_col) { count += 1; }
+			if npcs.is_follower_at(ship.aft_row, ship.aft_col) { count += 1; }
+			count
+		},
+		None => 0,
+	}
+}
+
+// Returns where the ship ended up
+fn sail_one_sq(state: &mut GameState, ships: &mut ShipsTable, loc: (usize, usize)) -> Result<(usize, usize), ExitReason> {
+	let mut ship = ships.remove(&loc).unwrap();
 	let bow_tile = state.map[&state.map_id][ship.bow_row][ship.bow_col].clone();
 
 	if ship.anchored {
-		state.write_msg_buff("The ships bobs.");
-	} else if bow_tile != map::Tile::Water && bow_tile != map::Tile::DeepWater {
+		state.write_flavour_msg("The ships bobs.");
+	} else if bow_tile != map::Tile::Water && bow_tile != map::Tile::DeepWater
+			&& bow_tile != map::Tile::Reef {
 		state.write_msg_buff("Your ship is beached!");
 	} else { 
 		let mut delta: (i8, i8) = (0, 0);
@@ -1369,77 +4237,160 @@ fn sail(state: &mut GameState, ships: &mut ShipsTable) -> Result<(), ExitReason>
 			}
 		}
 
-		// after movement, if the wheel is turned, adjust the bearing 
-		if ship.wheel != 0 {
-			let mut new_bearing = ship.bearing as i8 + ship.wheel;
-			
-			// Ugh how I wish that Rust handled -1 % 16 == 15 like Python does
-			// instead of returning -1...
-			if new_bearing < 0 {
-				new_bearing = 16 + ship.wheel;
-			} else if new_bearing > 15 {
-				new_bearing %= 16;
-			}
-
-			ship.bearing = new_bearing as u8;
-			state.player.bearing = new_bearing as u8;
-		}
-
-		state.player.row = (state.player.row as i32+ delta.0 as i32) as usize;
-		state.player.col = (state.player.col as i32 + delta.1 as i32) as usize;
+		// Is there another ship where she's headed?
+		let (start_r, start_c) = (ship.row, ship.col);
 		ship.row = (ship.row as i32 + delta.0 as i32) as usize;
 		ship.col = (ship.col as i32 + delta.1 as i32) as usize;
 		ship.update_loc_info();
-		ship.prev_move = delta;
+		let rammed = ships.iter()
+			.find(|(_, s)| s.occupies(ship.row, ship.col) || s.occupies(ship.bow_row, ship.bow_col)
+				|| s.occupies(ship.aft_row, ship.aft_col))
+			.map(|(loc, _)| *loc);
+		ship.row = start_r;
+		ship.col = start_c;
+		ship.update_loc_info();
 
-		if state.map[&state.map_id][ship.bow_row][ship.bow_col] == map::Tile::Water {
-			state.write_msg_buff("Shallow water...");
-		} else if state.map[&state.map_id][ship.bow_row][ship.bow_col] != map::Tile::DeepWater {
-			ship_hit_land(state, &mut ship, ships)?;
-		}
+		if let Some(loc) = rammed {
+			ram_ship(state, &mut ship, loc, ships);
+		} else {
+			// Take the crew off the deck while she moves and put them back
+			// at the bow, wheel and stern once she's in her new spot. The 
+			// player too, if they're somewhere on deck rather than at the helm.
+			let decks = [(ship.bow_row, ship.bow_col), (ship.row, ship.col), (ship.aft_row, ship.aft_col)];
+			let mut crew = Vec::new();
+			for (part, loc) in decks.iter().enumerate() {
+				if state.npcs[&state.map_id].is_follower_at(loc.0, loc.1) {
+					let npc = state.npcs.get_mut(&state.map_id).unwrap().npc_at(loc.0, loc.1).unwrap();
+					state.npcs.get_mut(&state.map_id).unwrap().remove(npc.id, loc.0, loc.1);
+					crew.push((npc, part));
+				}
+			}
+			let player_deck = if state.player.on_ship {
+				None
+			} else {
+				decks.iter().position(|loc| *loc == (state.player.row, state.player.col))
+			};
+
+			// after movement, if the wheel is turned, adjust the bearing 
+			if ship.wheel != 0 {
+				// rem_euclid() gives us Python-style -1 % 16 == 15
+				let new_bearing = (ship.bearing as i8 + ship.wheel).rem_euclid(16);
+
+				ship.bearing = new_bearing as u8;
+				state.player.bearing = new_bearing as u8;
+			}
+
+			if state.player.on_ship {
+				state.player.row = (state.player.row as i32+ delta.0 as i32) as usize;
+				state.player.col = (state.player.col as i32 + delta.1 as i32) as usize;
+			}
+			ship.row = (ship.row as i32 + delta.0 as i32) as usize;
+			ship.col = (ship.col as i32 + delta.1 as i32) as usize;
+			ship.update_loc_info();
+			ship.prev_move = delta;
+			let decks = [(ship.bow_row, ship.bow_col), (ship.row, ship.col), (ship.aft_row, ship.aft_col)];
+			if let Some(part) = player_deck {
+				state.player.row = decks[part].0;
+				state.player.col = decks[part].1;
+			}
 
-        // Check to see if the ship's bow hit anyone
-        if state.npcs[&state.map_id].is_npc_at(ship.bow_row, ship.bow_col) {
+			let bow = (ship.bow_row, ship.bow_col);
+			if escaped_strait(state, bow) {
+				state.write_msg_buff("Ye've slipped past the Navy and out of the strait!");
+				return Err(ExitReason::Win);
+			}
+
+			if state.map_id == 0 && state.sandbars.contains(&bow) {
+				// Once found, the sandbar is marked on the map as the shallows it is
+				state.write_msg_buff("CRUNCH! There's a sandbar under the bow!");
+				state.map.get_mut(&0).unwrap()[bow.0][bow.1] = map::Tile::Water;
+				state.sandbars.remove(&bow);
+				ship_hit_land(state, &mut ship, ships)?;
+			} else if state.map[&state.map_id][bow.0][bow.1] == map::Tile::Reef {
+				ship_hit_reef(state, &mut ship);
+			} else if state.map[&state.map_id][bow.0][bow.1] == map::Tile::Water {
+				if map::depth(&map::Tile::Water) < ship.draft() {
+					state.write_msg_buff("She's riding too low in the water for the shallows!");
+					ship_hit_land(state, &mut ship, ships)?;
+				} else {
+					state.write_msg_buff("Shallow water...");
+				}
+			} else if state.map[&state.map_id][bow.0][bow.1] != map::Tile::DeepWater {
+				ship_hit_land(state, &mut ship, ships)?;
+			}
+
+	        // Check to see if the ship's bow hit anyone
+	        if state.npcs[&state.map_id].is_npc_at(ship.bow_row, ship.bow_col) {
 
-            let mut npc = state.npcs.get_mut(&state.map_id)
-                                .unwrap()
-                                .npc_at(ship.bow_row, ship.bow_col)
-                                .unwrap();
-            let s = format!("Your ship hit a {}", npc.name);
-            state.write_msg_buff(&s);
+	            let mut npc = state.npcs.get_mut(&state.map_id)
+	                                .unwrap()
+	                                .npc_at(ship.bow_row, ship.bow_col)
+	                                .unwrap();
+	            let s = format!("Your ship hit a {}", npc.name);
+	            state.write_msg_buff(&s);
             
-            // The ship hit someone so try to bump them out of the way
-            match util::rnd_empty_adj(state, ships, ship.bow_row as i32, ship.bow_col as i32) {
-                Some(loc) => {
-                    let s = format!("The {} is shoved out of the way!", npc.name);
-                    state.write_msg_buff(&s);
-                    npc.row = loc.0;
-                    npc.col = loc.1;
-                    state.npcs.get_mut(&state.map_id).unwrap().update(npc, ship.bow_row, ship.bow_col);
-                },
-                None => { 
-                    let s = format!("The {} is crushed!", npc.name);
-                    state.write_msg_buff(&s);
-                    state.npcs.get_mut(&state.map_id).unwrap().remove(npc.id, npc.row, npc.col);
-                },
-            }
-        }
+	            // The ship hit someone so try to bump them out of the way
+	            match util::rnd_empty_adj(state, ships, ship.bow_row as i32, ship.bow_col as i32) {
+	                Some(loc) => {
+	                    let s = format!("The {} is shoved out of the way!", npc.name);
+	                    state.write_msg_buff(&s);
+	                    npc.row = loc.0;
+	                    npc.col = loc.1;
+	                    state.npcs.get_mut(&state.map_id).unwrap().update(npc, ship.bow_row, ship.bow_col);
+	                },
+	                None => { 
+	                    let s = format!("The {} is crushed!", npc.name);
+	                    state.write_msg_buff(&s);
+	                    if npc.leaves_corpse() {
+	                        state.add_corpse(npc.row, npc.col);
+	                    }
+	                    if npc.npc_type == actor::NPCType::UndeadCaptain {
+	                        undead_crew_collapses(state, npc.id);
+	                    }
+	                    state.player.score += npc.score;
+	                    state.record_kill(npc.npc_type.clone());
+	                    state.npcs.get_mut(&state.map_id).unwrap().remove(npc.id, npc.row, npc.col);
+	                },
+	            }
+	        }
+
+			for (mut npc, part) in crew {
+				npc.row = decks[part].0;
+				npc.col = decks[part].1;
+				state.npcs.get_mut(&state.map_id).unwrap().add(npc);
+			}
+		}
 	}
 
-	ships.insert((ship.row, ship.col), ship);
+	let new_loc = (ship.row, ship.col);
+	if ship.hull == 0 {
+		let s = format!("The {} founders and slips beneath the waves!", ship.name);
+		state.write_msg_buff(&s);
+		state.player.on_ship = false;
+	} else {
+		ships.insert(new_loc, ship);
+	}
 
-	Ok(())
+	Ok(new_loc)
 }
 
 fn toggle_anchor(state: &mut GameState, ships: &mut ShipsTable) -> bool {
+	let crewed = crew_aboard(state, ships) > 0;
 	let mut ship = ships.get_mut(&(state.player.row, state.player.col)).unwrap();
 	ship.anchored = !ship.anchored;
 
 	state.turn += 1;
+	// Hauling on the anchor cable is easier with another pair of hands
+	if !crewed {
+		state.player.exert(2);
+	}
 
 	if ship.anchored {
 		state.write_msg_buff("You lower the anchor.");
 		false
+	} else if crewed {
+		state.write_msg_buff("You and your crew haul up the anchor.");
+		true
 	} else {
 		state.write_msg_buff("You raise the anchor.");
 		true
@@ -1451,24 +4402,106 @@ fn turn_wheel(state: &mut GameState, ships: &mut ShipsTable, change: i8) {
 
 	state.turn += 1;
 	if change < 0 && ship.wheel == -2 {
-		state.write_msg_buff("The wheel's as far to starboard as she'll turn");
+		state.write_msg_buff("The wheel's as far to port as she'll turn");
 		return;
 	} else if change > 0 && ship.wheel == 2 {
-		state.write_msg_buff("The wheel's as far to port as she'll turn");
+		state.write_msg_buff("The wheel's as far to starboard as she'll turn");
 		return;
 	}
 
 	ship.wheel += change;
 	state.player.wheel = ship.wheel;
 
-	if ship.wheel > -2 && ship.wheel < 2 {
-		state.write_msg_buff("You adjust the tiller.");
-	} else {
-		state.write_msg_buff("Hard about!");
+	if ship.wheel > -2 && ship.wheel < 2 {
+		state.write_msg_buff("You adjust the tiller.");
+	} else {
+		state.write_msg_buff("Hard about!");
+		state.player.exert(1);
+	}
+}
+
+// The ship the player is standing on, if one of their crew has her wheel
+fn crewed_helm(state: &GameState, ships: &ShipsTable) -> Option<(usize, usize)> {
+	if state.player.on_ship {
+		return None;
+	}
+
+	let npcs = &state.npcs[&state.map_id];
+	ships.iter()
+		.find(|(loc, ship)| ship.occupies(state.player.row, state.player.col) 
+			&& npcs.is_follower_at(loc.0, loc.1))
+		.map(|(loc, _)| *loc)
+}
+
+// Leave a crewmate at the wheel holding her present heading, so the player is
+// free to move about the deck while she sails on
+fn hand_over_helm(state: &mut GameState, ships: &mut ShipsTable) {
+	let player_loc = (state.player.row, state.player.col);
+	let ship = ships.get_mut(&player_loc).unwrap();
+	if ship.raft {
+		state.write_msg_buff("There's no wheel to hand over on a raft.");
+		return;
+	}
+
+	let decks = [((ship.bow_row, ship.bow_col), "forward"), ((ship.aft_row, ship.aft_col), "aft")];
+	let npcs = state.npcs.get_mut(&state.map_id).unwrap();
+	let deck = decks.iter().find(|(loc, _)| npcs.is_follower_at(loc.0, loc.1));
+	let (loc, dir) = match deck {
+		Some(d) => *d,
+		None => {
+			state.write_msg_buff("You've no crew aboard to take the wheel.");
+			return;
+		},
+	};
+
+	let mut npc = npcs.npc_at(loc.0, loc.1).unwrap();
+	npc.row = player_loc.0;
+	npc.col = player_loc.1;
+	let s = format!("You hand the wheel to the {} and head {}.", npc.name, dir);
+	npcs.update(npc, loc.0, loc.1);
+
+	ship.wheel = 0;
+	state.player.wheel = 0;
+	state.player.on_ship = false;
+	state.player.row = loc.0;
+	state.player.col = loc.1;
+	state.write_msg_buff(&s);
+	state.turn += 1;
+}
+
+// A crewmate left at the wheel keeps the ship sailing while the player is
+// busy elsewhere on deck
+fn helmsman_sails(state: &mut GameState, ships: &mut ShipsTable) -> Result<(), ExitReason> {
+	if let Some(loc) = crewed_helm(state, ships) {
+		if !ships[&loc].anchored {
+			sail_ship(state, ships, loc)?;
+		}
 	}
+
+	Ok(())
 }
 
 fn take_helm(state: &mut GameState, ships: &ShipsTable) {
+	if let Some(loc) = crewed_helm(state, ships) {
+		let player_loc = (state.player.row, state.player.col);
+		let npcs = state.npcs.get_mut(&state.map_id).unwrap();
+		let mut npc = npcs.npc_at(loc.0, loc.1).unwrap();
+		npc.row = player_loc.0;
+		npc.col = player_loc.1;
+		let s = format!("You take the wheel back from the {}.", npc.name);
+		npcs.update(npc, loc.0, loc.1);
+
+		let ship = ships.get(&loc).unwrap();
+		state.player.on_ship = true;
+		state.player.row = loc.0;
+		state.player.col = loc.1;
+		state.player.bearing = ship.bearing;
+		state.player.wheel = ship.wheel;
+		state.write_msg_buff(&s);
+		state.turn += 1;
+		return;
+	}
+
 	let player_loc = (state.player.row, state.player.col);
 	if !ships.contains_key(&player_loc) {
 		state.write_msg_buff("You need to find yerself a ship before you can take the helm.");
@@ -1486,6 +4519,46 @@ fn take_helm(state: &mut GameState, ships: &ShipsTable) {
 	state.turn += 1;
 }
 
+// Hop straight from the wheel onto a neighbouring bit of land without going 
+// through leaving the helm and walking off the deck. The anchor goes down
+// first so the ship doesn't drift off without her captain.
+fn step_ashore(state: &mut GameState, ships: &mut ShipsTable, gui: &mut GameUI) {
+	let sbi = state.curr_sidebar_info();
+	let dir = match gui.pick_direction("Step off which way?", &sbi) {
+		Some(dir) => dir,
+		None => { state.write_msg_buff("Nevermind."); return; },
+	};
+
+	let r = state.player.row as i32 + dir.0;
+	let c = state.player.col as i32 + dir.1;
+	if !map::in_bounds(&state.map[&state.map_id], r, c) {
+		state.write_msg_buff("There's no dry land that way.");
+		return;
+	}
+	let (r, c) = (r as usize, c as usize);
+	let tile = &state.map[&state.map_id][r][c];
+	if !map::is_land(tile) || !map::is_passable(tile) {
+		state.write_msg_buff("There's no dry land that way.");
+		return;
+	} else if !sq_is_open(state, ships, r, c) {
+		state.write_msg_buff("Something's in the way.");
+		return;
+	}
+
+	let ship = ships.get_mut(&(state.player.row, state.player.col)).unwrap();
+	if !ship.anchored {
+		ship.anchored = true;
+		state.write_msg_buff("You drop anchor and step ashore.");
+	} else {
+		state.write_msg_buff("You step ashore.");
+	}
+
+	state.player.on_ship = false;
+	state.player.row = r;
+	state.player.col = c;
+	state.turn += 1;
+}
+
 fn leave_helm(state: &mut GameState) {
 	state.player.on_ship = false;
 	state.write_msg_buff("You step to gunwale.");
@@ -1515,11 +4588,29 @@ fn title_screen(gui: &mut GameUI) {
 	gui.write_long_msg(&lines, true);
 }
 
-fn confirm_quit(state: &GameState, gui: &mut GameUI) -> Result<(), ExitReason> {
+// Quitting throws the run away for good, which is easy to do by accident when
+// S (save and exit) is right there, so the prompt says as much and offers to 
+// save instead.
+fn confirm_quit(state: &mut GameState, items: &HashMap<u8, ItemsTable>, 
+			ships: &HashMap<u8, ShipsTable>, gui: &mut GameUI) -> Result<(), ExitReason> {
 	let sbi = state.curr_sidebar_info();
-	match gui.query_yes_no("Do you really want to Quit? (y/n)", &sbi) {
-		'y' => Err(ExitReason::Quit),
-		_ => Ok(()),
+	if state.won {
+		return match gui.query_yes_no("Retire from piracy and live off yer fortune? (y/n)", &sbi) {
+			'y' => Err(ExitReason::Win),
+			_ => Ok(()),
+		};
+	}
+
+	loop {
+		match gui.query_single_response("Abandon yer quest for good? (y/n, or s to save)", &sbi) {
+			Some('y') => return Err(ExitReason::Quit),
+			Some('s') => {
+				save_game(state, items, ships, gui);
+				return Err(ExitReason::Save);
+			},
+			Some('n') | None => return Ok(()),
+			Some(_) => continue,
+		}
 	}
 }
 
@@ -1532,8 +4623,8 @@ fn is_putting_on_airs(name: &str) -> bool {
 fn preamble(gui: &mut GameUI) -> (GameState, HashMap<u8, ItemsTable>, HashMap<u8, ShipsTable>, bool) {
 	let mut player_name: String;
 
-	let sbi = SidebarInfo::new("".to_string(), 0, 0, 0, -1, -1, 0, false, false, 0, String::from(""), 
-			String::from(""));
+	let sbi = SidebarInfo::new("".to_string(), 0, 0, 0, None, 0, false, false, 0, 0, String::from(""), 
+			String::from(""), None);
 	loop {
 		if let Some(name) = gui.query_user("Ahoy lubber, who be ye?", 15, &sbi) {
 			if name.len() > 0 {
@@ -1549,11 +4640,22 @@ fn preamble(gui: &mut GameUI) -> (GameState, HashMap<u8, ItemsTable>, HashMap<u8
 		}
 	}
 
-	if existing_save_file(&player_name) {
+	if autosave_is_newer(&player_name)
+			&& gui.query_yes_no("Found an autosave newer than yer last save. Resume from it? (y/n)", &sbi) == 'y' {
+		match load_existing_game(&gen_autosave_filename(&player_name)) {
+			Ok(gd) => { return gd; },
+			Err(_) => {
+				let v = vec![String::from("Oh no! The autosave appears to be damaged and unreadable :(")];
+				gui.write_long_msg(&v, false);
+			},
+		}
+	}
+
+	if existing_save_file(&gen_save_filename(&player_name)) {
 		let v = vec![String::from("Found save file. Welcome back, swab!")];
 		gui.write_long_msg(&v, false);
 
-		match load_existing_game(&player_name){
+		match load_existing_game(&gen_save_filename(&player_name)) {
 			Ok(gd) => { return gd; },
 			Err(_) => {
 				let v = vec![String::from("Oh no! The save file appears to be damaged and unreadable :(")];
@@ -1581,7 +4683,7 @@ fn preamble(gui: &mut GameUI) -> (GameState, HashMap<u8, ItemsTable>, HashMap<u8
 	let ships: HashMap<u8, ShipsTable> = HashMap::new();
 	let mut items = HashMap::new();
 	items.insert(0, ItemsTable::new());
-	let state: GameState;
+	let mut state: GameState;
 
 	let answer = gui.menu_picker(&menu, 2, true, true).unwrap();
 	if answer.contains(&0) {
@@ -1590,6 +4692,17 @@ fn preamble(gui: &mut GameUI) -> (GameState, HashMap<u8, ItemsTable>, HashMap<u8
 		state = GameState::new_pirate(player_name, PirateType::Seadog);
 	}
 
+	let mut menu = Vec::new();
+	menu.push("And what did ye do before ye washed up here?".to_string());
+	menu.push("".to_string());
+	for (j, bg) in BACKGROUNDS.iter().enumerate() {
+		menu.push(format!("  ({}) {}: {}", (b'a' + j as u8) as char, bg.name, bg.blurb));
+	}
+
+	let answer = gui.menu_picker(&menu, BACKGROUNDS.len() as u8, true, true).unwrap();
+	let choice = *answer.iter().next().unwrap() as usize;
+	state.player.outfit(&BACKGROUNDS[choice]);
+
 	(state, items, ships, true)
 }
 
@@ -1605,9 +4718,42 @@ fn gen_save_filename(player_name: &str) -> String {
 	format!("{}.yaml", s)
 }
 
-fn load_existing_game(player_name: &str) -> Result<(GameState, HashMap<u8, 
+fn gen_autosave_filename(player_name: &str) -> String {
+	let filename = gen_save_filename(player_name);
+	format!("{}.autosave.yaml", filename.trim_end_matches(".yaml"))
+}
+
+// Debugging aid: write out the current level's tiles, plus the monsters and
+// items on it, so a map that generated strangely can be attached to a bug
+// report.
+fn dump_map(state: &mut GameState, items: &ItemsTable) {
+	let filename = format!("{}.map{}.txt", 
+		gen_save_filename(&state.player.name).trim_end_matches(".yaml"), state.map_id);
+
+	let mut dump = format!("Map {} on turn {}, player at ({}, {})\n\n", 
+		state.map_id, state.turn, state.player.row, state.player.col);
+	for row in state.map[&state.map_id].iter() {
+		let line: String = row.iter()
+			.map(|tile| GameUI::sq_info_for_tile(tile).0)
+			.collect();
+		dump.push_str(&line);
+		dump.push('\n');
+	}
+
+	dump.push_str("\nMonsters:\n");
+	dump.push_str(&serde_yaml::to_string(&state.npcs[&state.map_id]).unwrap());
+	dump.push_str("\n\nItems:\n");
+	dump.push_str(&serde_yaml::to_string(items).unwrap());
+	dump.push('\n');
+
+	match fs::write(&filename, dump) {
+		Ok(_) => state.write_msg_buff(&format!("Map written to {}.", filename)),
+		Err(_) => state.write_msg_buff("Unable to write the map dump."),
+	}
+}
+
+fn load_existing_game(filename: &str) -> Result<(GameState, HashMap<u8, 
 			ItemsTable>, HashMap<u8, ShipsTable>, bool), serde_yaml::Error> {
-	let filename = gen_save_filename(&player_name);
 	let blob = fs::read_to_string(filename).expect("Error reading save file");
 	let game_data: (GameState, HashMap<u8, ItemsTable>, 
 			HashMap<u8, ShipsTable>) = serde_yaml::from_str(&blob)?;
@@ -1615,9 +4761,7 @@ fn load_existing_game(player_name: &str) -> Result<(GameState, HashMap<u8,
 	Ok((game_data.0, game_data.1, game_data.2, false))
 }
 
-fn existing_save_file(player_name: &str) -> bool {
-	let save_filename = gen_save_filename(player_name);
-
+fn existing_save_file(save_filename: &str) -> bool {
 	let paths = fs::read_dir("./").unwrap();
 	for path in paths {
 		if save_filename == path.unwrap().path().file_name().unwrap().to_str().unwrap() {
@@ -1628,10 +4772,36 @@ fn existing_save_file(player_name: &str) -> bool {
 	false
 }
 
+// An autosave is only worth offering if it was written after the player's
+// last manual save (or there isn't a manual save at all)
+fn autosave_is_newer(player_name: &str) -> bool {
+	let autosave_filename = gen_autosave_filename(player_name);
+	if !existing_save_file(&autosave_filename) {
+		return false;
+	}
+
+	let autosave_time = fs::metadata(&autosave_filename).and_then(|m| m.modified());
+	let save_time = fs::metadata(gen_save_filename(player_name)).and_then(|m| m.modified());
+
+	match (autosave_time, save_time) {
+		(Ok(a), Ok(s)) => a > s,
+		(Ok(_), Err(_)) => true,
+		_ => false,
+	}
+}
+
+// This is a permadeath game so once the pirate is dead (or has given up) we
+// don't want an autosave lying around to resurrect them
+fn delete_autosave(player_name: &str) {
+	let autosave_filename = gen_autosave_filename(player_name);
+	if existing_save_file(&autosave_filename) {
+		let _ = fs::remove_file(autosave_filename);
+	}
+}
+
 fn serialize_game_data(state: &mut GameState, 
 			items: &HashMap<u8, ItemsTable>, 
-			ships: &HashMap<u8, ShipsTable>, _gui: &mut GameUI) {
-	let filename = gen_save_filename(&state.player.name);
+			ships: &HashMap<u8, ShipsTable>, filename: &str, _gui: &mut GameUI) {
 	let game_data = (state, items, ships);
 
 	let serialized = serde_yaml::to_string(&game_data).unwrap();
@@ -1652,13 +4822,25 @@ fn save_and_exit(state: &mut GameState, items: &HashMap<u8, ItemsTable>,
 	let sbi = state.curr_sidebar_info();
 	match gui.query_yes_no("Save and exit? (y/n)", &sbi) {
 		'y' => { 
-				serialize_game_data(state, items, ships, gui); 
+				save_game(state, items, ships, gui);
 				Err(ExitReason::Save)
 		},
 		_ => Ok(())
 	}
 }
 
+fn save_game(state: &mut GameState, items: &HashMap<u8, ItemsTable>, 
+			ships: &HashMap<u8, ShipsTable>, gui: &mut GameUI) {
+	let filename = gen_save_filename(&state.player.name);
+	serialize_game_data(state, items, ships, &filename, gui); 
+}
+
+fn autosave(state: &mut GameState, items: &HashMap<u8, ItemsTable>, 
+			ships: &HashMap<u8, ShipsTable>, gui: &mut GameUI) {
+	let filename = gen_autosave_filename(&state.player.name);
+	serialize_game_data(state, items, ships, &filename, gui);
+}
+
 fn save_msg(state: &mut GameState, gui: &mut GameUI) {
 	let sbi = state.curr_sidebar_info();
 	state.write_msg_buff("See you soon, mate! --More--");
@@ -1687,6 +4869,62 @@ fn quit_msg(state: &mut GameState, gui: &mut GameUI) {
 	gui.write_long_msg(&lines, true);
 }
 
+fn pirate_rank(total: u32) -> &'static str {
+	if total < 50 {
+		"Landlubber"
+	} else if total < 150 {
+		"Deckhand"
+	} else if total < 300 {
+		"Buccaneer"
+	} else if total < 600 {
+		"Sea Wolf"
+	} else {
+		"Dread Pirate"
+	}
+}
+
+fn score_breakdown(state: &GameState, victory: bool) -> Vec<String> {
+	let mut lines = Vec::new();
+	let mut total = state.player.score as u32;
+
+	lines.push(String::from("Monsters slain:"));
+	if state.kills.is_empty() {
+		lines.push(String::from("    None. Not a drop of blood spilled."));
+	} else {
+		let mut kills: Vec<(String, u16)> = state.kills.iter()
+			.map(|(t, n)| (t.bestiary_entry().1.to_string(), *n))
+			.collect();
+		kills.sort();
+		for (name, n) in kills {
+			lines.push(format!("    {:<20}{}", name, n));
+		}
+	}
+	lines.push(format!("  Points for kills: {}", state.player.score));
+
+	let doubloons = match state.player.inventory.count_of_item("doubloon") {
+		Some((n, _)) => n as u32,
+		None => 0,
+	};
+	total += doubloons;
+	lines.push(format!("  Doubloons carried: {}", doubloons));
+
+	let islands = state.islands_discovered() as u32;
+	total += islands * 25;
+	lines.push(format!("  Islands discovered: {} ({} points)", islands, islands * 25));
+
+	lines.push(format!("  Turns taken: {}", state.turn));
+
+	if victory {
+		total += 500;
+		lines.push(String::from("  Found the treasure: 500 points"));
+	}
+
+	lines.push(String::from(""));
+	lines.push(format!("Final score: {}, earning ye the rank of {}.", total, pirate_rank(total)));
+
+	lines
+}
+
 fn victory_msg(state: &mut GameState, gui: &mut GameUI) {
 	let sbi = state.curr_sidebar_info();
 	state.write_msg_buff("Game over! --More--");
@@ -1698,9 +4936,15 @@ fn victory_msg(state: &mut GameState, gui: &mut GameUI) {
 	let s = format!("{}! Yer fame, and fortune, are assured and pirates will be", state.pirate_lord);
 	lines.push(s);
 	lines.push(String::from("talling tales of your exploits for years to come!"));
+	if state.navy_alerted {
+		lines.push(String::from("And ye gave the Yendorian Navy the slip, to boot!"));
+	}
 	lines.push(String::from(""));
 	let s = format!("Congratulations, Captain {}!", state.player.name);
 	lines.push(s);
+	lines.push(String::from(""));
+	lines.extend(score_breakdown(state, true));
+	lines.push(String::from(""));
 
 	let s = format!("So long, mate!");
 	lines.push(s);
@@ -1730,6 +4974,8 @@ fn death(state: &mut GameState, src: String, gui: &mut GameUI) {
 		lines.push(String::from("what gets you, it be the landing..."));
 	} else if src == "bboulder" {
 		lines.push(String::from("Crushed by a boulder!"));
+	} else if src == "thirst" {
+		lines.push(String::from("Ye died of thirst, with water water everywhere!"));
 	} else {
 		let s = format!("Killed by a {}!", src);
 		lines.push(s);
@@ -1739,6 +4985,8 @@ fn death(state: &mut GameState, src: String, gui: &mut GameUI) {
 	let s = format!("{}'s treasure remains for some other swab...", state.pirate_lord);
 	lines.push(s);
 	lines.push(String::from(""));
+	lines.extend(score_breakdown(state, false));
+	lines.push(String::from(""));
 	
 	let s = format!("So long, mate!");
 	lines.push(s);
@@ -1753,14 +5001,15 @@ fn check_drifting_ships(state: &mut GameState, ships: &mut ShipsTable) {
 	let curr_map = &state.map[&state.map_id];
 	for sl in ship_loc {
 		let mut ship = ships.remove(&sl).unwrap();
-		if ship.row != state.player.row && ship.col != state.player.col && !ship.anchored {
+		if !ship.occupies(state.player.row, state.player.col) && !ship.anchored && !ship.naval {
 			let mut adj = Vec::new();
 			for r in -1..=1 {
 				for c in -1..=1 {
 					if r == 0 && c == 0 { continue; }
 					let adj_r = (sl.0 as i32 + r) as usize;
 					let adj_c = (sl.1 as i32 + c) as usize;
-					if curr_map[adj_r][adj_c] != Tile::Water && curr_map[adj_r][adj_c] != Tile::DeepWater {
+					if curr_map[adj_r][adj_c] != Tile::Water && curr_map[adj_r][adj_c] != Tile::DeepWater
+							&& curr_map[adj_r][adj_c] != Tile::Reef {
 						continue;
 					}
 					if sq_is_open(state, ships, adj_r, adj_c) {
@@ -1783,15 +5032,407 @@ fn check_drifting_ships(state: &mut GameState, ships: &mut ShipsTable) {
 	}
 }
 
+// In the escape ending, the treasure's only won once the player sails it off
+// the north or west edge of the map, the way they came into the strait
+fn escaped_strait(state: &GameState, bow: (usize, usize)) -> bool {
+	state.navy_alerted && state.map_id == 0 
+		&& state.map[&0][bow.0][bow.1] == Tile::WorldEdge
+		&& (bow.0 == 0 || bow.1 == 0)
+		&& !state.player.inventory.items_of_type(ItemType::MacGuffin).is_empty()
+}
+
+// Once the Navy knows the player has the treasure, warships turn up and give
+// chase, closing in one square a turn and firing on the player when in range
+fn check_naval_ships(state: &mut GameState, ships: &mut ShipsTable) -> Result<(), ExitReason> {
+	if !state.navy_alerted || state.map_id != 0 {
+		return Ok(());
+	}
+
+	let pursuers = ships.values().filter(|s| s.naval).count();
+	if pursuers < MAX_PURSUERS && rand::thread_rng().gen_range(0.0, 1.0) < 0.05 {
+		if let Some(ship) = ship_arrives(state, ships, 25, 15, content_factory::new_naval_ship) {
+			let s = format!("The {} is bearing down on ye!", ship.name);
+			state.write_msg_buff(&s);
+			ships.insert((ship.row, ship.col), ship);
+		}
+	}
+
+	let locs = ships.iter()
+				.filter(|(_, s)| s.naval)
+				.map(|(loc, _)| *loc)
+				.collect::<Vec<(usize, usize)>>();
+	for loc in locs {
+		let mut ship = ships.remove(&loc).unwrap();
+		let d = util::cartesian_d(ship.row, ship.col, state.player.row, state.player.col);
+
+		if d <= 4 && rand::thread_rng().gen_range(0.0, 1.0) < 0.33 {
+			fire_broadside(state, &ship, ships)?;
+		}
+
+		if d > 2 {
+			navy_closes_in(state, &mut ship, ships);
+		}
+
+		ships.insert((ship.row, ship.col), ship);
+	}
+
+	Ok(())
+}
+
+fn navy_closes_in(state: &GameState, ship: &mut Ship, ships: &ShipsTable) {
+	let dr = (state.player.row as i32 - ship.row as i32).signum();
+	let dc = (state.player.col as i32 - ship.col as i32).signum();
+	ship_steps(state, ship, ships, dr, dc);
+}
+
+fn fire_broadside(state: &mut GameState, ship: &Ship, ships: &mut ShipsTable) -> Result<(), ExitReason> {
+	let s = format!("The {} fires a broadside!", ship.name);
+	state.write_msg_buff(&s);
+
+	let player_loc = (state.player.row, state.player.col);
+	if state.player.on_ship && ships.contains_key(&player_loc) {
+		let mut target = ships.remove(&player_loc).unwrap();
+		let dmg = dice::roll(4, 1, 0);
+		target.hull = target.hull.saturating_sub(dmg);
+		if target.hull == 0 {
+			let s = format!("The {} founders and slips beneath the waves!", target.name);
+			state.write_msg_buff(&s);
+			state.player.on_ship = false;
+		} else {
+			state.write_msg_buff("Cannonballs splinter yer timbers!");
+			ships.insert(player_loc, target);
+		}
+	} else if rand::thread_rng().gen_range(0.0, 1.0) < 0.5 {
+		state.write_msg_buff("Ye're raked by grapeshot!");
+		player_takes_dmg(&mut state.player, dice::roll(6, 1, 0), "grapeshot")?;
+	} else {
+		state.write_msg_buff("The shot whistles overhead.");
+	}
+
+	Ok(())
+}
+
+// Now and then a merchant ship crosses the player's path while they're at 
+// sea. Once she's drifted well out of sight she's gone for good and another
+// may turn up later.
+fn check_merchant_ships(state: &mut GameState, ships: &mut ShipsTable) {
+	if state.map_id != 0 {
+		return;
+	}
+
+	let (pr, pc) = (state.player.row, state.player.col);
+	ships.retain(|_, s| !s.merchant || util::cartesian_d(s.row, s.col, pr, pc) < 60);
+	merchants_flee(state, ships);
+
+	if !state.player.on_ship || ships.values().any(|s| s.merchant) 
+			|| rand::thread_rng().gen_range(0.0, 1.0) > 0.005 {
+		return;
+	}
+
+	if let Some(ship) = ship_arrives(state, ships, 20, 10, content_factory::new_merchant_ship) {
+		state.write_msg_buff("Ye spy sails on the horizon!");
+		ships.insert((ship.row, ship.col), ship);
+	}
+}
+
+// Find a clear stretch of deep water for a ship sailing in from somewhere
+// within max_d squares of the player, but no nearer than min_d
+fn ship_arrives(state: &GameState, ships: &ShipsTable, max_d: i32, min_d: usize,
+		new_ship: fn(usize, usize) -> Ship) -> Option<Ship> {
+	let (pr, pc) = (state.player.row, state.player.col);
+	let curr_map = &state.map[&0];
+	for _ in 0..20 {
+		let r = pr as i32 + rand::thread_rng().gen_range(-max_d, max_d + 1);
+		let c = pc as i32 + rand::thread_rng().gen_range(-max_d, max_d + 1);
+		if r < 2 || c < 2 || r >= curr_map.len() as i32 - 2 || c >= curr_map[0].len() as i32 - 2 {
+			continue;
+		}
+		if util::cartesian_d(r as usize, c as usize, pr, pc) < min_d {
+			continue;
+		}
+
+		let ship = new_ship(r as usize, c as usize);
+		let squares = [(ship.row, ship.col), (ship.bow_row, ship.bow_col), (ship.aft_row, ship.aft_col)];
+		if squares.iter().all(|sq| curr_map[sq.0][sq.1] == Tile::DeepWater 
+				&& sq_is_open(state, ships, sq.0, sq.1)) {
+			return Some(ship);
+		}
+	}
+
+	None
+}
+
+// Traders want no part of a warship, so a merchant with one of the Navy's 
+// ships nearby makes off in the other direction
+fn merchants_flee(state: &GameState, ships: &mut ShipsTable) {
+	let threats = ships.values()
+				.filter(|s| s.naval)
+				.map(|s| (s.row, s.col))
+				.collect::<Vec<(usize, usize)>>();
+	if threats.is_empty() {
+		return;
+	}
+
+	let locs = ships.iter()
+				.filter(|(_, s)| s.merchant)
+				.map(|(loc, _)| *loc)
+				.collect::<Vec<(usize, usize)>>();
+	for loc in locs {
+		let nearest = threats.iter()
+			.min_by_key(|t| util::cartesian_d(t.0, t.1, loc.0, loc.1))
+			.unwrap();
+		if util::cartesian_d(nearest.0, nearest.1, loc.0, loc.1) > MERCHANT_WARY_DIST {
+			continue;
+		}
+
+		let mut ship = ships.remove(&loc).unwrap();
+		let dr = (loc.0 as i32 - nearest.0 as i32).signum();
+		let dc = (loc.1 as i32 - nearest.1 as i32).signum();
+		ship_steps(state, &mut ship, ships, dr, dc);
+		ships.insert((ship.row, ship.col), ship);
+	}
+}
+
+// Move a ship one square in the direction given, turning her to face that
+// way. If she'd run aground or into something she stays put.
+fn ship_steps(state: &GameState, ship: &mut Ship, ships: &ShipsTable, dr: i32, dc: i32) {
+	let bearing = match (dr, dc) {
+		(-1, 0) => 0,
+		(-1, 1) => 2,
+		(0, 1) => 4,
+		(1, 1) => 6,
+		(1, 0) => 8,
+		(1, -1) => 10,
+		(0, -1) => 12,
+		_ => 14,
+	};
+
+	let (start_r, start_c, start_bearing) = (ship.row, ship.col, ship.bearing);
+	let curr_map = &state.map[&state.map_id];
+	let nr = ship.row as i32 + dr;
+	let nc = ship.col as i32 + dc;
+	if nr < 1 || nc < 1 || nr >= curr_map.len() as i32 - 1 || nc >= curr_map[0].len() as i32 - 1 {
+		return;
+	}
+	ship.row = nr as usize;
+	ship.col = nc as usize;
+	ship.bearing = bearing;
+	ship.update_loc_info();
+
+	let squares = [(ship.row, ship.col), (ship.bow_row, ship.bow_col), (ship.aft_row, ship.aft_col)];
+	let clear = squares.iter().all(|sq| {
+		let tile = &curr_map[sq.0][sq.1];
+		(*tile == Tile::DeepWater || *tile == Tile::Water) && sq_is_open(state, ships, sq.0, sq.1)
+	});
+
+	if !clear {
+		ship.row = start_r;
+		ship.col = start_c;
+		ship.bearing = start_bearing;
+		ship.update_loc_info();
+	}
+}
+
+// Where something lies relative to the way the ship is pointed. bearing is in
+// the sixteen points of the compass and dir one of the eight from
+// dir_between_sqs()
+fn relative_to_bow(bearing: u8, dir: &str) -> &'static str {
+	let points = match dir {
+		"N" => 0,
+		"NE" => 2,
+		"E" => 4,
+		"SE" => 6,
+		"S" => 8,
+		"SW" => 10,
+		"W" => 12,
+		_ => 14,
+	};
+
+	match (points - bearing as i8).rem_euclid(16) {
+		0 => "Dead ahead",
+		1..=3 => "Off the starboard bow",
+		4 => "To starboard",
+		5..=7 => "Off the starboard quarter",
+		8 => "Astern",
+		9..=11 => "Off the port quarter",
+		12 => "To port",
+		_ => "Off the port bow",
+	}
+}
+
+fn compass_name(dir: &str) -> &str {
+	match dir {
+		"N" => "the north",
+		"NE" => "the northeast",
+		"E" => "the east",
+		"SE" => "the southeast",
+		"S" => "the south",
+		"SW" => "the southwest",
+		"W" => "the west",
+		_ => "the northwest",
+	}
+}
+
+// Give the player a heads up the first time a hostile monster that has spotted 
+// them comes into view, while there is still a little time to do something 
+// about it.
+fn announce_approaching(state: &mut GameState, gui: &GameUI) {
+	let half_height = (FOV_HEIGHT / 2) as i32;
+	let half_width = (FOV_WIDTH / 2) as i32;
+
+	for j in 0..gui.v_matrix.len() {
+		if let Tile::Creature(_, _) = gui.v_matrix[j] {
+			let r = state.player.row as i32 + (j / FOV_WIDTH) as i32 - half_height;
+			let c = state.player.col as i32 + (j % FOV_WIDTH) as i32 - half_width;
+			let npc = match state.npcs.get_mut(&state.map_id).unwrap().npc_at(r as usize, c as usize) {
+				Some(npc) => npc,
+				None => continue,
+			};
+
+			if !npc.hostile || !npc.aware_of_player || state.announced.contains(&(state.map_id, npc.id)) {
+				continue;
+			}
+			if util::cartesian_d(npc.row, npc.col, state.player.row, state.player.col) < 3 {
+				continue;
+			}
+
+			let article = match npc.name.chars().next() {
+				Some('a') | Some('e') | Some('i') | Some('o') | Some('u') => "An",
+				_ => "A",
+			};
+			let dir = util::dir_between_sqs(state.player.row, state.player.col, npc.row, npc.col);
+			let s = format!("{} {} {} toward you from {}!", article, npc.name, 
+				npc.npc_type.approach_verb(), compass_name(&dir));
+			state.write_msg_buff(&s);
+			state.announced.insert((state.map_id, npc.id));
+		}
+	}
+}
+
 fn attack_player(state: &mut GameState, npc: &Monster) -> bool {
-	do_ability_check(npc.hit_bonus, state.player.ac, 0)
+	if !do_ability_check(npc.hit_bonus, state.player.ac, 0) {
+		return false;
+	}
+
+	// A pirate with a blade in hand can turn aside a blow that would otherwise
+	// land, and now and then leave the attacker open to a counter
+	let adjacent = util::cartesian_d(npc.row, npc.col, state.player.row, state.player.col) < 2;
+	if adjacent && state.player.inventory.get_equiped_weapon().is_some() {
+		let dex_mod = Player::mod_for_stat(state.player.dexterity);
+		if do_ability_check(dex_mod, PARRY_DC, state.player.prof_bonus as i8) {
+			let s = format!("You parry the {}'s attack!", npc.name);
+			state.write_msg_buff(&s);
+			if state.riposte.is_none() && rand::thread_rng().gen_range(0.0, 1.0) < 0.5 {
+				state.riposte = Some((npc.row, npc.col));
+			}
+			return false;
+		}
+	}
+
+	true
 }
 
-fn show_help(gui: &mut GameUI) {
+// A few tips aimed at what the player is doing right now, shown ahead of the 
+// full command reference. Steering the ship is what trips up new players the
+// most so that gets top billing when they're at the helm.
+fn context_help(state: &mut GameState, ships: &ShipsTable) -> Vec<String> {
 	let mut lines = Vec::new();
+	let player_loc = (state.player.row, state.player.col);
+
+	if state.player.on_ship {
+		lines.push(String::from("~At the helm~"));
+		lines.push(String::from(""));
+		lines.push(String::from("  A - raise or lower the anchor (ye won't go anywhere anchored!)"));
+		lines.push(String::from("  h - turn the wheel anticlockwise (to port)"));
+		lines.push(String::from("  j - turn the wheel clockwise (to starboard)"));
+		lines.push(String::from("  . - hold your course and let the ship sail"));
+		lines.push(String::from("  B - step away from the wheel"));
+		lines.push(String::from("  x - drop anchor and step straight onto dry land"));
+		lines.push(String::from("  k - kedge her off if she's run aground"));
+		lines.push(String::from("  o - scan the horizon for land you haven't sighted yet"));
+		lines.push(String::from("  c - hand the wheel to a crewmate, who'll hold her course"));
+		lines.push(String::from("  H - stow or fetch things from the ship's hold"));
+		lines.push(String::from(""));
+		lines.push(String::from("  The ship keeps turning as long as the wheel is over, so"));
+		lines.push(String::from("  straighten it out once you're on your new bearing. Watch"));
+		lines.push(String::from("  for shallows and reefs: they'll beach or hole your ship."));
+		lines.push(String::from("  Mind the wind, too: beating into it is slow going but"));
+		lines.push(String::from("  with it at your back she'll fairly fly."));
+	} else {
+		lines.push(String::from("~On foot~"));
+		lines.push(String::from(""));
+		if crewed_helm(state, ships).is_some() {
+			lines.push(String::from("  B - take the wheel back from your helmsman"));
+		} else if ships.contains_key(&player_loc) {
+			lines.push(String::from("  B - take the ship's wheel"));
+			lines.push(String::from("  H - stow or fetch things from the ship's hold"));
+		}
+		if state.map[&state.map_id][player_loc.0][player_loc.1] == Tile::DeepWater
+				&& !ships.contains_key(&player_loc)
+				&& !state.player.inventory.equiped_flotation() {
+			lines.push(String::from("  Swimming is exhausting! Get to land or a ship quickly."));
+		}
+		if state.player.inventory.get_equiped_firearm().is_some() {
+			lines.push(String::from("  f - fire your gun, v - shoot the nearest foe, r - reload it"));
+		}
+		match state.map[&state.map_id][player_loc.0][player_loc.1] {
+			Tile::Shipwreck(_, _) | Tile::Mast(_) | Tile::ShipPart(_) =>
+				lines.push(String::from("  Z - salvage planks from the wreckage")),
+			_ => { },
+		}
+		lines.push(String::from("  , - pick up, d - drop, s - search, e - enter a doorway"));
+	}
+
+	let mut hostile = false;
+	let mut merchant = false;
+	for r in -1..=1 {
+		for c in -1..=1 {
+			if r == 0 && c == 0 { continue; }
+			let adj_r = (player_loc.0 as i32 + r) as usize;
+			let adj_c = (player_loc.1 as i32 + c) as usize;
+			if let Some(npc) = state.npcs.get_mut(&state.map_id).unwrap().npc_at(adj_r, adj_c) {
+				if npc.hostile {
+					hostile = true;
+				} else if npc.is_merchant() {
+					merchant = true;
+				}
+			}
+		}
+	}
+
+	if hostile {
+		lines.push(String::from(""));
+		lines.push(String::from("~In a fight~"));
+		lines.push(String::from("  Move into an enemy to attack it. q - quaff rum to recover"));
+		lines.push(String::from("  stamina, E - eat something, W - ready your best gear."));
+	}
+	if merchant {
+		lines.push(String::from(""));
+		lines.push(String::from("~Trading~"));
+		lines.push(String::from("  C - parley with someone. Some will have goods to sell."));
+	}
+
+	lines.push(String::from(""));
+
+	lines
+}
+
+// Used if help.txt has gone missing
+const DEFAULT_HELP: &str = "~Movement in YarrL~
+
+When you are not steering your ship, move with the vi-keys:
+
+             y k u
+             h @ l
+             b j n
+
+(The full help file, help.txt, couldn't be found.)";
+
+fn show_help(state: &mut GameState, ships: &ShipsTable, gui: &mut GameUI) {
+	let mut lines = context_help(state, ships);
 
 	let contents = fs::read_to_string("help.txt")
-        .expect("Unable to find help file!"); 	
+		.unwrap_or_else(|_| String::from(DEFAULT_HELP));
 
 	for line in contents.split('\n') {
 		lines.push(String::from(line));
@@ -1839,15 +5480,31 @@ fn prologue(state: &GameState, gui: &mut GameUI) {
 }
 
 fn start_game() {
+	let config = Config::load();
+
     let ttf_context = sdl2::ttf::init()
 		.expect("Error creating ttf context on start-up!");
 	let font_path: &Path = Path::new("DejaVuSansMono.ttf");
-    let font = ttf_context.load_font(font_path, 24)
-		.expect("Error loading game font!");
-	let sm_font = ttf_context.load_font(font_path, 18)
-		.expect("Error loading small game font!");
-	let mut gui = GameUI::init(&font, &sm_font)
+	let mut fonts = Vec::new();
+	for (size, sm_size) in FONT_SCALES.iter() {
+		match (ttf_context.load_font(font_path, *size), ttf_context.load_font(font_path, *sm_size)) {
+			(Ok(font), Ok(sm_font)) => fonts.push((font, sm_font)),
+			_ => {
+				// No font means no way to draw our own screens, so fall back on
+				// the OS to tell the player what's wrong.
+				let msg = "Unable to load DejaVuSansMono.ttf. It should be in the same directory YarrL is run from.";
+				println!("{}", msg);
+				let _ = sdl2::messagebox::show_simple_message_box(
+					sdl2::messagebox::MessageBoxFlag::ERROR, "YarrL", msg, None);
+				return;
+			},
+		}
+	}
+	let font_refs = fonts.iter().map(|(f, sf)| (f, sf)).collect();
+	let mut gui = GameUI::init(font_refs, config.font_scale)
 		.expect("Error initializing GameUI object.");
+	gui.plain_glyphs = config.plain_glyphs;
+	gui.faded_charts = config.faded_charts;
 
 	title_screen(&mut gui);
 
@@ -1855,23 +5512,38 @@ fn start_game() {
 
 	if new_game {
 		show_character_sheet(&state, &mut gui);
-		generate_world(&mut state, &mut items, &mut ships);
+		generate_world(&mut state, &mut items, &mut ships, config.spawn_density);
 		prologue(&state, &mut gui);
         state.calc_vision_radius();
 	}
 
-	match run(&mut gui, &mut state, &mut items, &mut ships) {
+	match run(&mut gui, &mut state, &mut items, &mut ships, &config) {
 		Ok(_) => println!("Game over I guess? Probably the player won?!"),
 		Err(ExitReason::Save) => save_msg(&mut state, &mut gui),
-		Err(ExitReason::Quit) => quit_msg(&mut state, &mut gui),
-		Err(ExitReason::Win) => victory_msg(&mut state, &mut gui),
-		Err(ExitReason::Death(src)) => death(&mut state, src, &mut gui),
+		Err(ExitReason::Quit) => {
+			delete_autosave(&state.player.name);
+			quit_msg(&mut state, &mut gui);
+		},
+		Err(ExitReason::Win) => {
+			delete_autosave(&state.player.name);
+			victory_msg(&mut state, &mut gui);
+		},
+		Err(ExitReason::Death(src)) => {
+			delete_autosave(&state.player.name);
+			death(&mut state, src, &mut gui);
+		},
 	}
 }
 
 fn run(gui: &mut GameUI, state: &mut GameState, 
-		items: &mut HashMap<u8, ItemsTable>, ships: &mut HashMap<u8, ShipsTable>) -> Result<(), ExitReason> {
-
+		items: &mut HashMap<u8, ItemsTable>, ships: &mut HashMap<u8, ShipsTable>,
+		config: &Config) -> Result<(), ExitReason> {
+
+	state.verbosity = config.verbosity;
+	state.terrain_costs = config.terrain_costs;
+	state.show_damage = config.show_damage;
+	state.relight_torches = config.relight_torches;
+	state.escape_ending = config.endgame == Endgame::Escape;
 	state.write_msg_buff(&format!("Welcome, {}!", state.player.name));
 	let curr_ships = ships.get(&state.map_id).unwrap();
 	gui.v_matrix = fov::calc_v_matrix(state, items.get(&state.map_id).unwrap(), curr_ships, 
@@ -1887,17 +5559,21 @@ fn run(gui: &mut GameUI, state: &mut GameState,
 
 		if state.player.charmed {
 			action_while_charmed(state, items, map_ships, gui)?;
+		} else if state.fleeing > 0 {
+			flee_step(state, map_items, map_ships, gui)?;
 		} else {
 			let cmd = gui.get_command(&state);
 			match cmd {
-				Cmd::Quit => confirm_quit(state, gui)?,
+				Cmd::Quit => confirm_quit(state, items, ships, gui)?,
 				Cmd::Move(dir) => do_move(state, map_items, map_ships, &dir, gui)?,
 				Cmd::MsgHistory => show_message_history(state, gui),
+				Cmd::ShowLedger => show_ledger(state, gui),
 				Cmd::DropItem => drop_item(state, map_items, gui),
 				Cmd::PickUp => pick_up(state, map_items, gui)?,
 				Cmd::ShowInventory => show_inventory(state, gui),
 				Cmd::ShowCharacterSheet => show_character_sheet(state, gui),
 				Cmd::ToggleEquipment => toggle_equipment(state, gui),
+				Cmd::AutoEquip => auto_equip(state),
 				Cmd::ToggleAnchor => {
 					if toggle_anchor(state, map_ships) {
 						sail(state, map_ships)?;
@@ -1927,15 +5603,56 @@ fn run(gui: &mut GameUI, state: &mut GameState,
 				Cmd::Quaff => quaff(state, gui),
 				Cmd::Eat => eat(state, gui),
 				Cmd::FireGun => fire_gun(state, gui, map_items, map_ships),
+				Cmd::QuickFire => quick_fire(state, gui, map_items, map_ships),
 				Cmd::Reload => reload(state),
 				Cmd::WorldMap => gui.show_world_map(state),
 				Cmd::Search => search(state, map_items),
 				Cmd::Read => read(state, gui),
 				Cmd::Save => save_and_exit(state, items, ships, gui)?,
-                Cmd::EnterPortal => enter_portal(state, items, map_ships, gui),
-				Cmd::Chat => chat_with_npc(state, gui),
-                Cmd::Use => use_item(state, gui),
-				Cmd::Help => show_help(gui),
+                Cmd::EnterPortal => enter_portal(state, items, ships, gui),
+				Cmd::Chat => chat_with_npc(state, map_ships, gui),
+                Cmd::Use => use_item(state, map_items, map_ships, gui),
+				Cmd::Help => show_help(state, map_ships, gui),
+				Cmd::Hold => use_hold(state, map_ships, gui),
+				Cmd::Bestiary => show_bestiary(state, gui),
+				Cmd::Throw => throw_item(state, map_items, gui),
+				Cmd::Carpentry => carpentry(state, map_ships, gui),
+				Cmd::Salvage => salvage(state),
+				Cmd::DisarmTrap => disarm_trap(state, gui),
+				Cmd::ExamineGround => examine_ground(state, map_items, gui),
+				Cmd::Give => give_item(state, gui),
+				Cmd::ToggleRide => toggle_ride(state, map_ships, gui),
+				Cmd::Peek => peek(state, map_items, gui),
+				Cmd::Flee => start_fleeing(state, map_items, map_ships, gui)?,
+				Cmd::KedgeOff => kedge_off(state, map_ships),
+				Cmd::StepAshore => step_ashore(state, map_ships, gui),
+				Cmd::ScanHorizon => scan_horizon(state, map_ships),
+				Cmd::HandOverHelm => hand_over_helm(state, map_ships),
+				Cmd::Redraw => { },
+				Cmd::CycleFontScale => {
+					if let Err(e) = gui.cycle_font_scale() {
+						state.write_msg_buff(&format!("Couldn't change the text size: {}", e));
+					}
+				},
+				Cmd::DumpMap => {
+					if config.debug {
+						dump_map(state, map_items);
+					}
+				},
+				Cmd::RegenIsland => {
+					if config.debug && state.map_id == 0 {
+						let island = regenerate_island(state, items, ships, config.spawn_density);
+						state.write_msg_buff(&format!("Island {} regenerated.", island));
+					}
+				},
+				Cmd::ToggleSafeStep => {
+					state.safe_step = !state.safe_step;
+					if state.safe_step {
+						state.write_msg_buff("Safe step on: you'll be asked before stepping into danger.");
+					} else {
+						state.write_msg_buff("Safe step off.");
+					}
+				},
 			}
 		}
 
@@ -1947,36 +5664,64 @@ fn run(gui: &mut GameUI, state: &mut GameState,
 		if state.turn > start_turn {
 			if let Some(drained) = state.player.inventory.check_fueled_items() {
 				for i in drained {
-					let s = format!("Your {} has gone out.", i.name);
-					state.write_msg_buff(&s);
+					if i.name == "torch" && state.relight_torches 
+							&& state.player.inventory.light_spare_torch() {
+						state.write_msg_buff("You light a fresh torch from the dying embers.");
+					} else {
+						let s = format!("Your {} has gone out.", i.name);
+						state.write_msg_buff(&s);
+					}
 				}
 			}
 
+			state.crew_aboard = crew_aboard(state, map_ships);
             state.calc_vision_radius();
+			shift_wind(state);
 			check_environment_hazards(state, map_ships)?;
 
-			let ids = state.npcs[&state.map_id].all_npc_ids();
-			for id in ids {
-				match state.npcs.get_mut(&state.map_id).unwrap().npc_with_id(id) {
-					Some(mut npc) => {
-						let d = util::cartesian_d(npc.row, npc.col, state.player.row, state.player.col);
-						if d < 75 { 
-							let prev_r = npc.row;
-							let prev_c = npc.col;
-							npc.act(state, map_ships)?;
+			// Slow going on foot gives the monsters a chance to catch up
+			let npc_moves = 1 + state.extra_npc_moves;
+			state.extra_npc_moves = 0;
+			for _ in 0..npc_moves {
+				let ids = state.npcs[&state.map_id].all_npc_ids();
+				for id in ids {
+					match state.npcs.get_mut(&state.map_id).unwrap().npc_with_id(id) {
+						Some(mut npc) => {
+							let d = util::cartesian_d(npc.row, npc.col, state.player.row, state.player.col);
+							if d < 75 { 
+								let prev_r = npc.row;
+								let prev_c = npc.col;
+								npc.act(state, map_ships)?;
+								if burn_npc(state, &mut npc) {
+									npc.killed = true;
+								}
 							
-							if npc.killed {
-								state.npcs.get_mut(&state.map_id)
-										.unwrap()
-										.remove(npc.id, npc.row, npc.col);
-							} else {
-								state.npcs.get_mut(&state.map_id)
-										.unwrap()
-										.update(npc, prev_r, prev_c);
+								if npc.killed {
+									state.npcs.get_mut(&state.map_id)
+											.unwrap()
+											.remove(npc.id, npc.row, npc.col);
+								} else {
+									state.npcs.get_mut(&state.map_id)
+											.unwrap()
+											.update(npc, prev_r, prev_c);
+								}
 							}
-						}
-					},
-					None => { continue; }
+						},
+						None => { continue; }
+					}
+				}
+			}
+
+			// The monster might have moved off, or been killed by something 
+			// else, since the player parried it
+			if let Some((r, c)) = state.riposte.take() {
+				let hostile = match state.npcs.get_mut(&state.map_id).unwrap().npc_at(r, c) {
+					Some(npc) => npc.hostile,
+					None => false,
+				};
+				if hostile && util::cartesian_d(r, c, state.player.row, state.player.col) < 2 {
+					state.write_msg_buff("You riposte!");
+					attack_npc(state, items.get_mut(&state.map_id).unwrap(), r, c, gui);
 				}
 			}
 
@@ -1992,7 +5737,10 @@ fn run(gui: &mut GameUI, state: &mut GameState,
 
 			if state.player.charmed {
 				let verve_mod = Player::mod_for_stat(state.player.verve);
-				let bonus = f32::round(state.player.drunkeness as f32 / 5.0) as i8;
+				let mut bonus = f32::round(state.player.drunkeness as f32 / 5.0) as i8;
+				if state.player.inventory.equiped_captains_hat() {
+					bonus += 2;
+				}
 				if do_ability_check(verve_mod, 14, bonus) {
 					state.write_msg_buff("You snap out of it!");
 					state.player.charmed = false;
@@ -2003,31 +5751,53 @@ fn run(gui: &mut GameUI, state: &mut GameState,
 				state.player.drunkeness -= 1;
 			}
 
-			if state.turn % 25 == 0 {
+			let thirst = state.player.thirst_level();
+			if thirst == 0 && state.turn.is_multiple_of(25) {
 				state.player.add_stamina(1);
 			}
+			check_thirst(state)?;
+			check_scurvy(state);
 
 			// check for beached ships
+			helmsman_sails(state, map_ships)?;
 			check_drifting_ships(state, map_ships);
+			check_merchant_ships(state, map_ships);
+			check_naval_ships(state, map_ships)?;
 
-			if state.turn % 89 == 0 {
-				let ids = state.weather.keys()
-						.map(|v| v.clone())
-						.collect::<Vec<u8>>();
+			state.check_landfall();
+			state.decay_corpses();
+			state.burn_out_fires();
+			tend_signal_fire(state);
+			ambient_msg(state);
+			drift_bottles(state, items.get_mut(&0).unwrap());
 
-				for id in ids {
-					let map_id = state.map_id;
-					state.weather.get_mut(&id).unwrap().update(&state.map[&map_id]);
-				}
+			let ids = state.weather.keys()
+					.copied()
+					.collect::<Vec<u8>>();
+
+			for id in ids {
+				let turn = state.turn;
+				state.weather.get_mut(&id).unwrap().check_for_update(turn, &state.map[&id]);
 			}
 		}
+
+		// Only check the interval boundary here since some commands take more
+		// than a single turn
+		if config.autosave_interval > 0 
+				&& state.turn / config.autosave_interval > start_turn / config.autosave_interval {
+			autosave(state, items, ships, gui);
+		}
 	
 		let map_items = items.get(&state.map_id).unwrap();
+		let map_ships = ships.get(&state.map_id).unwrap();
 		gui.v_matrix = fov::calc_v_matrix(state, map_items, map_ships, FOV_HEIGHT, FOV_WIDTH);
+		announce_approaching(state, gui);
 		let sbi = state.curr_sidebar_info();
 		gui.write_screen(&mut state.msg_buff, &sbi);
 		
 		state.msg_buff.drain(..);
+		// A peek only lets the player see around the corner for the one turn
+		state.peek = None;
     }
 }
 
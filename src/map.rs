@@ -37,6 +37,7 @@ pub enum Tile {
 	Player((u8, u8, u8)),
 	Water,
 	DeepWater,
+	Reef,
 	WorldEdge,
 	Sand,
 	Mountain,
@@ -50,21 +51,25 @@ pub enum Tile {
 	Shipwreck(char, String),
 	Mast(char),
 	Bullet(char),
+	Cursor(char),
 	Lava,
 	FirePit,
 	OldFirePit,
 	Floor,
 	Window(char),
+	Barricade(u8),
 	Spring,
     Portal((usize, usize, u8)),
     Fog,
 	BoulderTrap((u8, u8, u8), bool, bool, (usize, usize), (i32, i32)),
+	Decal((u8, u8, u8), char), // ie., bloodstains, debris. Only ever drawn, never on the map itself
 }
 
 pub fn all_passable() -> HashSet<Tile> {
 	let mut passable = HashSet::new();
 	passable.insert(Tile::Water);
 	passable.insert(Tile::DeepWater);
+	passable.insert(Tile::Reef);
 	passable.insert(Tile::Grass);
 	passable.insert(Tile::Tree);
 	passable.insert(Tile::Dirt);
@@ -80,6 +85,26 @@ pub fn all_passable() -> HashSet<Tile> {
 	passable 
 }
 
+// How deep the water is, compared against a ship's draft to see if she'll
+// run aground
+pub fn depth(tile: &Tile) -> u8 {
+	match tile {
+		Tile::DeepWater => 3,
+		Tile::Water | Tile::Reef => 1,
+		_ => 0,
+	}
+}
+
+// How many turns, on average, it takes to cross a square on foot. Pushing 
+// through the trees or slogging through soft sand is hard going.
+pub fn move_cost(tile: &Tile) -> f32 {
+	match tile {
+		Tile::Tree => 2.0,
+		Tile::Sand => 1.5,
+		_ => 1.0,
+	}
+}
+
 // Probably at some point in the dev process, I'll need to begin 
 // storing the map in a struct with extra info instead of just
 // a matrix of Tiles. Then, I won't have to recalculate height and
@@ -94,7 +119,7 @@ pub fn in_bounds(map: &Vec<Vec<Tile>>, r: i32, c: i32) -> bool {
 pub fn is_clear(tile: &Tile) -> bool {
 	match tile {
 		Tile::Wall | Tile::Blank | Tile::Mountain | Tile::SnowPeak |
-			Tile::WoodWall => false,
+			Tile::WoodWall | Tile::Barricade(_) => false,
 		_ => true,
 	}
 }
@@ -103,11 +128,35 @@ pub fn is_passable(tile: &Tile) -> bool {
 	match tile {
 		Tile::Wall | Tile::Blank | Tile::WorldEdge |
 		Tile::Mountain | Tile::SnowPeak | Tile::Gate |
-		Tile::WoodWall | Tile::Window(_) => false,
+		Tile::WoodWall | Tile::Window(_) | Tile::Barricade(_) => false,
 		_ => true,
 	}
 }
 
+pub fn is_land(tile: &Tile) -> bool {
+	!matches!(tile, Tile::Water | Tile::DeepWater | Tile::Reef | Tile::WorldEdge | Tile::Blank)
+}
+
+fn is_wall(tile: &Tile) -> bool {
+	*tile == Tile::Wall || *tile == Tile::WoodWall
+}
+
+// Windows, and gaps in a wall that a body can walk through, can be boarded up
+pub fn can_barricade(map: &[Vec<Tile>], r: usize, c: usize) -> bool {
+	match map[r][c] {
+		Tile::Window(_) => return true,
+		Tile::Water | Tile::DeepWater | Tile::Reef | Tile::Lava | Tile::Portal(_) => return false,
+		_ => if !is_passable(&map[r][c]) { return false; },
+	}
+
+	if r == 0 || c == 0 || r >= map.len() - 1 || c >= map[0].len() - 1 {
+		return false;
+	}
+
+	(is_wall(&map[r - 1][c]) && is_wall(&map[r + 1][c]))
+		|| (is_wall(&map[r][c - 1]) && is_wall(&map[r][c + 1]))
+}
+
 fn val_to_terrain(val: f32) -> Tile {
 	if val < -0.5 {
 		return Tile::DeepWater;
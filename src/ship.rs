@@ -13,10 +13,13 @@
 // You should have received a copy of the GNU General Public License
 // along with YarrL.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::collections::HashSet;
+
 use rand::Rng;
 
 use serde::{Serialize, Deserialize};
 use crate::dice;
+use crate::items::Item;
 use crate::util;
 use crate::util::capitalize_word;
 
@@ -32,6 +35,12 @@ pub const BOW_N: char = '\u{25B2}';
 pub const BOW_S: char = '\u{25BC}';
 pub const AFT_STRAIGHT: char = '\u{25A0}'; 
 pub const AFT_ANGLE: char = '\u{25C6}'; 
+pub const RAFT: char = '=';
+pub const HOLD_CAPACITY: u16 = 250;
+pub const MAX_HULL: u8 = 20;
+pub const RAFT_HULL: u8 = 6;
+// One line per letter of the hold menu
+const MAX_HOLD_LINES: usize = 26;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Ship {
@@ -49,6 +58,18 @@ pub struct Ship {
 	pub bearing: u8,
 	pub anchored: bool,
 	pub prev_move: (i8, i8),
+	#[serde(default = "full_hull")]
+	pub hull: u8,
+	#[serde(default)]
+	pub hold: Vec<Item>,
+	#[serde(default)]
+	pub merchant: bool,
+	// A makeshift one-square vessel lashed together by a stranded player
+	#[serde(default)]
+	pub raft: bool,
+	// A Yendorian warship hunting the player once they have the treasure
+	#[serde(default)]
+	pub naval: bool,
 }
 
 impl Ship {
@@ -68,10 +89,147 @@ impl Ship {
 			bearing: 0,
 			anchored: true,
 			prev_move: (0, 0),
+			hull: MAX_HULL,
+			hold: Vec::new(),
+			merchant: false,
+			raft: false,
+			naval: false,
 	 	}
 	}
 
+	pub fn new_raft() -> Ship {
+		let mut raft = Ship::new(String::from("raft"));
+		raft.raft = true;
+		raft.hull = RAFT_HULL;
+
+		raft
+	}
+
+	pub fn hold_weight(&self) -> u16 {
+		self.hold.iter().map(|i| i.weight as u16).sum()
+	}
+
+	// There are only so many letters for the hold menu, so everything in the 
+	// hold has to fit on that many lines
+	pub fn has_hold_line_for(&self, item: &Item) -> bool {
+		if item.stackable && self.hold.iter().any(|i| i.stackable && i == item) {
+			return true;
+		}
+
+		self.hold_lines().len() < MAX_HOLD_LINES
+	}
+
+	// How deep she sits in the water. With her hold more than half full she's
+	// too low to make it through the shallows.
+	pub fn draft(&self) -> u8 {
+		if self.hold_weight() > HOLD_CAPACITY / 2 { 2 } else { 1 }
+	}
+
+	// Stackable things like doubloons get lumped together on one line otherwise
+	// a decent haul would run off the bottom of the menu. Each entry is the 
+	// indices in the hold of the items on that line.
+	fn hold_lines(&self) -> Vec<Vec<usize>> {
+		let mut lines: Vec<Vec<usize>> = Vec::new();
+
+		for j in 0..self.hold.len() {
+			let item = &self.hold[j];
+			let mut grouped = false;
+			if item.stackable {
+				for line in lines.iter_mut() {
+					if self.hold[line[0]] == *item {
+						line.push(j);
+						grouped = true;
+						break;
+					}
+				}
+			}
+
+			if !grouped {
+				lines.push(vec![j]);
+			}
+		}
+
+		lines
+	}
+
+	pub fn get_hold_menu(&self) -> Vec<String> {
+		let mut menu = Vec::new();
+		let lines = self.hold_lines();
+
+		for (j, line) in lines.iter().take(MAX_HOLD_LINES).enumerate() {
+			let mut s = String::from("");
+			s.push((b'a' + j as u8) as char);
+			s.push_str(") ");
+			s.push_str(&self.hold[line[0]].name);
+			if line.len() > 1 {
+				s.push_str(" x");
+				s.push_str(&line.len().to_string());
+			}
+
+			menu.push(s);
+		}
+
+		menu
+	}
+
+	// Like ItemsTable::get_many_at(), the caller is responsible for sending 
+	// menu lines that actually exist
+	pub fn take_from_hold(&mut self, picks: &HashSet<u8>) -> Vec<Item> {
+		let lines = self.hold_lines();
+		let mut indices = Vec::new();
+		for p in picks {
+			if let Some(line) = lines.get(*p as usize) {
+				indices.extend(line);
+			}
+		}
+		indices.sort();
+		indices.reverse();
+
+		let mut items = Vec::new();
+		for i in indices {
+			items.push(self.hold.remove(i));
+		}
+
+		items
+	}
+
+	// The item on a line of the hold menu, and how many of them there are
+	pub fn hold_line_item(&self, line: usize) -> Option<(Item, usize)> {
+		let lines = self.hold_lines();
+		lines.get(line).map(|l| (self.hold[l[0]].clone(), l.len()))
+	}
+
+	pub fn take_from_hold_line(&mut self, line: usize, count: usize) -> Vec<Item> {
+		let lines = self.hold_lines();
+		let mut indices = match lines.get(line) {
+			Some(l) => l.iter().take(count).copied().collect::<Vec<usize>>(),
+			None => Vec::new(),
+		};
+		indices.sort();
+		indices.reverse();
+
+		indices.iter().map(|i| self.hold.remove(*i)).collect()
+	}
+
+	pub fn occupies(&self, row: usize, col: usize) -> bool {
+		(row == self.row && col == self.col) || (row == self.bow_row && col == self.bow_col)
+			|| (row == self.aft_row && col == self.aft_col)
+	}
+
 	pub fn update_loc_info(&mut self) {
+		// A raft has no bow or stern to speak of, so they're the same square 
+		// as her deck
+		if self.raft {
+			self.bow_row = self.row;
+			self.bow_col = self.col;
+			self.aft_row = self.row;
+			self.aft_col = self.col;
+			self.bow_ch = RAFT;
+			self.aft_ch = RAFT;
+			self.deck_ch = RAFT;
+			return;
+		}
+
 		let boat_tiles: (char, i8, i8, char, i8, i8, char);
 		if self.bearing == 0 || self.bearing == 1 || self.bearing == 15 { 
 			boat_tiles = (BOW_N, -1, 0, AFT_STRAIGHT, 1, 0, DECK_STRAIGHT);
@@ -101,6 +259,10 @@ impl Ship {
 	}
 }
 
+fn full_hull() -> u8 {
+	MAX_HULL
+}
+
 pub fn random_name(allow_ys: bool) -> String {
 	let mut name = String::from("");
 	let ns = util::read_names_file();
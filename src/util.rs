@@ -57,12 +57,27 @@ pub fn get_articled_name(definite: bool, item: &Item) -> String {
 	}
 }
 
+// A small stand-in for names.txt, so a missing file doesn't stop the game
+fn default_name_seeds() -> NameSeeds {
+	let to_strings = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+
+	NameSeeds {
+		adjectives: to_strings(&["salty", "black", "stormy", "silent", "wandering", "drunken"]),
+		nouns: to_strings(&["gull", "kraken", "mermaid", "cutlass", "barnacle", "widow"]),
+		proper_nouns: to_strings(&["Bartholomew", "Grace", "Flint", "Anne", "Edward", "Mary"]),
+	}
+}
+
 pub fn read_names_file() -> NameSeeds {
 	let mut ns = NameSeeds::new();
 
-	let contents = fs::read_to_string("names.txt")
-        .expect("Unable to find names file!"); 	// I should probably shoot a warning and 
-												// a return a small default version of NS
+	let contents = match fs::read_to_string("names.txt") {
+		Ok(contents) => contents,
+		Err(_) => {
+			println!("Unable to find names.txt, using a few built-in names instead.");
+			return default_name_seeds();
+		},
+	};
 
 	let mut reading = 0;
 	for line in contents.split('\n') {
@@ -126,6 +141,38 @@ pub fn bresenham_circle(rc: i32, cc: i32, radius: i32) -> Vec<(i32, i32)> {
 	pts
 }
 
+// The standard all-octants version of Bresenham's line algorithm. The 
+// returned points include both endpoints.
+pub fn bresenham_line(r0: i32, c0: i32, r1: i32, c1: i32) -> Vec<(i32, i32)> {
+	let mut pts = Vec::new();
+	let delta_r = (r1 - r0).abs();
+	let delta_c = (c1 - c0).abs();
+	let r_step = if r0 < r1 { 1 } else { -1 };
+	let c_step = if c0 < c1 { 1 } else { -1 };
+	let mut error = delta_c - delta_r;
+	let mut r = r0;
+	let mut c = c0;
+
+	loop {
+		pts.push((r, c));
+		if r == r1 && c == c1 {
+			break;
+		}
+
+		let e2 = error * 2;
+		if e2 > -delta_r {
+			error -= delta_r;
+			c += c_step;
+		}
+		if e2 < delta_c {
+			error += delta_c;
+			r += r_step;
+		}
+	}
+
+	pts
+}
+
 pub fn rnd_adj() -> (i32, i32) {
 	let x = roll(8, 1, 0);
 	if x == 1 { return (-1, -1); }
@@ -19,21 +19,39 @@ use rand::Rng;
 use serde::{Serialize, Deserialize};
 
 use crate::map::{in_bounds, Tile};
-use crate::util::bresenham_circle;
+use crate::util::{bresenham_circle, cartesian_d};
 
 // Currently, weather consists only of fog
 
+const UPDATE_INTERVAL: u32 = 89;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Weather {
     pub systems: Vec<WeatherSystem>,
     pub clouds: HashSet<(usize, usize)>,
+	// Older save files won't have this, in which case we fall back on the
+	// weather having last changed on the most recent multiple of the interval
+	#[serde(default)]
+	last_update: Option<u32>,
 }
 
 impl Weather {
     pub fn new() -> Weather {
-        Weather { systems:Vec::new(), clouds: HashSet::new() }
+        Weather { systems:Vec::new(), clouds: HashSet::new(), last_update: Some(0) }
     }
 
+	// Some actions take more than one turn, so rather than hoping we land
+	// exactly on an update turn, catch up on however many updates are owed
+	// since the last one.
+	pub fn check_for_update(&mut self, turn: u32, map: &Vec<Vec<Tile>>) {
+		let mut last = self.last_update.unwrap_or(turn - turn % UPDATE_INTERVAL);
+		while turn >= last + UPDATE_INTERVAL {
+			last += UPDATE_INTERVAL;
+			self.update(map);
+		}
+		self.last_update = Some(last);
+	}
+
 	pub fn update(&mut self, map: &Vec<Vec<Tile>>) {
 		let mut updated = Vec::new();
 
@@ -51,6 +69,32 @@ impl Weather {
 		self.calc_clouds(map);
 	}
 
+	// The centre and radius of each weather system
+	pub fn system_locs(&self) -> Vec<(usize, usize, i32)> {
+		self.systems.iter().map(|s| (s.row, s.col, s.radius)).collect()
+	}
+
+	// The weather system bearing down hardest on the given square, ie. the one
+	// that's thickest relative to how far off its edge is. Returns its centre,
+	// how far the square is from its edge (0 if we're already inside it) and 
+	// its intensity.
+	pub fn nearest_system(&self, row: usize, col: usize, range: usize) -> Option<((usize, usize), usize, f32)> {
+		let mut best = None;
+		let mut best_score = 0.0;
+		for s in &self.systems {
+			let d = cartesian_d(row, col, s.row, s.col);
+			let gap = d.saturating_sub(s.radius as usize);
+			if gap > range { continue; }
+			let score = s.intensity / (gap + 1) as f32;
+			if score > best_score {
+				best_score = score;
+				best = Some(((s.row, s.col), gap, s.intensity));
+			}
+		}
+
+		best
+	}
+
     pub fn calc_clouds(&mut self, map: &Vec<Vec<Tile>>) {
         self.clouds.clear();
     